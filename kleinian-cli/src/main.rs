@@ -1,11 +1,135 @@
-use clap::{Arg, Command};
+mod plotter;
+mod poster;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use image::codecs::png::PngEncoder;
 use image::{ExtendedColorType, ImageEncoder};
-use kleinian::Cpx;
+use kleinian::cache::PointCache;
+use kleinian::fuchsian;
+use kleinian::nielsen::NielsenMove;
+use kleinian::partition::{self, Density, Partition};
+use kleinian::plan::{LimitSet, Target};
+use kleinian::pointcloud;
+use kleinian::postprocess;
+use kleinian::presets::{self, Symmetry};
+use kleinian::raster::{generate_into_bitmap, pack_rgba};
+use kleinian::render::{self, render_equirect_rgba, tone_map, Palette, Style};
+use kleinian::seed::SeedCircles;
+use kleinian::subgroup::{self, CosetTable};
+#[cfg(feature = "parquet")]
+use kleinian::table;
+use kleinian::{CircleQueue, Cpx, TabRoot};
+use plotter::PlotOptions;
 use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process;
+
+/// The number of points used to fit the window with `--direct`.
+const DIRECT_SAMPLE: usize = 10000;
+
+/// Returns the output formats.  The arrow and parquet formats are only
+/// available with the `parquet` feature.
+fn formats() -> Vec<&'static str> {
+    let mut formats = vec!["png", "gcode", "hpgl", "ply", "xyz"];
+    if cfg!(feature = "parquet") {
+        formats.extend(["arrow", "parquet"]);
+    }
+    formats.extend(["glb", "sensitivity", "points", "density"]);
+    formats
+}
+
+/// Parses a list of words generating a subgroup of finite index.
+fn parse_subgroup(s: &str) -> Result<CosetTable, String> {
+    let words = s
+        .split(',')
+        .map(|w| kleinian::parse_letters(w.trim(), 2).ok_or_else(|| format!("invalid word: {}", w)))
+        .collect::<Result<Vec<_>, _>>()?;
+    CosetTable::from_words(&words).map_err(|e| e.to_string())
+}
+
+/// Explores the subgroup until there are at least `n` circles.
+fn explore_subgroup(
+    gens: &[kleinian::Generator; 4],
+    table: &CosetTable,
+    n: usize,
+) -> Result<CircleQueue, kleinian::Error> {
+    let mut queue = subgroup::subgroup_queue(gens, table)?;
+    while queue.len() < n {
+        queue.advance()?;
+    }
+    Ok(queue)
+}
+
+/// Parses a chunk of a partition, given as INDEX/COUNT.
+fn parse_chunk(s: &str) -> Result<(usize, usize), String> {
+    let (i, k) = s
+        .split_once('/')
+        .ok_or_else(|| "expected INDEX/COUNT".to_string())?;
+    let i: usize = i.parse().map_err(|e| format!("invalid index: {}", e))?;
+    let k: usize = k.parse().map_err(|e| format!("invalid count: {}", e))?;
+    if i >= k {
+        return Err("the index must be less than the count".to_string());
+    }
+    Ok((i, k))
+}
+
+/// Merges the files written for the chunks of a partition.
+fn merge(matches: &ArgMatches) {
+    let filename: &String = matches.get_one("outfile").unwrap();
+    let open = |name: &String| File::open(name).unwrap_or_else(|e| clap::Error::from(e).exit());
+    let parts = matches.get_many::<String>("parts").unwrap();
+    if matches.get_flag("points") {
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        partition::merge_points(parts.map(open), f).unwrap_or_else(|e| clap::Error::from(e).exit());
+        return;
+    }
+    let mut total: Option<Density> = None;
+    for name in parts {
+        let d = Density::read_from(open(name)).unwrap_or_else(|e| clap::Error::from(e).exit());
+        match &mut total {
+            Some(t) if (t.width, t.height) != (d.width, d.height) => {
+                eprintln!("error: {} has a different size from the other parts", name);
+                process::exit(1);
+            }
+            Some(t) => t.add(&d),
+            None => total = Some(d),
+        }
+    }
+    let total = total.unwrap();
+    let pixel_data = tone_map(&total.counts, &Style::default());
+    let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+    PngEncoder::new(f)
+        .write_image(
+            &pixel_data,
+            total.width as u32,
+            total.height as u32,
+            ExtendedColorType::Rgba8,
+        )
+        .unwrap();
+}
+
+fn fail(e: kleinian::Error) -> ! {
+    eprintln!("error: {}", e);
+    process::exit(1)
+}
 
 fn main() {
     let matches = Command::new("kleinian")
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(poster::command())
+        .subcommand(
+            Command::new("merge")
+                .about("Merge the density files written for the chunks of a partition into a png")
+                .arg(Arg::new("outfile").required(true))
+                .arg(Arg::new("parts").required(true).num_args(1..))
+                .arg(
+                    Arg::new("points")
+                        .long("points")
+                        .action(ArgAction::SetTrue)
+                        .help("Merge point files, given in order, into a point file instead"),
+                ),
+        )
         .arg(
             Arg::new("width")
                 .required(true)
@@ -46,7 +170,216 @@ fn main() {
                 .value_parser(clap::value_parser!(usize)),
         )
         .arg(Arg::new("outfile").required(true))
+        .arg(
+            Arg::new("preset")
+                .long("preset")
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    presets::PRESETS.iter().map(|p| p.name),
+                ))
+                .help("Draw a named preset, whose traces and recipe replace re1, im1, re2, and im2"),
+        )
+        .arg(
+            Arg::new("root")
+                .long("root")
+                .value_parser(["minus", "plus"])
+                .default_value("minus")
+                .help("Root of the equation for tr ab; plus gives the other marking of the group, with different circles"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(formats())
+                .default_value("png")
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("feed-rate")
+                .long("feed-rate")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("1000")
+                .help("Feed rate for plotter output, in mm/min"),
+        )
+        .arg(
+            Arg::new("bed-width")
+                .long("bed-width")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("200")
+                .help("Width of the plotter bed, in mm"),
+        )
+        .arg(
+            Arg::new("bed-height")
+                .long("bed-height")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("200")
+                .help("Height of the plotter bed, in mm"),
+        )
+        .arg(
+            Arg::new("circles")
+                .long("circles")
+                .action(ArgAction::SetTrue)
+                .help("Also draw the circle outlines in plotter output"),
+        )
+        .arg(
+            Arg::new("projection")
+                .long("projection")
+                .value_parser(["plane", "equirect"])
+                .default_value("plane")
+                .help("Projection for png output; equirect draws the whole Riemann sphere"),
+        )
+        .arg(
+            Arg::new("lift")
+                .long("lift")
+                .value_parser(["plane", "sphere"])
+                .default_value("plane")
+                .help("Surface on which point cloud output lies"),
+        )
+        .arg(
+            Arg::new("color-by")
+                .long("color-by")
+                .value_parser(["uniform", "generator", "depth", "radius"])
+                .default_value("depth")
+                .help("Coloring of the points in point cloud output"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_parser(["commutator", "isometric"])
+                .default_value("commutator")
+                .help("Heuristic for the circle of each generator"),
+        )
+        .arg(
+            Arg::new("nielsen")
+                .long("nielsen")
+                .action(ArgAction::Append)
+                .value_parser(["swap", "invert-a", "invert-b", "multiply-a", "multiply-b"])
+                .help("Change the generators by a Nielsen move; may be repeated, applied in order"),
+        )
+        .arg(
+            Arg::new("subgroup")
+                .long("subgroup")
+                .value_parser(parse_subgroup)
+                .help("Explore the subgroup of finite index generated by these comma-separated words in a, b, A, B"),
+        )
+        .arg(
+            Arg::new("conjugate")
+                .long("conjugate")
+                .value_parser(clap::value_parser!(u64))
+                .help("Conjugate the group by a random transformation with this seed, to change the framing"),
+        )
+        .arg(
+            Arg::new("wildness")
+                .long("wildness")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("0.3")
+                .help("Size of the random transformation for --conjugate"),
+        )
+        .arg(
+            Arg::new("density")
+                .long("density")
+                .action(ArgAction::SetTrue)
+                .help("Shade png output by the number of points in each pixel"),
+        )
+        .arg(
+            Arg::new("bloom")
+                .long("bloom")
+                .value_parser(clap::value_parser!(f32))
+                .help("Add a glow of this strength around the points; implies --density"),
+        )
+        .arg(
+            Arg::new("bloom-radius")
+                .long("bloom-radius")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("4")
+                .help("Radius of the glow for --bloom, in pixels"),
+        )
+        .arg(
+            Arg::new("sharpen")
+                .long("sharpen")
+                .value_parser(clap::value_parser!(f32))
+                .help("Sharpen the density with an unsharp mask of this strength; implies --density"),
+        )
+        .arg(
+            Arg::new("sharpen-radius")
+                .long("sharpen-radius")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("1")
+                .help("Radius of the unsharp mask for --sharpen, in pixels"),
+        )
+        .arg(
+            Arg::new("disk")
+                .long("disk")
+                .action(ArgAction::SetTrue)
+                .help("Conjugate a group that preserves a circle so that the circle is the unit circle"),
+        )
+        .arg(
+            Arg::new("direct")
+                .long("direct")
+                .action(ArgAction::SetTrue)
+                .help("Draw png output directly into the image without storing the points"),
+        )
+        .arg(
+            Arg::new("budget")
+                .long("budget")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(f64))
+                .help("Choose how to draw png output so that it takes about this long"),
+        )
+        .arg(
+            Arg::new("memory")
+                .long("memory")
+                .value_name("MB")
+                .value_parser(clap::value_parser!(usize))
+                .requires("budget")
+                .help("Memory that may be used with --budget, in megabytes"),
+        )
+        .arg(
+            Arg::new("chunk")
+                .long("chunk")
+                .value_parser(parse_chunk)
+                .help("Only write the chunk INDEX/COUNT of points or density output, to be merged with the merge subcommand"),
+        )
+        .arg(
+            Arg::new("chunk-depth")
+                .long("chunk-depth")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("8")
+                .help("Number of letters by which words are grouped into chunks"),
+        )
+        .arg(
+            Arg::new("cache")
+                .long("cache")
+                .help("Directory in which to cache generated points, to skip exploring the same group again"),
+        )
+        .arg(
+            Arg::new("validate")
+                .long("validate")
+                .action(ArgAction::SetTrue)
+                .help("Check the generators and their circles before exploring the group"),
+        )
+        .arg(
+            Arg::new("score")
+                .long("score")
+                .action(ArgAction::SetTrue)
+                .help("Report how interesting the picture of the group is likely to be"),
+        )
+        .arg(
+            Arg::new("systole")
+                .long("systole")
+                .value_parser(clap::value_parser!(usize))
+                .help("Report the shortest closed geodesic among the words up to this length"),
+        )
+        .arg(
+            Arg::new("diagnostics")
+                .long("diagnostics")
+                .action(ArgAction::SetTrue)
+                .help("Report NaN or infinite numbers found while exploring the group"),
+        )
         .get_matches();
+    match matches.subcommand() {
+        Some(("merge", sub)) => return merge(sub),
+        Some(("poster", sub)) => return poster::run(sub),
+        _ => {}
+    }
     let width: usize = *matches.get_one("width").unwrap();
     let height: usize = *matches.get_one("height").unwrap();
     let re1: f64 = *matches.get_one("re1").unwrap();
@@ -55,16 +388,333 @@ fn main() {
     let im2: f64 = *matches.get_one("im2").unwrap();
     let iters: usize = *matches.get_one("iters").unwrap();
     let filename: &String = matches.get_one("outfile").unwrap();
-    let p1 = Cpx::new(re1, im1);
-    let p2 = Cpx::new(re2, im2);
-    let pts = kleinian::generate_points_from_traces(p1, p2, iters);
-    let trans = kleinian::window::window_transform(&pts, width, height);
+    let format: &String = matches.get_one("format").unwrap();
+    let preset = matches
+        .get_one::<String>("preset")
+        .and_then(|name| presets::find(name));
+    let (p1, p2) = match preset {
+        Some(p) => (p.ta, p.tb),
+        None => (Cpx::new(re1, im1), Cpx::new(re2, im2)),
+    };
+    let symmetry = preset.map_or(Symmetry::Oi, |p| p.symmetry);
+    let root: TabRoot = matches.get_one::<String>("root").unwrap().parse().unwrap();
+    let seed: SeedCircles = matches.get_one::<String>("seed").unwrap().parse().unwrap();
+    let moves: Vec<NielsenMove> = matches
+        .get_many::<String>("nielsen")
+        .unwrap_or_default()
+        .map(|m| m.parse().unwrap())
+        .collect();
+    let conjugate: Option<u64> = matches.get_one("conjugate").copied();
+    let wildness: f64 = *matches.get_one("wildness").unwrap();
+    let conjugated = |ta, tb| {
+        let gens = match symmetry {
+            Symmetry::Oi => kleinian::generators_with_root(ta, tb, root),
+            _ => symmetry.generators(ta, tb),
+        };
+        let gens = moves.iter().fold(gens, |g, m| m.apply(&g));
+        let gens = seed.apply(gens);
+        match conjugate {
+            Some(s) => kleinian::conjugate::random_conjugate(&gens, s, wildness),
+            None => gens,
+        }
+    };
+    // The conjugation to the disk is found once, so that the recipe stays
+    // holomorphic in the traces for `--format sensitivity`.
+    let disk = matches.get_flag("disk").then(|| {
+        let c = fuchsian::invariant_circle(&conjugated(p1, p2)).unwrap_or_else(|| {
+            eprintln!("error: the group does not preserve a circle");
+            process::exit(1);
+        });
+        fuchsian::disk_normalizer(&c)
+    });
+    let recipe = |ta, tb| {
+        let gens = conjugated(ta, tb);
+        match &disk {
+            Some(m) => kleinian::conjugate::conjugate(&gens, m),
+            None => gens,
+        }
+    };
+    let gens = || recipe(p1, p2);
+    let cache = matches.get_one::<String>("cache").map(PointCache::new);
+    let subgroup: Option<&CosetTable> = matches.get_one("subgroup");
+    // The cache is keyed by the generators of the whole group, so it is not
+    // used for subgroups.
+    let points = |n: usize| match (subgroup, &cache) {
+        (Some(t), _) => Ok(explore_subgroup(&gens(), t, n)?.points().collect()),
+        (None, Some(c)) => c.generate_points(gens(), n),
+        (None, None) => kleinian::generate_points(gens(), n),
+    };
+    let labelled_points = || match (subgroup, &cache) {
+        (Some(t), _) => Ok(explore_subgroup(&gens(), t, iters)?
+            .labelled_points()
+            .collect()),
+        (None, Some(c)) => c.generate_labelled_points(gens(), iters),
+        (None, None) => kleinian::generate_labelled_points(gens(), iters),
+    };
+    if matches.get_flag("validate") {
+        let report = kleinian::validation::validate(&gens(), Some(Cpx::from(-2.0)));
+        eprint!("{}", report);
+    }
+    if matches.get_flag("score") {
+        let score = kleinian::score::score(gens(), iters);
+        eprintln!("discreteness {:.3}", score.discreteness);
+        match score.dimension {
+            Some(d) => eprintln!("dimension {:.3}", d),
+            None => eprintln!("dimension unknown"),
+        }
+        eprintln!("connectivity {:.3}", score.connectivity);
+        eprintln!("degenerate {:.3}", score.degenerate);
+        eprintln!("score {:.3}", score.total());
+    }
+    if let Some(&n) = matches.get_one::<usize>("systole") {
+        match kleinian::systole::systole(&gens(), n) {
+            Some(s) => eprintln!(
+                "systole {} length {:.6} (complex length {:.6}), axis from {:.6} to {:.6}",
+                subgroup::format_word(&s.word),
+                s.length(),
+                s.complex_length,
+                s.axis[0],
+                s.axis[1]
+            ),
+            None => eprintln!("no loxodromic words of length up to {}", n),
+        }
+    }
+    if matches.get_flag("diagnostics") {
+        let report = kleinian::diagnostics::diagnose(gens(), iters);
+        eprint!("{}", report);
+    }
+    if format == "glb" {
+        let circles = kleinian::generate_circles(gens(), iters).unwrap_or_else(|e| fail(e));
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        let mut out = BufWriter::new(f);
+        kleinian::gltf::write_glb(&mut out, &circles, &Default::default())
+            .and_then(|()| out.flush())
+            .unwrap_or_else(|e| clap::Error::from(e).exit());
+        return;
+    }
+    #[cfg(feature = "parquet")]
+    if format == "arrow" || format == "parquet" {
+        let mut queue = CircleQueue::new(gens()).unwrap_or_else(|e| fail(e));
+        queue.enable_words();
+        while queue.len() < iters {
+            queue.advance().unwrap_or_else(|e| fail(e));
+        }
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        let out = BufWriter::new(f);
+        let result = match format.as_str() {
+            "arrow" => table::write_arrow(out, &queue).map_err(|e| e.to_string()),
+            _ => table::write_parquet(out, &queue).map_err(|e| e.to_string()),
+        };
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+    if format == "sensitivity" {
+        let pts =
+            kleinian::sensitivity::sensitivities(recipe, p1, p2, iters).unwrap_or_else(|e| fail(e));
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        let mut out = BufWriter::new(f);
+        // Each line is a point followed by its derivatives with respect to
+        // the two traces.
+        pts.iter()
+            .try_for_each(|p| {
+                writeln!(
+                    out,
+                    "{} {} {} {} {} {}",
+                    p.z.re, p.z.im, p.d_ta.re, p.d_ta.im, p.d_tb.re, p.d_tb.im
+                )
+            })
+            .and_then(|()| out.flush())
+            .unwrap_or_else(|e| clap::Error::from(e).exit());
+        return;
+    }
+    if format == "points" || format == "density" {
+        if subgroup.is_some() {
+            eprintln!(
+                "error: --subgroup is not supported with --format {}",
+                format
+            );
+            process::exit(1);
+        }
+        let (chunk, num_chunks) = matches
+            .get_one::<(usize, usize)>("chunk")
+            .copied()
+            .unwrap_or((0, 1));
+        let partition = Partition {
+            depth: *matches.get_one("chunk-depth").unwrap(),
+            num_chunks,
+        };
+        // Every chunk fits the window to the same sample of points, so
+        // that the densities line up, and explores down to circles the
+        // size of a pixel.
+        let pts = points(iters.min(DIRECT_SAMPLE)).unwrap_or_else(|e| fail(e));
+        let trans =
+            kleinian::window::window_transform(&pts, width, height).unwrap_or_else(|e| fail(e));
+        let min_radius = 1.0 / trans.scale();
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        let result = match format.as_str() {
+            "points" => partition
+                .generate_chunk_to(&gens(), min_radius, chunk, f)
+                .map(|_| ()),
+            _ => partition
+                .chunk_density(&gens(), min_radius, chunk, &trans, width, height)
+                .unwrap_or_else(|e| fail(e))
+                .write_to(f),
+        };
+        result.unwrap_or_else(|e| clap::Error::from(e).exit());
+        return;
+    }
+    if format == "ply" || format == "xyz" {
+        let lift: &String = matches.get_one("lift").unwrap();
+        let color_by: &String = matches.get_one("color-by").unwrap();
+        let style = Style {
+            palette: Palette::Rainbow,
+            color_by: color_by.parse().unwrap(),
+            ..Style::default()
+        };
+        let pts = labelled_points().unwrap_or_else(|e| fail(e));
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        let mut out = BufWriter::new(f);
+        let lift = lift.parse().unwrap();
+        let result = match format.as_str() {
+            "ply" => pointcloud::write_ply(&mut out, &pts, lift, &style),
+            _ => pointcloud::write_xyz(&mut out, &pts, lift, &style),
+        };
+        result
+            .and_then(|()| out.flush())
+            .unwrap_or_else(|e| clap::Error::from(e).exit());
+        return;
+    }
+    if format != "png" {
+        let opts = PlotOptions {
+            feed_rate: *matches.get_one("feed-rate").unwrap(),
+            circles: matches.get_flag("circles"),
+        };
+        let bed_width: f64 = *matches.get_one("bed-width").unwrap();
+        let bed_height: f64 = *matches.get_one("bed-height").unwrap();
+        let circles = kleinian::generate_circles(gens(), iters).unwrap_or_else(|e| fail(e));
+        let pts: Vec<Cpx> = circles.iter().map(|c| c.center_point().affine()).collect();
+        let trans = kleinian::window::fit_transform(&pts, bed_width, bed_height)
+            .unwrap_or_else(|e| fail(e));
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        let mut out = BufWriter::new(f);
+        let result = match format.as_str() {
+            "gcode" => plotter::write_gcode(&mut out, &circles, &trans, &opts),
+            _ => plotter::write_hpgl(&mut out, &circles, &trans, &opts),
+        };
+        result.unwrap_or_else(|e| clap::Error::from(e).exit());
+        return;
+    }
+    let projection: &String = matches.get_one("projection").unwrap();
+    if projection == "equirect" {
+        let pts = labelled_points().unwrap_or_else(|e| fail(e));
+        let pixel_data = render_equirect_rgba(&pts, width, height, &Style::default());
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        PngEncoder::new(f)
+            .write_image(
+                &pixel_data,
+                width as u32,
+                height as u32,
+                ExtendedColorType::Rgba8,
+            )
+            .unwrap();
+        return;
+    }
+    if let Some(&budget) = matches.get_one::<f64>("budget") {
+        let limit_set = LimitSet::new(gens());
+        let target = Target {
+            time_budget: budget,
+            memory_budget: matches
+                .get_one::<usize>("memory")
+                .map_or(usize::MAX, |mb| mb << 20),
+            ..Target::new(width, height)
+        };
+        let plan = limit_set.auto(&target).unwrap_or_else(|e| fail(e));
+        eprintln!("drawing by {}", plan.strategy);
+        let mask = limit_set.render(&plan).unwrap_or_else(|e| fail(e));
+        let pixel_data: Vec<u8> = mask.iter().map(|&m| if m { 0 } else { 255 }).collect();
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        PngEncoder::new(f)
+            .write_image(
+                &pixel_data,
+                width as u32,
+                height as u32,
+                ExtendedColorType::L8,
+            )
+            .unwrap();
+        return;
+    }
+    if matches.get_flag("direct") {
+        // The window is fitted to a small sample of the points, and the
+        // number of circles is only limited in case the group is far from
+        // discrete.
+        let pts = points(iters.min(DIRECT_SAMPLE)).unwrap_or_else(|e| fail(e));
+        let trans =
+            kleinian::window::window_transform(&pts, width, height).unwrap_or_else(|e| fail(e));
+        let mut bitmap = vec![pack_rgba([255, 255, 255]); width * height];
+        generate_into_bitmap(
+            &gens(),
+            &mut bitmap,
+            width,
+            height,
+            &trans,
+            pack_rgba([0, 0, 0]),
+            64 * width * height,
+        )
+        .unwrap_or_else(|e| fail(e));
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        PngEncoder::new(f)
+            .write_image(
+                bytemuck::cast_slice(&bitmap),
+                width as u32,
+                height as u32,
+                ExtendedColorType::Rgba8,
+            )
+            .unwrap();
+        return;
+    }
+    let bloom: Option<f32> = matches.get_one("bloom").copied();
+    let sharpen: Option<f32> = matches.get_one("sharpen").copied();
+    if matches.get_flag("density") || bloom.is_some() || sharpen.is_some() {
+        let pts = labelled_points().unwrap_or_else(|e| fail(e));
+        let zs: Vec<Cpx> = pts.iter().map(|p| p.z).collect();
+        let trans =
+            kleinian::window::window_transform(&zs, width, height).unwrap_or_else(|e| fail(e));
+        let mut counts = render::density(&pts, &trans, width, height, 1);
+        // The filters work on the counts, before they are quantized to
+        // colors.
+        if let Some(strength) = sharpen {
+            let radius = *matches.get_one("sharpen-radius").unwrap();
+            postprocess::unsharp_mask(&mut counts, width, radius, strength);
+        }
+        if let Some(strength) = bloom {
+            let radius = *matches.get_one("bloom-radius").unwrap();
+            postprocess::bloom(&mut counts, width, radius, strength);
+        }
+        let pixel_data = tone_map(&counts, &Style::default());
+        let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+        PngEncoder::new(f)
+            .write_image(
+                &pixel_data,
+                width as u32,
+                height as u32,
+                ExtendedColorType::Rgba8,
+            )
+            .unwrap();
+        return;
+    }
+    let pts = points(iters).unwrap_or_else(|e| fail(e));
+    let trans = kleinian::window::window_transform(&pts, width, height).unwrap_or_else(|e| fail(e));
     let mut pixel_data = Vec::new();
     pixel_data.resize(width * height, 255);
     for pt in pts {
-        let (x, y) = trans.apply(&pt);
-        let idx = x * height + y;
-        pixel_data[idx] = 0;
+        // Points at infinity are not drawn.
+        if let Some((x, y)) = trans.apply_within(&pt, width, height) {
+            let idx = x * height + y;
+            pixel_data[idx] = 0;
+        }
     }
     let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
     PngEncoder::new(f)
@@ -0,0 +1,107 @@
+use kleinian::window::CoordTransform;
+use kleinian::{Circle, Cpx};
+use std::io::{self, Write};
+
+/// Height to which the pen (or tool) is raised between strokes, in mm.
+const PEN_UP_Z: f64 = 2.0;
+
+/// HPGL plotter units per millimetre.
+const HPGL_UNITS: f64 = 40.0;
+
+pub struct PlotOptions {
+    pub feed_rate: f64,
+    pub circles: bool,
+}
+
+/// Returns the centers of the circles, skipping those at infinity, which
+/// cannot be plotted.
+fn finite_centers(circles: &[Circle]) -> impl Iterator<Item = Cpx> + '_ {
+    circles
+        .iter()
+        .map(|c| c.center_point())
+        .filter(|p| !p.is_infinite())
+        .map(|p| p.affine())
+}
+
+/// Returns the circles whose outlines can be plotted, skipping the lines
+/// and the circles that pass near infinity.
+fn finite_circles(circles: &[Circle]) -> impl Iterator<Item = &Circle> {
+    circles.iter().filter(|c| !c.passes_near_infinity())
+}
+
+/// Writes G-code that draws the limit curve through the circle centers,
+/// followed by the circle outlines if requested, leaving out what lies at
+/// or near infinity.  Coordinates are in mm.
+pub fn write_gcode<W: Write>(
+    out: &mut W,
+    circles: &[Circle],
+    trans: &CoordTransform,
+    opts: &PlotOptions,
+) -> io::Result<()> {
+    writeln!(out, "G21")?;
+    writeln!(out, "G90")?;
+    writeln!(out, "G0 Z{:.3}", PEN_UP_Z)?;
+    let mut pts = finite_centers(circles).map(|z| trans.map(&z));
+    if let Some((x, y)) = pts.next() {
+        writeln!(out, "G0 X{:.3} Y{:.3}", x, y)?;
+        writeln!(out, "G1 Z0 F{:.1}", opts.feed_rate)?;
+        for (x, y) in pts {
+            writeln!(out, "G1 X{:.3} Y{:.3} F{:.1}", x, y, opts.feed_rate)?;
+        }
+        writeln!(out, "G0 Z{:.3}", PEN_UP_Z)?;
+    }
+    if opts.circles {
+        for c in finite_circles(circles) {
+            let (x, y) = trans.map(&c.center());
+            let r = trans.scale() / c.radius_inv();
+            writeln!(out, "G0 X{:.3} Y{:.3}", x + r, y)?;
+            writeln!(out, "G1 Z0 F{:.1}", opts.feed_rate)?;
+            writeln!(
+                out,
+                "G2 X{:.3} Y{:.3} I{:.3} J0 F{:.1}",
+                x + r,
+                y,
+                -r,
+                opts.feed_rate
+            )?;
+            writeln!(out, "G0 Z{:.3}", PEN_UP_Z)?;
+        }
+    }
+    writeln!(out, "M2")
+}
+
+fn hpgl_coords(trans: &CoordTransform, z: &Cpx) -> (i64, i64) {
+    let (x, y) = trans.map(z);
+    (
+        (x * HPGL_UNITS).round() as i64,
+        (y * HPGL_UNITS).round() as i64,
+    )
+}
+
+/// Writes HPGL that draws the limit curve through the circle centers,
+/// followed by the circle outlines if requested, as in `write_gcode`.
+pub fn write_hpgl<W: Write>(
+    out: &mut W,
+    circles: &[Circle],
+    trans: &CoordTransform,
+    opts: &PlotOptions,
+) -> io::Result<()> {
+    // HPGL velocities are in cm/s, while the feed rate is in mm/min.
+    writeln!(out, "IN;SP1;VS{:.1};", opts.feed_rate / 600.0)?;
+    let mut pts = finite_centers(circles).map(|z| hpgl_coords(trans, &z));
+    if let Some((x, y)) = pts.next() {
+        writeln!(out, "PU{},{};", x, y)?;
+        for (x, y) in pts {
+            writeln!(out, "PD{},{};", x, y)?;
+        }
+        writeln!(out, "PU;")?;
+    }
+    if opts.circles {
+        for c in finite_circles(circles) {
+            let (x, y) = hpgl_coords(trans, &c.center());
+            let r = trans.scale() * HPGL_UNITS / c.radius_inv();
+            writeln!(out, "PU{},{};CI{:.1};", x, y, r)?;
+        }
+    }
+    writeln!(out, "PU;SP0;")
+}
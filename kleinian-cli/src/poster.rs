@@ -0,0 +1,232 @@
+//! The `poster` subcommand, which draws the groups along a path between two
+//! pairs of traces as a grid of panels in one image, such as a sequence of
+//! groups approaching a cusp.  The path is a `GroupPath`, which keeps the
+//! limit sets from jumping between panels, and every panel is drawn with
+//! the same window, fitted to the points of all of them, so that they can
+//! be compared directly.
+
+use clap::{Arg, ArgMatches, Command};
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, ImageEncoder};
+use kleinian::morph::GroupPath;
+use kleinian::window::window_transform;
+use kleinian::Cpx;
+use std::fs::File;
+use std::process;
+
+/// The gray level of the frames around the panels.
+const FRAME: u8 = 160;
+
+/// The 5×7 pixel glyphs of the characters used in labels, one byte per
+/// row with the leftmost pixel in the fifth bit.  Other characters are
+/// drawn as spaces.
+const GLYPHS: [(char, [u8; 7]); 20] = [
+    ('0', [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e]),
+    ('1', [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('2', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f]),
+    ('3', [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e]),
+    ('4', [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02]),
+    ('5', [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e]),
+    ('6', [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e]),
+    ('7', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e]),
+    ('9', [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c]),
+    (',', [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08]),
+    ('+', [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00]),
+    ('-', [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00]),
+    ('=', [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00]),
+    ('a', [0x00, 0x00, 0x0e, 0x01, 0x0f, 0x11, 0x0f]),
+    ('b', [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1e]),
+    ('i', [0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x0e]),
+    ('r', [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10]),
+    ('t', [0x08, 0x08, 0x1c, 0x08, 0x08, 0x09, 0x06]),
+];
+
+/// A grayscale image, in row-major order.
+struct Canvas {
+    width: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn set(&mut self, x: usize, y: usize, value: u8) {
+        self.pixels[y * self.width + x] = value;
+    }
+    /// Draws the text in black with its top left corner at `(x, y)`, with
+    /// each pixel of the glyphs drawn as a `scale` by `scale` square.  The
+    /// characters that do not fit within `max_width` are dropped.
+    fn text(&mut self, x: usize, y: usize, text: &str, scale: usize, max_width: usize) {
+        let fits = max_width / (6 * scale);
+        for (k, ch) in text.chars().take(fits).enumerate() {
+            let Some((_, rows)) = GLYPHS.iter().find(|(c, _)| *c == ch) else {
+                continue;
+            };
+            let x0 = x + 6 * scale * k;
+            for (r, bits) in rows.iter().enumerate() {
+                for c in (0..5).filter(|c| bits & (0x10 >> c) != 0) {
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set(x0 + scale * c + dx, y + scale * r + dy, 0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    /// Draws the outline of the rectangle with top left corner `(x, y)`.
+    fn frame(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        for i in x..x + width {
+            self.set(i, y, FRAME);
+            self.set(i, y + height - 1, FRAME);
+        }
+        for j in y..y + height {
+            self.set(x, j, FRAME);
+            self.set(x + width - 1, j, FRAME);
+        }
+    }
+}
+
+/// Formats a trace for a label.
+fn format_trace(z: Cpx) -> String {
+    // Rounding first, and adding 0 to turn -0 into 0, keeps parts that
+    // are within rounding error of 0 from being shown as -0.000.
+    let round = |x: f64| (1000.0 * x).round() / 1000.0 + 0.0;
+    format!("{:.3}{:+.3}i", round(z.re), round(z.im))
+}
+
+/// Returns the times along the path of the `n` panels.  Each step is
+/// `ratio` times as long as the one before, so for a ratio less than 1 the
+/// panels crowd towards the end of the path.
+fn times(n: usize, ratio: f64) -> Vec<f64> {
+    let mut t = vec![0.0];
+    let mut step = 1.0;
+    for _ in 1..n {
+        let last = t[t.len() - 1];
+        t.push(last + step);
+        step *= ratio;
+    }
+    let total = t[t.len() - 1];
+    if total > 0.0 {
+        t.iter_mut().for_each(|x| *x /= total);
+    }
+    t
+}
+
+/// Returns the traces given by the four values of a `--from` or `--to`
+/// argument.
+fn traces(matches: &ArgMatches, id: &str) -> (Cpx, Cpx) {
+    let v: Vec<f64> = matches.get_many(id).unwrap().copied().collect();
+    (Cpx::new(v[0], v[1]), Cpx::new(v[2], v[3]))
+}
+
+pub fn command() -> Command {
+    let trace_arg = |id: &'static str, help: &'static str| {
+        Arg::new(id)
+            .long(id)
+            .required(true)
+            .num_args(4)
+            .value_names(["RE1", "IM1", "RE2", "IM2"])
+            .value_parser(clap::value_parser!(f64))
+            .allow_negative_numbers(true)
+            .help(help)
+    };
+    let size_arg = |id: &'static str, default: &'static str, help: &'static str| {
+        Arg::new(id)
+            .long(id)
+            .value_parser(clap::value_parser!(usize))
+            .default_value(default)
+            .help(help)
+    };
+    Command::new("poster")
+        .about("Draw the groups along a path between two pairs of traces as a grid of panels")
+        .arg(Arg::new("outfile").required(true))
+        .arg(trace_arg("from", "Traces of a and b in the first panel"))
+        .arg(trace_arg("to", "Traces of a and b in the last panel"))
+        .arg(size_arg("rows", "2", "Number of rows of panels"))
+        .arg(size_arg("cols", "3", "Number of columns of panels"))
+        .arg(size_arg("panel-width", "400", "Width of each panel, in pixels"))
+        .arg(size_arg("panel-height", "400", "Height of each panel, in pixels"))
+        .arg(size_arg("gap", "16", "Space between the panels, in pixels"))
+        .arg(size_arg("iters", "20000", "Number of points in each panel"))
+        .arg(
+            Arg::new("ratio")
+                .long("ratio")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("1")
+                .help("Ratio between the lengths of successive steps along the path; less than 1 crowds the panels towards the end"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) {
+    let filename: &String = matches.get_one("outfile").unwrap();
+    let size = |id: &str| *matches.get_one::<usize>(id).unwrap();
+    let (rows, cols) = (size("rows"), size("cols"));
+    if rows == 0 || cols == 0 {
+        eprintln!("error: there must be at least one row and one column");
+        process::exit(1);
+    }
+    let (pw, ph, gap, iters) = (
+        size("panel-width"),
+        size("panel-height"),
+        size("gap"),
+        size("iters"),
+    );
+    let ratio: f64 = *matches.get_one("ratio").unwrap();
+    let (from, to) = (traces(matches, "from"), traces(matches, "to"));
+    let path = GroupPath::new(
+        &kleinian::generators(from.0, from.1),
+        &kleinian::generators(to.0, to.1),
+    )
+    .unwrap_or_else(|| {
+        eprintln!("error: the generators at the ends of the path are degenerate");
+        process::exit(1);
+    });
+    let times = times(rows * cols, ratio);
+    let panels: Vec<Vec<Cpx>> = times
+        .iter()
+        .map(|&t| {
+            kleinian::generate_points(path.generators_at(t), iters)
+                .unwrap_or_else(|e| super::fail(e))
+        })
+        .collect();
+    let all: Vec<Cpx> = panels.iter().flatten().copied().collect();
+    let trans = window_transform(&all, pw, ph).unwrap_or_else(|e| super::fail(e));
+    // The label goes in a band below each panel, in the largest size at
+    // which the longest label fits.
+    let labels: Vec<String> = times
+        .iter()
+        .map(|&t| {
+            let [ta, tb, _] = path.traces_at(t);
+            format!("tr a = {}, tr b = {}", format_trace(ta), format_trace(tb))
+        })
+        .collect();
+    let longest = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    let scale = (pw / (6 * longest + 4)).clamp(1, 3);
+    let band = 11 * scale;
+    let (cell_w, cell_h) = (pw + gap, ph + band + gap);
+    let (width, height) = (cols * cell_w + gap, rows * cell_h + gap);
+    let mut canvas = Canvas {
+        width,
+        pixels: vec![255; width * height],
+    };
+    for (k, (pts, label)) in panels.iter().zip(&labels).enumerate() {
+        let (x0, y0) = (gap + (k % cols) * cell_w, gap + (k / cols) * cell_h);
+        for z in pts {
+            if let Some((x, y)) = trans.apply_within(z, pw, ph) {
+                canvas.set(x0 + x, y0 + ph - 1 - y, 0);
+            }
+        }
+        canvas.frame(x0, y0, pw, ph);
+        canvas.text(x0 + 2 * scale, y0 + ph + 2 * scale, label, scale, pw);
+    }
+    let f = File::create(filename.as_str()).unwrap_or_else(|e| clap::Error::from(e).exit());
+    PngEncoder::new(f)
+        .write_image(
+            &canvas.pixels,
+            width as u32,
+            height as u32,
+            ExtendedColorType::L8,
+        )
+        .unwrap();
+}
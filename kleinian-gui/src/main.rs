@@ -0,0 +1,301 @@
+use eframe::egui;
+use kleinian::presets::{self, Symmetry};
+use kleinian::render::{self, ColorBy, Mode, Style};
+use kleinian::window::{self, CoordTransform};
+use kleinian::{CircleQueue, Cpx, LimitPoint};
+use std::time::{Duration, Instant};
+
+/// The time spent exploring the group in each frame, so that the preview
+/// updates while the computation runs.
+const FRAME_BUDGET: Duration = Duration::from_millis(30);
+
+/// The part of the plane that is shown.
+#[derive(Clone, Copy, PartialEq)]
+struct View {
+    center: Cpx,
+    /// Pixels per unit length.
+    scale: f64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Drawn {
+    size: [usize; 2],
+    view: View,
+    style: (Mode, ColorBy, usize),
+    /// Whether the computation had finished.
+    done: bool,
+}
+
+struct Explorer {
+    symmetry: Symmetry,
+    ta: Cpx,
+    tb: Cpx,
+    iters: usize,
+    style: Style,
+    /// If `None`, the view is chosen to fit the points.
+    view: Option<View>,
+    queue: Option<CircleQueue>,
+    /// The parameters that `queue` was computed with.
+    computed: Option<(Symmetry, Cpx, Cpx)>,
+    /// tr ab and tr aba^{-1}b^{-1} for those parameters.
+    derived: Option<(Cpx, Cpx)>,
+    texture: Option<egui::TextureHandle>,
+    /// What the image in `texture` shows, so that it is only redrawn when
+    /// something changes.
+    drawn: Option<Drawn>,
+    /// The size of the image, in pixels.
+    size: [usize; 2],
+    export_path: String,
+    status: String,
+}
+
+impl Default for Explorer {
+    fn default() -> Self {
+        Explorer {
+            symmetry: Symmetry::Oi,
+            ta: Cpx::new(1.87, 0.1),
+            tb: Cpx::new(1.87, -0.1),
+            iters: 100000,
+            style: Style::default(),
+            view: None,
+            queue: None,
+            computed: None,
+            derived: None,
+            texture: None,
+            drawn: None,
+            size: [1, 1],
+            export_path: "kleinian.png".to_string(),
+            status: String::new(),
+        }
+    }
+}
+
+impl Explorer {
+    fn done(&self) -> bool {
+        self.queue.as_ref().is_none_or(|q| q.len() >= self.iters)
+    }
+    /// Restarts the computation if the parameters have changed, and
+    /// otherwise continues it for a while.
+    fn compute(&mut self) {
+        let params = (self.symmetry, self.ta, self.tb);
+        if self.computed != Some(params) {
+            self.computed = Some(params);
+            self.drawn = None;
+            let set = self.symmetry.generator_set(self.ta, self.tb);
+            self.derived = Some((set.trace_ab, set.trace_commutator));
+            match CircleQueue::new(set.into()) {
+                Ok(q) => {
+                    self.queue = Some(q);
+                    self.status.clear();
+                }
+                Err(e) => {
+                    self.queue = None;
+                    self.status = e.to_string();
+                }
+            }
+        }
+        let start = Instant::now();
+        while !self.done() && start.elapsed() < FRAME_BUDGET {
+            let q = self.queue.as_mut().unwrap();
+            if let Err(e) = q.advance() {
+                self.status = e.to_string();
+                // Keep what has been computed so far, but stop.
+                self.iters = q.len();
+            }
+        }
+    }
+    fn points(&self) -> Vec<LimitPoint> {
+        self.queue
+            .as_ref()
+            .map(|q| q.labelled_points().collect())
+            .unwrap_or_default()
+    }
+    /// Returns the transform for a `width` by `height` image.
+    fn transform(&self, pts: &[LimitPoint], width: usize, height: usize) -> Option<CoordTransform> {
+        match self.view {
+            Some(v) => Some(CoordTransform::centered(v.center, v.scale, width, height)),
+            None => {
+                let zs: Vec<Cpx> = pts.iter().map(|p| p.z).collect();
+                window::window_transform(&zs, width, height).ok()
+            }
+        }
+    }
+    fn export(&mut self) {
+        let size = self.size;
+        let pts = self.points();
+        let Some(trans) = self.transform(&pts, size[0], size[1]) else {
+            return;
+        };
+        let result = if self.export_path.ends_with(".svg") {
+            let svg = kleinian::svg::to_svg(&pts, &trans, size[0], size[1], &self.style);
+            std::fs::write(&self.export_path, svg).map_err(|e| e.to_string())
+        } else {
+            let pixels = render::render_rgba(&pts, &trans, size[0], size[1], &self.style);
+            image::save_buffer(
+                &self.export_path,
+                &pixels,
+                size[0] as u32,
+                size[1] as u32,
+                image::ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| e.to_string())
+        };
+        self.status = match result {
+            Ok(()) => format!("saved {}", self.export_path),
+            Err(e) => e,
+        };
+    }
+    fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Presets", |ui| {
+            for p in presets::PRESETS {
+                if ui.button(p.name).on_hover_text(p.description).clicked() {
+                    self.symmetry = p.symmetry;
+                    self.ta = p.ta;
+                    self.tb = p.tb;
+                    self.view = None;
+                    ui.close();
+                }
+            }
+        });
+        egui::ComboBox::from_label("Symmetry")
+            .selected_text(self.symmetry.as_str())
+            .show_ui(ui, |ui| {
+                for s in [Symmetry::Oi, Symmetry::Xxi, Symmetry::Xii] {
+                    ui.selectable_value(&mut self.symmetry, s, s.as_str());
+                }
+            });
+        ui.add(egui::Slider::new(&mut self.ta.re, 0.0..=4.0).text("Re tr a"));
+        ui.add(egui::Slider::new(&mut self.ta.im, -2.0..=2.0).text("Im tr a"));
+        if self.symmetry != Symmetry::Xii {
+            ui.add(egui::Slider::new(&mut self.tb.re, 0.0..=4.0).text("Re tr b"));
+            ui.add(egui::Slider::new(&mut self.tb.im, -2.0..=2.0).text("Im tr b"));
+        }
+        if let Some((tab, tk)) = self.derived {
+            ui.label(format!("tr ab = {:.4}", tab));
+            ui.label(format!("tr aba⁻¹b⁻¹ = {:.4}", tk));
+        }
+        ui.add(
+            egui::Slider::new(&mut self.iters, 1000..=10000000)
+                .logarithmic(true)
+                .text("Points"),
+        );
+        ui.separator();
+        egui::ComboBox::from_label("Mode")
+            .selected_text(format!("{:?}", self.style.mode))
+            .show_ui(ui, |ui| {
+                for m in [Mode::Points, Mode::Density] {
+                    ui.selectable_value(&mut self.style.mode, m, format!("{:?}", m));
+                }
+            });
+        egui::ComboBox::from_label("Color by")
+            .selected_text(format!("{:?}", self.style.color_by))
+            .show_ui(ui, |ui| {
+                for c in [
+                    ColorBy::Uniform,
+                    ColorBy::Generator,
+                    ColorBy::Depth,
+                    ColorBy::Radius,
+                ] {
+                    ui.selectable_value(&mut self.style.color_by, c, format!("{:?}", c));
+                }
+            });
+        ui.add(egui::Slider::new(&mut self.style.point_size, 1..=5).text("Point size"));
+        if ui.button("Fit view").clicked() {
+            self.view = None;
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.export_path);
+            if ui.button("Export").clicked() {
+                self.export();
+            }
+        });
+        ui.label("Files ending in .svg are saved as SVG, and others as PNG.");
+        if let Some(q) = &self.queue {
+            ui.label(format!("{} points", q.len()));
+        }
+        ui.label(&self.status);
+    }
+    /// Pans and zooms in response to dragging and scrolling.
+    fn navigate(&mut self, ui: &egui::Ui, response: &egui::Response, size: [usize; 2]) {
+        let Some(trans) = self.transform(&self.points(), size[0], size[1]) else {
+            return;
+        };
+        let mut view = self.view.unwrap_or(View {
+            center: trans.center(size[0], size[1]),
+            scale: trans.scale(),
+        });
+        let drag = response.drag_delta();
+        if drag != egui::Vec2::ZERO {
+            view.center -= Cpx::new(drag.x as f64, -drag.y as f64) / view.scale;
+            self.view = Some(view);
+        }
+        let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+        if let (Some(pos), true) = (response.hover_pos(), scroll != 0.0) {
+            // Keep the point under the cursor fixed.
+            let d = pos - response.rect.center();
+            let offset = Cpx::new(d.x as f64, -d.y as f64);
+            let z = view.center + offset / view.scale;
+            view.scale *= (scroll as f64 / 200.0).exp();
+            view.center = z - offset / view.scale;
+            self.view = Some(view);
+        }
+    }
+}
+
+impl eframe::App for Explorer {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.compute();
+        egui::SidePanel::left("controls").show(ctx, |ui| self.controls(ui));
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let avail = ui.available_size();
+            let size = [avail.x.max(1.0) as usize, avail.y.max(1.0) as usize];
+            self.size = size;
+            let (rect, response) = ui.allocate_exact_size(avail, egui::Sense::drag());
+            self.navigate(ui, &response, size);
+            let pts = self.points();
+            if let Some(trans) = self.transform(&pts, size[0], size[1]) {
+                let view = View {
+                    center: trans.center(size[0], size[1]),
+                    scale: trans.scale(),
+                };
+                let drawn = Drawn {
+                    size,
+                    view,
+                    style: (self.style.mode, self.style.color_by, self.style.point_size),
+                    done: self.done(),
+                };
+                if self.drawn != Some(drawn) || !drawn.done {
+                    let pixels = render::render_rgba(&pts, &trans, size[0], size[1], &self.style);
+                    let image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+                    match &mut self.texture {
+                        Some(t) => t.set(image, egui::TextureOptions::NEAREST),
+                        None => {
+                            self.texture = Some(ctx.load_texture(
+                                "limit set",
+                                image,
+                                egui::TextureOptions::NEAREST,
+                            ))
+                        }
+                    }
+                    self.drawn = Some(drawn);
+                }
+            }
+            if let Some(t) = &self.texture {
+                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                ui.painter().image(t.id(), rect, uv, egui::Color32::WHITE);
+            }
+        });
+        if !self.done() {
+            ctx.request_repaint();
+        }
+    }
+}
+
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "Kleinian",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Ok(Box::<Explorer>::default())),
+    )
+}
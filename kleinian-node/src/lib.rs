@@ -0,0 +1,161 @@
+use kleinian::presets::Symmetry;
+use kleinian::render::{parse_color, render_rgba, Style};
+use kleinian::window::{window_transform, CoordTransform};
+use kleinian::{Cpx, Generator};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// The parameters of a group.  `symmetry` is one of "oi" (the default),
+/// "xxi", and "xii".
+#[napi(object)]
+#[derive(Clone)]
+pub struct Traces {
+    pub a_re: f64,
+    pub a_im: f64,
+    pub b_re: f64,
+    pub b_im: f64,
+    pub symmetry: Option<String>,
+}
+
+/// Options for `renderPng`.  Missing options get the same defaults as in
+/// the command line program.  If `centerRe`, `centerIm`, and `scale` (in
+/// pixels per unit length) are not all given, the view is chosen to fit
+/// all of the points.
+#[napi(object)]
+#[derive(Clone)]
+pub struct RenderOptions {
+    pub width: u32,
+    pub height: u32,
+    pub iters: u32,
+    pub center_re: Option<f64>,
+    pub center_im: Option<f64>,
+    pub scale: Option<f64>,
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    pub palette: Option<String>,
+    pub color_by: Option<String>,
+    pub mode: Option<String>,
+    pub point_size: Option<u32>,
+    pub gamma: Option<f64>,
+}
+
+fn invalid_arg<E: ToString>(e: E) -> Error {
+    Error::new(Status::InvalidArg, e.to_string())
+}
+
+/// Converts a library error into an error whose code identifies the kind
+/// of error.
+fn failure(e: kleinian::Error) -> Error {
+    Error::new(Status::GenericFailure, format!("{}: {}", e.code(), e))
+}
+
+fn make_generators(t: &Traces) -> Result<[Generator; 4]> {
+    let symmetry = match &t.symmetry {
+        Some(s) => s.parse().map_err(invalid_arg)?,
+        None => Symmetry::Oi,
+    };
+    Ok(symmetry.generators(Cpx::new(t.a_re, t.a_im), Cpx::new(t.b_re, t.b_im)))
+}
+
+fn make_style(o: &RenderOptions) -> Result<Style> {
+    let mut style = Style::default();
+    if let Some(c) = &o.foreground {
+        style.foreground = parse_color(c).map_err(invalid_arg)?;
+    }
+    if let Some(c) = &o.background {
+        style.background = parse_color(c).map_err(invalid_arg)?;
+    }
+    if let Some(p) = &o.palette {
+        style.palette = p.parse().map_err(invalid_arg)?;
+    }
+    if let Some(c) = &o.color_by {
+        style.color_by = c.parse().map_err(invalid_arg)?;
+    }
+    if let Some(m) = &o.mode {
+        style.mode = m.parse().map_err(invalid_arg)?;
+    }
+    if let Some(s) = o.point_size {
+        style.point_size = s as usize;
+    }
+    if let Some(g) = o.gamma {
+        style.gamma = g;
+    }
+    Ok(style)
+}
+
+/// Computes approximately `iters` points of the limit set, returned as
+/// interleaved real and imaginary parts.
+#[napi]
+pub fn compute_points(traces: Traces, iters: u32) -> Result<Float64Array> {
+    let gens = make_generators(&traces)?;
+    let pts = kleinian::generate_points(gens, iters as usize).map_err(failure)?;
+    Ok(pts
+        .into_iter()
+        .flat_map(|z| [z.re, z.im])
+        .collect::<Vec<_>>()
+        .into())
+}
+
+/// Computes the circles whose centers are returned by `computePoints`, as
+/// consecutive (center real part, center imaginary part, radius) triples.
+/// The circles are ordered along the limit set.
+#[napi]
+pub fn compute_circles(traces: Traces, iters: u32) -> Result<Float64Array> {
+    let gens = make_generators(&traces)?;
+    let circles = kleinian::generate_circles(gens, iters as usize).map_err(failure)?;
+    Ok(circles
+        .into_iter()
+        .flat_map(|c| {
+            let z = c.center_point().affine();
+            [z.re, z.im, 1.0 / c.radius_inv()]
+        })
+        .collect::<Vec<_>>()
+        .into())
+}
+
+fn render_png_sync(traces: &Traces, options: &RenderOptions) -> Result<Vec<u8>> {
+    let gens = make_generators(traces)?;
+    let style = make_style(options)?;
+    let (w, h) = (options.width as usize, options.height as usize);
+    let pts = kleinian::generate_labelled_points(gens, options.iters as usize).map_err(failure)?;
+    let trans = match (options.center_re, options.center_im, options.scale) {
+        (Some(re), Some(im), Some(s)) => CoordTransform::centered(Cpx::new(re, im), s, w, h),
+        _ => {
+            let zs: Vec<Cpx> = pts.iter().map(|p| p.z).collect();
+            window_transform(&zs, w, h).map_err(failure)?
+        }
+    };
+    let pixel_data = render_rgba(&pts, &trans, w, h, &style);
+    let png_error = |e: png::EncodingError| Error::from_reason(e.to_string());
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, options.width, options.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(&pixel_data).map_err(png_error)?;
+    writer.finish().map_err(png_error)?;
+    Ok(out)
+}
+
+pub struct RenderTask {
+    traces: Traces,
+    options: RenderOptions,
+}
+
+impl Task for RenderTask {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+    fn compute(&mut self) -> Result<Vec<u8>> {
+        render_png_sync(&self.traces, &self.options)
+    }
+    fn resolve(&mut self, _env: Env, output: Vec<u8>) -> Result<Buffer> {
+        Ok(output.into())
+    }
+}
+
+/// Draws the limit set and encodes it as a PNG image.  The work is done on
+/// the libuv thread pool, so that the event loop is not blocked.
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn render_png(traces: Traces, options: RenderOptions) -> AsyncTask<RenderTask> {
+    AsyncTask::new(RenderTask { traces, options })
+}
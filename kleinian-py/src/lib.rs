@@ -0,0 +1,166 @@
+use kleinian::presets::{self, Symmetry};
+use kleinian::render::{parse_color, render_rgba, Style};
+use kleinian::window::{self, CoordTransform};
+use kleinian::{Cpx, Generator};
+use numpy::ndarray::{Array2, Array3};
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArray3};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+fn value_error<E: ToString>(e: E) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn make_generators(ta: Cpx, tb: Cpx, symmetry: &str) -> PyResult<[Generator; 4]> {
+    let symmetry: Symmetry = symmetry.parse().map_err(value_error)?;
+    Ok(symmetry.generators(ta, tb))
+}
+
+/// Returns the matrices of the generators a, b, a^-1, b^-1 with traces
+/// `ta` and `tb`, as an array of shape (4, 2, 2).  `symmetry` is one of
+/// "oi", "xxi", and "xii".
+#[pyfunction]
+#[pyo3(signature = (ta, tb, symmetry = "oi"))]
+fn generators<'py>(
+    py: Python<'py>,
+    ta: Cpx,
+    tb: Cpx,
+    symmetry: &str,
+) -> PyResult<Bound<'py, PyArray3<Cpx>>> {
+    let gens = make_generators(ta, tb, symmetry)?;
+    let arr = Array3::from_shape_fn((4, 2, 2), |(g, i, j)| gens[g].matrix[(i, j)]);
+    Ok(arr.into_pyarray(py))
+}
+
+/// Returns about `n` points of the limit set as a complex array.
+#[pyfunction]
+#[pyo3(signature = (ta, tb, n, symmetry = "oi"))]
+fn points<'py>(
+    py: Python<'py>,
+    ta: Cpx,
+    tb: Cpx,
+    n: usize,
+    symmetry: &str,
+) -> PyResult<Bound<'py, PyArray1<Cpx>>> {
+    let gens = make_generators(ta, tb, symmetry)?;
+    let pts = py
+        .detach(|| kleinian::generate_points(gens, n))
+        .map_err(value_error)?;
+    Ok(pts.into_pyarray(py))
+}
+
+/// Returns the circles whose centers are returned by `points`, in the order
+/// in which they occur along the limit set, as an array of shape (n, 3)
+/// whose rows are (center real part, center imaginary part, radius).
+#[pyfunction]
+#[pyo3(signature = (ta, tb, n, symmetry = "oi"))]
+fn circles<'py>(
+    py: Python<'py>,
+    ta: Cpx,
+    tb: Cpx,
+    n: usize,
+    symmetry: &str,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let gens = make_generators(ta, tb, symmetry)?;
+    let circles = py
+        .detach(|| kleinian::generate_circles(gens, n))
+        .map_err(value_error)?;
+    let arr = Array2::from_shape_fn((circles.len(), 3), |(i, j)| {
+        let c = &circles[i];
+        match j {
+            0 => c.center_point().affine().re,
+            1 => c.center_point().affine().im,
+            _ => 1.0 / c.radius_inv(),
+        }
+    });
+    Ok(arr.into_pyarray(py))
+}
+
+/// Draws about `n` points of the limit set into an RGBA image, returned as
+/// an array of shape (height, width, 4).  The imaginary axis points up.
+/// If `center` and `scale` (in pixels per unit length) are not given, the
+/// view is chosen to fit all of the points.  The remaining arguments have
+/// the same meanings as the options of the command line program.
+#[pyfunction]
+#[pyo3(signature = (
+    ta, tb, n, width, height, symmetry = "oi", *, center = None, scale = None,
+    foreground = "#000000", background = "#ffffff", palette = "gradient",
+    color_by = "uniform", mode = "points", point_size = 1, gamma = 1.0,
+))]
+#[allow(clippy::too_many_arguments)]
+fn render<'py>(
+    py: Python<'py>,
+    ta: Cpx,
+    tb: Cpx,
+    n: usize,
+    width: usize,
+    height: usize,
+    symmetry: &str,
+    center: Option<Cpx>,
+    scale: Option<f64>,
+    foreground: &str,
+    background: &str,
+    palette: &str,
+    color_by: &str,
+    mode: &str,
+    point_size: usize,
+    gamma: f64,
+) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let gens = make_generators(ta, tb, symmetry)?;
+    let style = Style {
+        foreground: parse_color(foreground).map_err(value_error)?,
+        background: parse_color(background).map_err(value_error)?,
+        palette: palette.parse().map_err(value_error)?,
+        color_by: color_by.parse().map_err(value_error)?,
+        point_size,
+        mode: mode.parse().map_err(value_error)?,
+        gamma,
+    };
+    let pixels = py
+        .detach(|| -> Result<Vec<u8>, kleinian::Error> {
+            let pts = kleinian::generate_labelled_points(gens, n)?;
+            let trans = match (center, scale) {
+                (Some(c), Some(s)) => CoordTransform::centered(c, s, width, height),
+                _ => {
+                    let zs: Vec<Cpx> = pts.iter().map(|p| p.z).collect();
+                    window::window_transform(&zs, width, height)?
+                }
+            };
+            Ok(render_rgba(&pts, &trans, width, height, &style))
+        })
+        .map_err(value_error)?;
+    let arr = Array3::from_shape_vec((height, width, 4), pixels).map_err(value_error)?;
+    Ok(arr.into_pyarray(py))
+}
+
+/// Returns a list of dictionaries describing parameters that are known to
+/// give good pictures, with the keys "name", "description", "symmetry",
+/// "ta", and "tb".
+#[pyfunction]
+fn list_presets(py: Python<'_>) -> PyResult<Vec<Bound<'_, PyDict>>> {
+    presets::PRESETS
+        .iter()
+        .map(|p| {
+            let d = PyDict::new(py);
+            d.set_item("name", p.name)?;
+            d.set_item("description", p.description)?;
+            d.set_item("symmetry", p.symmetry.as_str())?;
+            d.set_item("ta", p.ta)?;
+            d.set_item("tb", p.tb)?;
+            Ok(d)
+        })
+        .collect()
+}
+
+/// Drawings of limit sets of Kleinian groups.
+#[pymodule]
+#[pyo3(name = "kleinian")]
+fn kleinian_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(generators, m)?)?;
+    m.add_function(wrap_pyfunction!(points, m)?)?;
+    m.add_function(wrap_pyfunction!(circles, m)?)?;
+    m.add_function(wrap_pyfunction!(render, m)?)?;
+    m.add_function(wrap_pyfunction!(list_presets, m)?)?;
+    Ok(())
+}
@@ -1,4 +1,11 @@
-import init, { draw } from './kleinian_web.js';
+import init, { State, Viewport, list_presets } from './kleinian_web.js';
+
+// Advice shown for each kind of error reported by the library.
+const ERROR_ADVICE = {
+	'degenerate-generators': 'These traces are degenerate for this symmetry type. Try moving them slightly.',
+	'numerical-breakdown': 'The computation broke down, so the group is probably not discrete. Try traces with larger real parts.',
+	'no-finite-points': 'No points could be drawn. Try different traces.',
+};
 
 function defaultValue(val, def) {
 	if (isNaN(val)) {
@@ -9,10 +16,108 @@ function defaultValue(val, def) {
 	}
 }
 
+// The key under which the most recent drawing is saved in localStorage.
+const STORAGE_KEY = 'kleinian-state';
+
+// Returns the shareable part of the parameters as a State, which the
+// caller must free.
+function makeState(params, view) {
+	const state = new State();
+	state.typ = params.typ;
+	state.a_re = params.a_re;
+	state.a_im = params.a_im;
+	state.b_re = params.b_re;
+	state.b_im = params.b_im;
+	state.iters = params.iters;
+	state.center_re = view.center_re;
+	state.center_im = view.center_im;
+	state.scale = view.scale;
+	state.foreground = params.foreground;
+	state.background = params.background;
+	state.palette = params.palette;
+	state.color_by = params.color_by;
+	state.mode = params.mode;
+	state.point_size = params.point_size;
+	return state;
+}
+
+// Returns the shareable part of the parameters, encoded for the URL, and
+// saves it in localStorage so that it is restored on the next visit.
+function saveState(params, view) {
+	const state = makeState(params, view);
+	const encoded = state.encode();
+	try {
+		localStorage.setItem(STORAGE_KEY, JSON.stringify(state.get_state()));
+	}
+	catch (e) {
+		// Storage may be full or disabled, which only loses the saved state.
+	}
+	state.free();
+	return encoded;
+}
+
+// Fills in the controls from a State, and returns its view.
+function applyState(state) {
+	document.getElementById("type").value = state.typ;
+	document.getElementById("a-re").value = state.a_re;
+	document.getElementById("a-im").value = state.a_im;
+	document.getElementById("b-re").value = state.b_re;
+	document.getElementById("b-im").value = state.b_im;
+	document.getElementById("iters").value = state.iters;
+	document.getElementById("foreground").value = state.foreground;
+	document.getElementById("background").value = state.background;
+	document.getElementById("palette").value = state.palette;
+	document.getElementById("color-by").value = state.color_by;
+	document.getElementById("mode").value = state.mode;
+	document.getElementById("point-size").value = state.point_size;
+	return state.scale === undefined ? null
+		: { center_re: state.center_re, center_im: state.center_im, scale: state.scale };
+}
+
+// Fills in the controls from an encoded state, and returns its view.
+function loadState(encoded) {
+	const state = State.decode(encoded);
+	const view = applyState(state);
+	state.free();
+	return view;
+}
+
+// Fills in the controls from the state saved in localStorage, and returns
+// its view, or returns undefined if nothing was saved.
+function loadSavedState() {
+	const saved = localStorage.getItem(STORAGE_KEY);
+	if (saved === null) {
+		return undefined;
+	}
+	const state = new State();
+	try {
+		state.set_state(JSON.parse(saved));
+		return applyState(state);
+	}
+	finally {
+		state.free();
+	}
+}
+
 async function run() {
 	await init();
 	const canvas = document.getElementById('drawing');
-	const ctx = canvas.getContext('2d');
+	// The points are drawn either on the 2d canvas or, with WebGL, on this
+	// one, which is shown in its place.  The mouse events are handled by the
+	// element that contains both.
+	const glCanvas = document.getElementById('drawing-gl');
+	const drawingArea = document.getElementById('drawing-area');
+	// All of the computation and drawing happens in a worker, so that the
+	// page stays responsive during long renders.
+	const worker = new Worker('worker.js', { type: 'module' });
+	const offscreen = canvas.transferControlToOffscreen();
+	const glOffscreen = glCanvas.transferControlToOffscreen();
+	const mapCanvas = document.getElementById('map');
+	const mapOffscreen = mapCanvas.transferControlToOffscreen();
+	worker.postMessage({ type: 'init', canvas: offscreen, gl: glOffscreen, map: mapOffscreen },
+		[offscreen, glOffscreen, mapOffscreen]);
+	// The region of the tr a plane shown in the map.
+	const map = { width: mapCanvas.width, height: mapCanvas.height, center_re: 2, center_im: 0, scale: 100, iters: 200, metric: 'discreteness' };
 
 	const renderBtn = document.getElementById('render');
 	document.getElementById('type').addEventListener('change', () => {
@@ -24,18 +129,227 @@ async function run() {
 		}
 	});
 
+	let params = null;
+	let view = null;
+
+	for (const preset of list_presets()) {
+		const button = document.createElement('button');
+		button.textContent = preset.name;
+		button.title = preset.description;
+		button.addEventListener('click', () => {
+			document.getElementById('type').value = preset.typ;
+			document.getElementById('type').dispatchEvent(new Event('change'));
+			document.getElementById('a-re').value = preset.a_re;
+			document.getElementById('a-im').value = preset.a_im;
+			document.getElementById('b-re').value = preset.b_re;
+			document.getElementById('b-im').value = preset.b_im;
+			renderBtn.click();
+		});
+		document.getElementById('presets').appendChild(button);
+	}
+
+	worker.onmessage = (e) => {
+		const msg = e.data;
+		const status = document.getElementById('status');
+		if (msg.type == 'progress') {
+			document.getElementById('progress').value = msg.value;
+		}
+		else if (msg.type == 'done') {
+			view = msg.view;
+			status.textContent = '';
+			const s = msg.stats;
+			document.getElementById('stats').textContent =
+				s.points + ' points in ' + Math.round(s.compute_ms) + ' ms ('
+				+ Math.round(s.points_per_sec) + ' points/s), drawn in '
+				+ Math.round(s.draw_ms) + ' ms';
+			history.replaceState(null, '', '#' + saveState(params, view));
+		}
+		else if (msg.type == 'png') {
+			const url = URL.createObjectURL(new Blob([msg.data], { type: 'image/png' }));
+			const a = document.createElement('a');
+			a.href = url;
+			a.download = 'kleinian.png';
+			a.click();
+			URL.revokeObjectURL(url);
+		}
+		else if (msg.type == 'picked') {
+			document.getElementById('a-re').value = msg.re.toFixed(3);
+			document.getElementById('a-im').value = msg.im.toFixed(3);
+			renderBtn.click();
+		}
+		else if (msg.type == 'nearest') {
+			const pt = msg.point;
+			document.getElementById('nearest').textContent = pt
+				? 'Nearest point: ' + pt.word + ' (' + pt.re.toFixed(6) + ', ' + pt.im.toFixed(6) + ')'
+				: '';
+		}
+		else if (msg.type == 'error') {
+			status.textContent = ERROR_ADVICE[msg.code] || msg.message;
+		}
+		else if (msg.type == 'webgl') {
+			if (!msg.available) {
+				const renderer = document.getElementById('renderer');
+				renderer.value = 'canvas';
+				renderer.querySelector('option[value="webgl"]').disabled = true;
+			}
+		}
+	};
+
+	function render() {
+		params.pixel_ratio = window.devicePixelRatio || 1;
+		const gl = params.renderer == 'webgl';
+		for (const c of [canvas, glCanvas]) {
+			c.style.width = params.width + 'px';
+			c.style.height = params.height + 'px';
+		}
+		canvas.style.display = gl ? 'none' : '';
+		glCanvas.style.display = gl ? '' : 'none';
+		params.show_circles = document.getElementById('show-circles').checked;
+		worker.postMessage({ type: 'render', params: params, view: view });
+	}
+
+	function readAllParams() {
+		return {
+			width: defaultValue(parseInt(document.getElementById("width").value),800),
+			height: defaultValue(parseInt(document.getElementById("height").value),800),
+			a_re: defaultValue(parseFloat(document.getElementById("a-re").value),2),
+			a_im: defaultValue(parseFloat(document.getElementById("a-im").value),0),
+			b_re: defaultValue(parseFloat(document.getElementById("b-re").value),2),
+			b_im: defaultValue(parseFloat(document.getElementById("b-im").value),0),
+			typ: document.getElementById("type").value,
+			iters: parseInt(document.getElementById("iters").value) || 100000,
+			foreground: document.getElementById("foreground").value,
+			background: document.getElementById("background").value,
+			palette: document.getElementById("palette").value,
+			color_by: document.getElementById("color-by").value,
+			mode: document.getElementById("mode").value,
+			point_size: defaultValue(parseInt(document.getElementById("point-size").value),1),
+			renderer: document.getElementById("renderer").value,
+		};
+	}
+
 	renderBtn.addEventListener('click', () => {
-		const width = defaultValue(parseInt(document.getElementById("width").value),800);
-		const height = defaultValue(parseInt(document.getElementById("height").value),800);
-		const a_re = defaultValue(parseFloat(document.getElementById("a-re").value),2);
-		const a_im = defaultValue(parseFloat(document.getElementById("a-im").value),0);
-		const b_re = defaultValue(parseFloat(document.getElementById("b-re").value),2);
-		const b_im = defaultValue(parseFloat(document.getElementById("b-im").value),0);
-		const typ = document.getElementById("type").value;
-		const iters = parseInt(document.getElementById("iters").value) || 100000;
-		canvas.width = width;
-		canvas.height = height;
-		draw(ctx, width, height, a_re, a_im, b_re, b_im, typ, iters);
+		params = readAllParams();
+		view = null;
+		render();
+	});
+
+	try {
+		// A link takes precedence over the drawing from the last visit.
+		const saved = location.hash.length > 1
+			? loadState(location.hash.substring(1)) : loadSavedState();
+		if (saved !== undefined) {
+			document.getElementById('type').dispatchEvent(new Event('change'));
+			params = readAllParams();
+			view = saved;
+			render();
+		}
+	}
+	catch (e) {
+		document.getElementById('status').textContent = e.message;
+	}
+
+	function readParams() {
+		return {
+			b_re: defaultValue(parseFloat(document.getElementById("b-re").value),2),
+			b_im: defaultValue(parseFloat(document.getElementById("b-im").value),0),
+			typ: document.getElementById("type").value,
+		};
+	}
+
+	document.getElementById('export').addEventListener('click', () => {
+		if (view) {
+			worker.postMessage({
+				type: 'export',
+				width: defaultValue(parseInt(document.getElementById("export-width").value),3840),
+				height: defaultValue(parseInt(document.getElementById("export-height").value),2160),
+				view: view,
+			});
+		}
+	});
+
+	document.getElementById('show-map').addEventListener('click', () => {
+		map.metric = document.getElementById('map-metric').value;
+		// Fractions p/q, separated by spaces.
+		const rays = document.getElementById("rays").value.split(/\s+/)
+			.map((f) => f.split('/').map((n) => parseInt(n)))
+			.filter((f) => f.length == 2 && f.every((n) => n >= 0) && (f[0] > 0 || f[1] > 0));
+		worker.postMessage({ type: 'map', map: map, params: readParams(), rays: rays });
+	});
+
+	mapCanvas.addEventListener('click', (e) => {
+		worker.postMessage({ type: 'pick', map: map, x: e.offsetX, y: e.offsetY });
+	});
+
+	// Returns the point of the plane under the pixel (x, y) of the drawing.
+	function pixelToComplex(x, y) {
+		const v = new Viewport(view.center_re, view.center_im, view.scale);
+		const z = v.pixel_to_complex(x, y, params.width, params.height);
+		v.free();
+		return z;
+	}
+
+	drawingArea.addEventListener('mousemove', (e) => {
+		if (view) {
+			const [re, im] = pixelToComplex(e.offsetX, e.offsetY);
+			const sign = im < 0 ? '-' : '+';
+			document.getElementById('coords').textContent =
+				re.toFixed(6) + ' ' + sign + ' ' + Math.abs(im).toFixed(6) + 'i';
+		}
+	});
+
+	drawingArea.addEventListener('wheel', (e) => {
+		if (!view) {
+			return;
+		}
+		e.preventDefault();
+		const factor = e.deltaY < 0 ? 1.25 : 0.8;
+		const dx = e.offsetX - 0.5 * params.width;
+		const dy = e.offsetY - 0.5 * params.height;
+		// Keep the point under the cursor fixed.
+		const [re, im] = pixelToComplex(e.offsetX, e.offsetY);
+		const scale = view.scale * factor;
+		view.center_re = re - dx / scale;
+		view.center_im = im + dy / scale;
+		view.scale = scale;
+		render();
+	});
+
+	// Double-clicking zooms in, centered on the point that was clicked.
+	drawingArea.addEventListener('dblclick', (e) => {
+		if (view) {
+			[view.center_re, view.center_im] = pixelToComplex(e.offsetX, e.offsetY);
+			view.scale *= 2;
+			render();
+		}
+	});
+
+	// Shift-clicking shows the word of the nearest point.
+	drawingArea.addEventListener('click', (e) => {
+		if (view && e.shiftKey) {
+			const [re, im] = pixelToComplex(e.offsetX, e.offsetY);
+			worker.postMessage({ type: 'nearest', re: re, im: im });
+		}
+	});
+
+	let dragStart = null;
+	drawingArea.addEventListener('mousedown', (e) => {
+		if (view && !e.shiftKey) {
+			dragStart = { x: e.offsetX, y: e.offsetY };
+		}
+	});
+	drawingArea.addEventListener('mouseup', (e) => {
+		if (dragStart) {
+			const dx = e.offsetX - dragStart.x;
+			const dy = e.offsetY - dragStart.y;
+			dragStart = null;
+			// A click without movement may be part of a double click.
+			if (dx != 0 || dy != 0) {
+				view.center_re -= dx / view.scale;
+				view.center_im += dy / view.scale;
+				render();
+			}
+		}
 	});
 }
 
@@ -0,0 +1,121 @@
+import init, { Cyclic } from './kleinian_web.js';
+
+// Pixels per unit length.
+const SCALE = 100;
+// The number of iterates drawn in each direction.
+const ITERATES = 12;
+// The time taken to apply the transformation once, in milliseconds.
+const PERIOD = 1500;
+
+function value(id) {
+	const v = parseFloat(document.getElementById(id).value);
+	return isNaN(v) ? 0 : v;
+}
+
+// Returns the polylines of a square grid centered at `c`, as arrays of
+// interleaved real and imaginary parts.
+function grid(c) {
+	const lines = [];
+	for (let i = -4; i <= 4; i++) {
+		const h = [];
+		const v = [];
+		for (let j = -40; j <= 40; j++) {
+			h.push(c.re + 0.1 * j, c.im + 0.1 * i);
+			v.push(c.re + 0.1 * i, c.im + 0.1 * j);
+		}
+		lines.push(h, v);
+	}
+	return lines;
+}
+
+async function run() {
+	await init();
+	const canvas = document.getElementById('drawing');
+	const ctx = canvas.getContext('2d');
+	const toX = (re) => 0.5 * canvas.width + re * SCALE;
+	const toY = (im) => 0.5 * canvas.height - im * SCALE;
+	let shape = { re: 0, im: 0.5 };
+	let cyclic = null;
+
+	function update() {
+		if (cyclic) {
+			cyclic.free();
+		}
+		cyclic = new Cyclic(value('p-re'), value('p-im'), value('q-re'), value('q-im'),
+			value('k-re'), value('k-im'));
+		document.getElementById('kind').textContent = 'This transformation is ' + cyclic.kind() + '.';
+	}
+
+	function drawPolyline(pts) {
+		ctx.beginPath();
+		for (let i = 0; i < pts.length; i += 2) {
+			// Skip segments that jump across the point mapped to infinity.
+			if (i == 0 || Math.abs(pts[i] - pts[i - 2]) + Math.abs(pts[i + 1] - pts[i - 1]) > 2) {
+				ctx.moveTo(toX(pts[i]), toY(pts[i + 1]));
+			}
+			else {
+				ctx.lineTo(toX(pts[i]), toY(pts[i + 1]));
+			}
+		}
+		ctx.stroke();
+	}
+
+	function frame(time) {
+		const t = (time % PERIOD) / PERIOD;
+		const kind = document.getElementById('shape').value;
+		ctx.fillStyle = '#ffffff';
+		ctx.fillRect(0, 0, canvas.width, canvas.height);
+		// The orbit of the shape, which stays fixed.
+		ctx.strokeStyle = '#bbbbbb';
+		ctx.fillStyle = '#bbbbbb';
+		if (kind == 'circle') {
+			const circles = cyclic.circle_orbit(shape.re, shape.im, 0.2, ITERATES);
+			for (let i = 0; i < circles.length; i += 3) {
+				ctx.beginPath();
+				ctx.arc(toX(circles[i]), toY(circles[i + 1]), circles[i + 2] * SCALE, 0, 2 * Math.PI);
+				ctx.stroke();
+			}
+		}
+		else if (kind == 'point') {
+			const pts = cyclic.point_orbit(shape.re, shape.im, ITERATES);
+			for (let i = 0; i < pts.length; i += 2) {
+				ctx.fillRect(toX(pts[i]) - 2, toY(pts[i + 1]) - 2, 4, 4);
+			}
+		}
+		// The shape, moved part of the way by the transformation.
+		ctx.strokeStyle = '#000000';
+		ctx.fillStyle = '#000000';
+		if (kind == 'grid') {
+			for (const line of grid(shape)) {
+				drawPolyline(cyclic.map_points(line, t));
+			}
+		}
+		else if (kind == 'circle') {
+			const pts = [];
+			for (let i = 0; i <= 100; i++) {
+				const a = 2 * Math.PI * i / 100;
+				pts.push(shape.re + 0.2 * Math.cos(a), shape.im + 0.2 * Math.sin(a));
+			}
+			drawPolyline(cyclic.map_points(pts, t));
+		}
+		else {
+			const z = cyclic.map_points([shape.re, shape.im], t);
+			ctx.fillRect(toX(z[0]) - 3, toY(z[1]) - 3, 6, 6);
+		}
+		requestAnimationFrame(frame);
+	}
+
+	for (const id of ['p-re', 'p-im', 'q-re', 'q-im', 'k-re', 'k-im']) {
+		document.getElementById(id).addEventListener('change', update);
+	}
+	canvas.addEventListener('click', (e) => {
+		shape = {
+			re: (e.offsetX - 0.5 * canvas.width) / SCALE,
+			im: (0.5 * canvas.height - e.offsetY) / SCALE,
+		};
+	});
+	update();
+	requestAnimationFrame(frame);
+}
+
+run();
@@ -0,0 +1,217 @@
+import init, { start, GlRenderer, generator_circles, param_map, param_map_point, param_map_ray, Style } from './kleinian_web.js';
+
+// The target time, in milliseconds, between progress updates.  The number
+// of points computed between updates is tuned to the speed of the machine.
+const FRAME_MS = 50;
+const MIN_CHUNK = 1000;
+const MAX_CHUNK = 1000000;
+
+let canvas = null;
+let ctx = null;
+// The canvas drawn on with WebGL, and its renderer, which is null if the
+// browser does not support WebGL2.
+let glCanvas = null;
+let glRenderer = null;
+let mapCtx = null;
+let current = null;
+// The most recent finished render, kept for exporting.
+let last = null;
+
+function drawGeneratorCircles(p, view) {
+	const circles = generator_circles(p.a_re, p.a_im, p.b_re, p.b_im, p.typ);
+	const colors = ['#d62728', '#1f77b4', '#ff7f0e', '#2ca02c'];
+	ctx.setTransform(p.pixel_ratio, 0, 0, p.pixel_ratio, 0, 0);
+	ctx.lineWidth = 1;
+	for (let i = 0; i < 4; i++) {
+		const x = 0.5 * p.width + (circles[3 * i] - view.center_re) * view.scale;
+		const y = 0.5 * p.height - (circles[3 * i + 1] - view.center_im) * view.scale;
+		ctx.strokeStyle = colors[i];
+		ctx.beginPath();
+		ctx.arc(x, y, circles[3 * i + 2] * view.scale, 0, 2 * Math.PI);
+		ctx.stroke();
+	}
+	ctx.resetTransform();
+}
+
+function render(p, view) {
+	if (current) {
+		current.cancel();
+	}
+	let handle;
+	try {
+		handle = start(p.a_re, p.a_im, p.b_re, p.b_im, p.typ, p.iters);
+	}
+	catch (e) {
+		postMessage({ type: 'error', code: e.code, message: e.message });
+		return;
+	}
+	current = handle;
+	let chunk = MIN_CHUNK;
+	const step = () => {
+		if (handle.is_cancelled()) {
+			handle.free();
+			return;
+		}
+		try {
+			const value = handle.step(chunk);
+			const stats = handle.stats();
+			postMessage({ type: 'progress', value: value });
+			if (stats.points_per_sec > 0) {
+				chunk = Math.min(Math.max(Math.round(stats.points_per_sec * FRAME_MS / 1000), MIN_CHUNK), MAX_CHUNK);
+			}
+			stats.free();
+			if (!handle.done()) {
+				setTimeout(step, 0);
+				return;
+			}
+			finish(handle, p, view);
+			if (last) {
+				last.handle.free();
+			}
+			last = { handle: handle, params: p };
+		}
+		catch (e) {
+			postMessage({ type: 'error', code: e.code, message: e.message });
+			handle.free();
+		}
+		current = null;
+	};
+	step();
+}
+
+function makeStyle(p, pixel_ratio) {
+	const style = new Style();
+	try {
+		style.set_foreground(p.foreground);
+		style.set_background(p.background);
+		style.set_palette(p.palette);
+		style.set_color_by(p.color_by);
+		style.set_point_size(p.point_size);
+		style.set_mode(p.mode);
+		style.set_pixel_ratio(pixel_ratio);
+	}
+	catch (e) {
+		style.free();
+		throw e;
+	}
+	return style;
+}
+
+// Exports the last render as a PNG image, showing the same region as
+// `view` but at a different resolution.
+function exportPng(width, height, view) {
+	const p = last.params;
+	const style = makeStyle(p, 1);
+	try {
+		const scale = view.scale * Math.min(width / p.width, height / p.height);
+		const png = last.handle.export_png(width, height, style, view.center_re, view.center_im, scale);
+		postMessage({ type: 'png', data: png }, [png.buffer]);
+	}
+	finally {
+		style.free();
+	}
+}
+
+function finish(handle, p, view) {
+	const gl = p.renderer == 'webgl' && glRenderer;
+	const target = gl ? glCanvas : canvas;
+	const pw = Math.round(p.width * p.pixel_ratio);
+	const ph = Math.round(p.height * p.pixel_ratio);
+	if (target.width != pw || target.height != ph) {
+		target.width = pw;
+		target.height = ph;
+	}
+	const style = makeStyle(p, p.pixel_ratio);
+	try {
+		const v = gl
+			? (view
+				? handle.draw_gl(glRenderer, p.width, p.height, style, view.center_re, view.center_im, view.scale)
+				: handle.draw_gl(glRenderer, p.width, p.height, style))
+			: (view
+				? handle.draw(ctx, p.width, p.height, style, view.center_re, view.center_im, view.scale)
+				: handle.draw(ctx, p.width, p.height, style));
+		const result = { center_re: v.center_re, center_im: v.center_im, scale: v.scale };
+		v.free();
+		// The generator circles are only drawn on the 2d canvas.
+		if (p.show_circles && !gl) {
+			drawGeneratorCircles(p, result);
+		}
+		const stats = handle.stats();
+		postMessage({
+			type: 'done',
+			view: result,
+			stats: {
+				points: stats.points,
+				compute_ms: stats.compute_ms,
+				draw_ms: stats.draw_ms,
+				points_per_sec: stats.points_per_sec,
+				min_radius: stats.min_radius,
+			},
+		});
+		stats.free();
+	}
+	finally {
+		style.free();
+	}
+}
+
+const ready = init();
+
+onmessage = async (e) => {
+	await ready;
+	const msg = e.data;
+	if (msg.type == 'init') {
+		canvas = msg.canvas;
+		ctx = canvas.getContext('2d');
+		glCanvas = msg.gl;
+		const glCtx = glCanvas.getContext('webgl2');
+		try {
+			glRenderer = glCtx ? new GlRenderer(glCtx) : null;
+		}
+		catch (e) {
+			// The shaders failed to compile, so only the 2d canvas is used.
+			glRenderer = null;
+		}
+		postMessage({ type: 'webgl', available: glRenderer !== null });
+		mapCtx = msg.map.getContext('2d');
+	}
+	else if (msg.type == 'map') {
+		const m = msg.map;
+		param_map(mapCtx, m.width, m.height, m.center_re, m.center_im, m.scale,
+			msg.params.b_re, msg.params.b_im, msg.params.typ, m.iters, m.metric);
+		mapCtx.strokeStyle = 'red';
+		mapCtx.beginPath();
+		for (const [p, q] of msg.rays) {
+			const segs = param_map_ray(m.width, m.height, m.center_re, m.center_im, m.scale,
+				msg.params.b_re, msg.params.b_im, msg.params.typ, p, q);
+			for (let i = 0; i + 3 < segs.length; i += 4) {
+				mapCtx.moveTo(segs[i], segs[i + 1]);
+				mapCtx.lineTo(segs[i + 2], segs[i + 3]);
+			}
+		}
+		mapCtx.stroke();
+	}
+	else if (msg.type == 'export') {
+		if (last) {
+			try {
+				exportPng(msg.width, msg.height, msg.view);
+			}
+			catch (e) {
+				postMessage({ type: 'error', code: e.code, message: e.message });
+			}
+		}
+	}
+	else if (msg.type == 'pick') {
+		const m = msg.map;
+		const ta = param_map_point(msg.x, msg.y, m.width, m.height, m.center_re, m.center_im, m.scale);
+		postMessage({ type: 'picked', re: ta[0], im: ta[1] });
+	}
+	else if (msg.type == 'nearest') {
+		if (last) {
+			postMessage({ type: 'nearest', point: last.handle.nearest(msg.re, msg.im) });
+		}
+	}
+	else if (msg.type == 'render') {
+		render(msg.params, msg.view);
+	}
+};
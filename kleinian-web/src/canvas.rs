@@ -0,0 +1,201 @@
+//! Drawing to canvases.  Everything else in this crate only depends on
+//! `js-sys`, so that it can be used outside of a browser by building
+//! without the `canvas` feature.
+
+use crate::minimap::param_map_pixels;
+use crate::{
+    direct_pixels, js_error, make_generators, render_pixels, view_from, FrameSequence, Morph,
+    Render, Style, Viewport,
+};
+use kleinian::presets;
+use kleinian::LimitPoint;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::{CanvasRenderingContext2d, ImageData, OffscreenCanvasRenderingContext2d};
+
+/// Copies the image to a canvas, with its left edge at `x`.  The context may
+/// belong to either an ordinary canvas or an `OffscreenCanvas`, so that
+/// rendering can be done in a worker.
+pub(crate) fn put_image_data(ctx: &JsValue, data: &ImageData, x: f64) -> Result<(), JsValue> {
+    if let Some(ctx) = ctx.dyn_ref::<CanvasRenderingContext2d>() {
+        ctx.put_image_data(data, x, 0.0)
+    } else if let Some(ctx) = ctx.dyn_ref::<OffscreenCanvasRenderingContext2d>() {
+        ctx.put_image_data(data, x, 0.0)
+    } else {
+        Err(JsError::new("expected a 2d canvas rendering context").into())
+    }
+}
+
+/// Draws the points.  See `render_pixels` for the meaning of the
+/// parameters.  The canvas should have been sized to the full resolution.
+/// Returns the view that was used.
+fn rasterize(
+    ctx: &JsValue,
+    width: u32,
+    height: u32,
+    pts: &[LimitPoint],
+    view: Option<Viewport>,
+    style: &Style,
+) -> Result<Viewport, JsValue> {
+    let (pixel_data, pw, ph, view) = render_pixels(width, height, pts, view, style)?;
+    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixel_data), pw, ph)?;
+    put_image_data(ctx, &data, 0.0)?;
+    Ok(view)
+}
+
+/// Draws the limit set.  If `center_re`, `center_im`, and `scale` are all
+/// given, they determine the part of the plane that is shown; otherwise the
+/// view is chosen to fit the whole limit set.  Returns the view that was used.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn draw(
+    ctx: &JsValue,
+    width: u32,
+    height: u32,
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+    style: &Style,
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Result<Viewport, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    let pts = kleinian::generate_labelled_points(gens, iters).map_err(js_error)?;
+    let view = view_from(center_re, center_im, scale);
+    rasterize(ctx, width, height, &pts, view, style)
+}
+
+/// Like `draw`, but draws the limit set straight into the image without
+/// storing any points; see `render_direct`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_direct(
+    ctx: &JsValue,
+    width: u32,
+    height: u32,
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    style: &Style,
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Result<Viewport, JsValue> {
+    let gens = || make_generators(typ, re1, im1, re2, im2);
+    let view = view_from(center_re, center_im, scale);
+    let (bitmap, pw, ph, view) = direct_pixels(width, height, gens, view, style)?;
+    let data = ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(bytemuck::cast_slice(&bitmap)),
+        pw,
+        ph,
+    )?;
+    put_image_data(ctx, &data, 0.0)?;
+    Ok(view)
+}
+
+/// Like `draw`, but draws the preset with the given name.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_preset(
+    name: &str,
+    ctx: &JsValue,
+    width: u32,
+    height: u32,
+    iters: usize,
+    style: &Style,
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Result<Viewport, JsValue> {
+    let preset =
+        presets::find(name).ok_or_else(|| JsError::new(&format!("unknown preset: {}", name)))?;
+    let pts = kleinian::generate_labelled_points(preset.generators(), iters).map_err(js_error)?;
+    let view = view_from(center_re, center_im, scale);
+    rasterize(ctx, width, height, &pts, view, style)
+}
+
+#[wasm_bindgen]
+impl Render {
+    /// Draws the points computed so far.  See `draw` for the meaning of the
+    /// view parameters.  Returns the view that was used.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self,
+        ctx: &JsValue,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Viewport, JsValue> {
+        let start = js_sys::Date::now();
+        let pts: Vec<LimitPoint> = self.queue.labelled_points().collect();
+        let view = view_from(center_re, center_im, scale);
+        let result = rasterize(ctx, width, height, &pts, view, style);
+        self.draw_ms = js_sys::Date::now() - start;
+        result
+    }
+}
+
+#[wasm_bindgen]
+impl Morph {
+    /// Draws the frame at time `t`.  See `draw` for the meaning of the
+    /// other parameters.  Returns the view that was used.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_frame(
+        &self,
+        t: f64,
+        ctx: &JsValue,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Viewport, JsValue> {
+        let pts = self.points_at(t);
+        let view = view_from(center_re, center_im, scale);
+        rasterize(ctx, width, height, &pts, view, style)
+    }
+}
+
+#[wasm_bindgen]
+impl FrameSequence {
+    /// Draws frame `i` to a canvas, which should have been sized to
+    /// `pixel_width` by `pixel_height`.
+    pub fn draw(&mut self, i: u32, ctx: &JsValue) -> Result<(), JsValue> {
+        let (pixel_data, pw, ph) = self.pixels(i)?;
+        let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixel_data), pw, ph)?;
+        put_image_data(ctx, &data, 0.0)
+    }
+}
+
+/// Draws the map rendered by `param_map_pixels`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn param_map(
+    ctx: &JsValue,
+    width: u32,
+    height: u32,
+    center_re: f64,
+    center_im: f64,
+    scale: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+    metric: &str,
+) -> Result<(), JsValue> {
+    let pixel_data = param_map_pixels(
+        width, height, center_re, center_im, scale, re2, im2, typ, iters, metric,
+    )?;
+    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixel_data), width, height)?;
+    put_image_data(ctx, &data, 0.0)
+}
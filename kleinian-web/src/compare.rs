@@ -0,0 +1,163 @@
+#[cfg(feature = "canvas")]
+use crate::canvas::put_image_data;
+use crate::{fit_view, render_pixels, start, view_from, Render, Style, Viewport};
+use kleinian::LimitPoint;
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "canvas")]
+use wasm_bindgen::Clamped;
+#[cfg(feature = "canvas")]
+use web_sys::ImageData;
+
+/// Two computations that proceed together and are drawn with the same view,
+/// so that the effect of changing the traces can be seen.
+#[wasm_bindgen]
+pub struct Comparison {
+    left: Render,
+    right: Render,
+}
+
+/// Like `start`, but for two sets of traces `(re1a + i im1a, re2a + i im2a)`
+/// and `(re1b + i im1b, re2b + i im2b)` of the same symmetry type.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn compare(
+    re1a: f64,
+    im1a: f64,
+    re2a: f64,
+    im2a: f64,
+    re1b: f64,
+    im1b: f64,
+    re2b: f64,
+    im2b: f64,
+    typ: &str,
+    iters: usize,
+) -> Result<Comparison, JsValue> {
+    Ok(Comparison {
+        left: start(re1a, im1a, re2a, im2a, typ, iters)?,
+        right: start(re1b, im1b, re2b, im2b, typ, iters)?,
+    })
+}
+
+/// RGBA pixels, along with their width and height.
+type Pixels = (Vec<u8>, u32, u32);
+
+impl Comparison {
+    /// Renders both sides.  If no view is given, the view is chosen to fit
+    /// the points of both sides.
+    fn pixels(
+        &self,
+        width: u32,
+        height: u32,
+        style: &Style,
+        view: Option<Viewport>,
+    ) -> Result<([Pixels; 2], Viewport), JsValue> {
+        let left: Vec<LimitPoint> = self.left.queue.labelled_points().collect();
+        let right: Vec<LimitPoint> = self.right.queue.labelled_points().collect();
+        let view = match view {
+            Some(v) => v,
+            None => {
+                let both: Vec<LimitPoint> = left.iter().chain(&right).cloned().collect();
+                fit_view(&both, width, height)?
+            }
+        };
+        let (l, lw, lh, _) = render_pixels(width, height, &left, Some(view), style)?;
+        let (r, rw, rh, _) = render_pixels(width, height, &right, Some(view), style)?;
+        Ok(([(l, lw, lh), (r, rw, rh)], view))
+    }
+}
+
+#[wasm_bindgen]
+impl Comparison {
+    /// Computes up to `n_points` more points on each side.  Returns the
+    /// fraction of the computation that has been completed.
+    pub fn step(&mut self, n_points: usize) -> Result<f64, JsValue> {
+        self.left.step(n_points)?;
+        self.right.step(n_points)?;
+        Ok(self.progress())
+    }
+    pub fn progress(&self) -> f64 {
+        f64::min(self.left.progress(), self.right.progress())
+    }
+    pub fn done(&self) -> bool {
+        self.left.done() && self.right.done()
+    }
+    pub fn cancel(&mut self) {
+        self.left.cancel();
+        self.right.cancel();
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.left.is_cancelled()
+    }
+    /// Renders the two sides next to each other as RGBA pixels, in an image
+    /// that is twice as wide as each side.  See `draw` for the meaning of
+    /// the parameters.
+    pub fn render(
+        &self,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let view = view_from(center_re, center_im, scale);
+        let ([(l, pw, ph), (r, _, _)], _) = self.pixels(width, height, style, view)?;
+        let row = 4 * pw as usize;
+        let mut pixel_data = Vec::with_capacity(2 * row * ph as usize);
+        for (a, b) in l.chunks_exact(row).zip(r.chunks_exact(row)) {
+            pixel_data.extend_from_slice(a);
+            pixel_data.extend_from_slice(b);
+        }
+        Ok(pixel_data)
+    }
+}
+
+#[cfg(feature = "canvas")]
+#[wasm_bindgen]
+impl Comparison {
+    /// Draws the two sides into the left and right halves of one canvas,
+    /// each of which is `width` by `height`.  See `draw` for the meaning
+    /// of the other parameters.  Returns the view that was used.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &self,
+        ctx: &JsValue,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Viewport, JsValue> {
+        let view = view_from(center_re, center_im, scale);
+        let (sides, view) = self.pixels(width, height, style, view)?;
+        let mut x = 0.0;
+        for (pixel_data, pw, ph) in sides {
+            let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixel_data), pw, ph)?;
+            put_image_data(ctx, &data, x)?;
+            x += pw as f64;
+        }
+        Ok(view)
+    }
+    /// Like `draw`, but draws the two sides on separate canvases.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_separate(
+        &self,
+        left_ctx: &JsValue,
+        right_ctx: &JsValue,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Viewport, JsValue> {
+        let view = view_from(center_re, center_im, scale);
+        let (sides, view) = self.pixels(width, height, style, view)?;
+        for (ctx, (pixel_data, pw, ph)) in [left_ctx, right_ctx].into_iter().zip(sides) {
+            let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixel_data), pw, ph)?;
+            put_image_data(ctx, &data, 0.0)?;
+        }
+        Ok(view)
+    }
+}
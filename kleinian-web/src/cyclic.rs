@@ -0,0 +1,79 @@
+use kleinian::cyclic::{apply_all, CyclicGroup};
+use kleinian::flow;
+use kleinian::{Circle, Cpx};
+use wasm_bindgen::prelude::*;
+
+/// The group generated by a single Möbius transformation, for showing how
+/// one transformation moves the plane before introducing groups with two
+/// generators.
+#[wasm_bindgen]
+pub struct Cyclic {
+    group: CyclicGroup,
+}
+
+#[wasm_bindgen]
+impl Cyclic {
+    /// Describes the transformation that fixes `p` and `q` and multiplies
+    /// (z - p) / (z - q) by `k`.  If `p` and `q` are equal, the
+    /// transformation adds `k` to 1 / (z - p) instead.
+    #[wasm_bindgen(constructor)]
+    pub fn new(p_re: f64, p_im: f64, q_re: f64, q_im: f64, k_re: f64, k_im: f64) -> Self {
+        Cyclic {
+            group: CyclicGroup::new(
+                Cpx::new(p_re, p_im),
+                Cpx::new(q_re, q_im),
+                Cpx::new(k_re, k_im),
+            ),
+        }
+    }
+    /// Returns "elliptic", "parabolic", "hyperbolic", or "loxodromic".
+    pub fn kind(&self) -> String {
+        self.group.kind().as_str().to_string()
+    }
+    /// Returns the images of the point under the powers of the
+    /// transformation from -`n` to `n`, as interleaved real and imaginary
+    /// parts.
+    pub fn point_orbit(&self, re: f64, im: f64, n: usize) -> Vec<f64> {
+        self.group
+            .orbit(Cpx::new(re, im), n)
+            .into_iter()
+            .flat_map(|z| [z.re, z.im])
+            .collect()
+    }
+    /// Like `point_orbit`, but for a circle.  Returns (center real part,
+    /// center imaginary part, radius) triples.
+    pub fn circle_orbit(&self, re: f64, im: f64, radius: f64, n: usize) -> Vec<f64> {
+        self.group
+            .circle_orbit(Circle::new(Cpx::new(re, im), radius), n)
+            .into_iter()
+            .flat_map(|c| {
+                let z = c.center_point().affine();
+                [z.re, z.im, 1.0 / c.radius_inv()]
+            })
+            .collect()
+    }
+    /// Applies the `t`th power of the transformation to points given as
+    /// interleaved real and imaginary parts.  Fractional powers move the
+    /// points part of the way, so increasing `t` from 0 to 1 animates the
+    /// transformation.
+    pub fn map_points(&self, points: &[f64], t: f64) -> Vec<f64> {
+        let m = self.group.matrix(t);
+        let mut pts: Vec<Cpx> = points
+            .chunks_exact(2)
+            .map(|p| Cpx::new(p[0], p[1]))
+            .collect();
+        apply_all(&m, &mut pts);
+        pts.iter().flat_map(|z| [z.re, z.im]).collect()
+    }
+    /// Returns `num_lines` streamlines of the flow of the transformation,
+    /// each of which is `samples` points from the -`powers`th power of the
+    /// transformation to the `powers`th, as interleaved real and imaginary
+    /// parts, one line after another.
+    pub fn streamlines(&self, num_lines: usize, powers: f64, samples: usize) -> Vec<f64> {
+        flow::streamlines(&self.group.matrix(1.0), num_lines, powers, samples)
+            .into_iter()
+            .flatten()
+            .flat_map(|z| [z.re, z.im])
+            .collect()
+    }
+}
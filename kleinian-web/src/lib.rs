@@ -1,11 +1,327 @@
-use kleinian::Cpx;
+#[cfg(feature = "canvas")]
+mod canvas;
+mod compare;
+mod cyclic;
+mod minimap;
+mod state;
+#[cfg(feature = "canvas")]
+mod webgl;
+
+use kleinian::index::PointIndex;
+use kleinian::morph::GroupPath;
+use kleinian::presets::{self, Symmetry};
+use kleinian::raster;
+use kleinian::render;
+use kleinian::window::CoordTransform;
+use kleinian::{CircleQueue, Cpx, Generator, LimitPoint};
 use wasm_bindgen::prelude::*;
-use wasm_bindgen::Clamped;
-use web_sys::{CanvasRenderingContext2d, ImageData};
 
+/// The number of points used to fit the view when drawing directly.
+const DIRECT_SAMPLE: usize = 10000;
+
+/// The region of the complex plane shown by a call to `draw`.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Viewport {
+    pub center_re: f64,
+    pub center_im: f64,
+    /// Pixels per unit length.
+    pub scale: f64,
+}
+
+#[wasm_bindgen]
+impl Viewport {
+    #[wasm_bindgen(constructor)]
+    pub fn new(center_re: f64, center_im: f64, scale: f64) -> Self {
+        Viewport {
+            center_re,
+            center_im,
+            scale,
+        }
+    }
+    /// Returns the point, as `[re, im]`, drawn at the pixel `(x, y)` of a
+    /// `width` by `height` canvas showing this view.  As with the view,
+    /// pixels are CSS pixels, with `y` increasing downwards.
+    pub fn pixel_to_complex(&self, x: f64, y: f64, width: u32, height: u32) -> Vec<f64> {
+        let z = self.transform(width, height).inverse(x, height as f64 - y);
+        vec![z.re, z.im]
+    }
+    /// The inverse of `pixel_to_complex`.  Returns `[x, y]`.
+    pub fn complex_to_pixel(&self, re: f64, im: f64, width: u32, height: u32) -> Vec<f64> {
+        let (x, y) = self.transform(width, height).map(&Cpx::new(re, im));
+        vec![x, height as f64 - y]
+    }
+}
+
+impl Viewport {
+    fn transform(&self, width: u32, height: u32) -> CoordTransform {
+        let center = Cpx::new(self.center_re, self.center_im);
+        CoordTransform::centered(center, self.scale, width as usize, height as usize)
+    }
+}
+
+/// Converts a library error into a JavaScript `Error` whose `code` property
+/// identifies the kind of error.
+fn js_error(e: kleinian::Error) -> JsValue {
+    let err = js_sys::Error::new(&e.to_string());
+    let _ = js_sys::Reflect::set(&err, &"code".into(), &e.code().into());
+    err.into()
+}
+
+fn make_generators(typ: &str, re1: f64, im1: f64, re2: f64, im2: f64) -> [Generator; 4] {
+    let symmetry = typ.parse().unwrap_or(Symmetry::Oi);
+    symmetry.generators(Cpx::new(re1, im1), Cpx::new(re2, im2))
+}
+
+/// Computes approximately `iters` points of the limit set, returned as
+/// interleaved real and imaginary parts.
+#[wasm_bindgen]
+pub fn compute_points(
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+) -> Result<Vec<f64>, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    Ok(kleinian::generate_points(gens, iters)
+        .map_err(js_error)?
+        .into_iter()
+        .flat_map(|z| [z.re, z.im])
+        .collect())
+}
+
+/// Computes the circles whose centers are returned by `compute_points`,
+/// returned as consecutive (center real part, center imaginary part, radius)
+/// triples.  The circles are ordered along the limit set.
+#[wasm_bindgen]
+pub fn compute_circles(
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+) -> Result<Vec<f64>, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    Ok(kleinian::generate_circles(gens, iters)
+        .map_err(js_error)?
+        .into_iter()
+        .flat_map(|c| {
+            let z = c.center_point().affine();
+            [z.re, z.im, 1.0 / c.radius_inv()]
+        })
+        .collect())
+}
+
+/// Returns how the group is elementary, such as `"finite, of order 12"`,
+/// or `undefined` if it is not.  The limit set of an elementary group has
+/// at most two points, so drawing it is not worthwhile.
+#[wasm_bindgen]
+pub fn elementary(re1: f64, im1: f64, re2: f64, im2: f64, typ: &str) -> Option<String> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    kleinian::elementary::elementary(&gens).map(|e| e.to_string())
+}
+
+/// Returns the circles attached to the generators a, b, a^{-1}, b^{-1},
+/// in that order, as (center real part, center imaginary part, radius)
+/// triples.  Each generator maps the circle of its inverse to the
+/// complement of its own circle.
+#[wasm_bindgen]
+pub fn generator_circles(
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+) -> Result<Vec<f64>, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    kleinian::check_generators(&gens).map_err(js_error)?;
+    Ok(gens
+        .iter()
+        .flat_map(|g| {
+            let z = g.circle.center_point().affine();
+            [z.re, z.im, 1.0 / g.circle.radius_inv()]
+        })
+        .collect())
+}
+
+/// Returns the view given by the optional view parameters of the drawing
+/// functions, if they are all present.
+fn view_from(
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Option<Viewport> {
+    match (center_re, center_im, scale) {
+        (Some(re), Some(im), Some(s)) => Some(Viewport::new(re, im, s)),
+        _ => None,
+    }
+}
+
+/// Renders the points into an RGBA buffer.  If `view` is `None`, the view
+/// is chosen to fit all of the points.  The imaginary axis points up.
+/// Returns the pixels, their width and height, and the view that was used.
+///
+/// The width, height, and view are in CSS pixels.  The image is drawn at
+/// `style.pixel_ratio` times that resolution.
+fn render_pixels(
+    width: u32,
+    height: u32,
+    pts: &[LimitPoint],
+    view: Option<Viewport>,
+    style: &Style,
+) -> Result<(Vec<u8>, u32, u32, Viewport), JsValue> {
+    let ratio = style.pixel_ratio;
+    let pw = (width as f64 * ratio).round() as u32;
+    let ph = (height as f64 * ratio).round() as u32;
+    let (w, h) = (pw as usize, ph as usize);
+    let view = match view {
+        Some(v) => v,
+        None => fit_view(pts, width, height)?,
+    };
+    let center = Cpx::new(view.center_re, view.center_im);
+    let trans = CoordTransform::centered(center, view.scale * ratio, w, h);
+    let mut rs = style.style.clone();
+    rs.point_size = (rs.point_size as f64 * ratio).round() as usize;
+    let pixel_data = render::render_rgba(pts, &trans, w, h, &rs);
+    Ok((pixel_data, pw, ph, view))
+}
+
+/// Like `render_pixels`, but draws the limit set of the generators straight
+/// into the image with `raster::generate_into_bitmap`, without storing the
+/// points.  The view, if not given, is fitted to a sample of the points.
+/// The generators are made twice, by calling `gens`.
+fn direct_pixels<G: Fn() -> [Generator; 4]>(
+    width: u32,
+    height: u32,
+    gens: G,
+    view: Option<Viewport>,
+    style: &Style,
+) -> Result<(Vec<u32>, u32, u32, Viewport), JsValue> {
+    let ratio = style.pixel_ratio;
+    let pw = (width as f64 * ratio).round() as u32;
+    let ph = (height as f64 * ratio).round() as u32;
+    let (w, h) = (pw as usize, ph as usize);
+    let view = match view {
+        Some(v) => v,
+        None => {
+            let pts =
+                kleinian::generate_labelled_points(gens(), DIRECT_SAMPLE).map_err(js_error)?;
+            fit_view(&pts, width, height)?
+        }
+    };
+    let center = Cpx::new(view.center_re, view.center_im);
+    let trans = CoordTransform::centered(center, view.scale * ratio, w, h);
+    let mut bitmap = vec![raster::pack_rgba(style.style.background); w * h];
+    let foreground = raster::pack_rgba(style.style.foreground);
+    raster::generate_into_bitmap(&gens(), &mut bitmap, w, h, &trans, foreground, 64 * w * h)
+        .map_err(js_error)?;
+    Ok((bitmap, pw, ph, view))
+}
+
+/// Returns the view that fits the points into a `width` by `height` canvas.
+fn fit_view(pts: &[LimitPoint], width: u32, height: u32) -> Result<Viewport, JsValue> {
+    let zs: Vec<Cpx> = pts.iter().map(|p| p.z).collect();
+    let trans =
+        kleinian::window::fit_transform(&zs, width as f64, height as f64).map_err(js_error)?;
+    let center = trans.center(width as usize, height as usize);
+    Ok(Viewport::new(center.re, center.im, trans.scale()))
+}
+
+/// Encodes RGBA pixels as a PNG file.
+fn encode_png(pixel_data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    let png_error = |e: png::EncodingError| JsValue::from(JsError::new(&e.to_string()));
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(pixel_data).map_err(png_error)?;
+    writer.finish().map_err(png_error)?;
+    Ok(out)
+}
+
+/// Options controlling the appearance of drawings.
 #[wasm_bindgen]
-pub fn draw(
-    ctx: &CanvasRenderingContext2d,
+#[derive(Clone)]
+pub struct Style {
+    style: render::Style,
+    /// The number of device pixels per CSS pixel.
+    pixel_ratio: f64,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            style: render::Style::default(),
+            pixel_ratio: 1.0,
+        }
+    }
+}
+
+fn parse_err(e: render::UnknownName) -> JsValue {
+    JsError::new(&e.to_string()).into()
+}
+
+#[wasm_bindgen]
+impl Style {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the color of the points, given as `#rrggbb`.
+    pub fn set_foreground(&mut self, color: &str) -> Result<(), JsValue> {
+        self.style.foreground = render::parse_color(color).map_err(parse_err)?;
+        Ok(())
+    }
+    /// Sets the background color, given as `#rrggbb`.
+    pub fn set_background(&mut self, color: &str) -> Result<(), JsValue> {
+        self.style.background = render::parse_color(color).map_err(parse_err)?;
+        Ok(())
+    }
+    /// Sets the palette: `gradient`, `rainbow`, or `fire`.
+    pub fn set_palette(&mut self, palette: &str) -> Result<(), JsValue> {
+        self.style.palette = palette.parse().map_err(parse_err)?;
+        Ok(())
+    }
+    /// Sets what the color of a point depends on: `uniform`, `generator`,
+    /// `depth`, or `radius`.
+    pub fn set_color_by(&mut self, color_by: &str) -> Result<(), JsValue> {
+        self.style.color_by = color_by.parse().map_err(parse_err)?;
+        Ok(())
+    }
+    /// Sets the side length, in pixels, of the square drawn for each point.
+    pub fn set_point_size(&mut self, size: usize) {
+        self.style.point_size = size;
+    }
+    /// Sets the rendering mode: `points`, or `density` to shade pixels
+    /// according to how many points land in them.
+    pub fn set_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.style.mode = mode.parse().map_err(parse_err)?;
+        Ok(())
+    }
+    /// Sets the number of device pixels per CSS pixel, usually
+    /// `window.devicePixelRatio`.  Drawing functions take their sizes in
+    /// CSS pixels and draw at the full resolution of the display.
+    pub fn set_pixel_ratio(&mut self, ratio: f64) {
+        self.pixel_ratio = ratio;
+    }
+    /// Sets the exponent used when tone-mapping densities.
+    pub fn set_gamma(&mut self, gamma: f64) {
+        self.style.gamma = gamma;
+    }
+}
+
+/// Renders the limit set as RGBA pixels, with the imaginary axis pointing
+/// up.  The image is `style.pixel_ratio` times as large as `width` by
+/// `height`.  If `center_re`, `center_im`, and `scale` are all given, they
+/// determine the part of the plane that is shown; otherwise the view is
+/// chosen to fit the whole limit set.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn render_rgba(
     width: u32,
     height: u32,
     re1: f64,
@@ -14,27 +330,530 @@ pub fn draw(
     im2: f64,
     typ: &str,
     iters: usize,
-) -> Result<(), JsValue> {
-    let p1 = Cpx::new(re1, im1);
-    let p2 = Cpx::new(re2, im2);
-    let gens = match typ {
-        "xxi" => kleinian::generators_xx(p1, p2),
-        "xii" => kleinian::generators_x(p1),
-        _ => kleinian::generators(p1, p2),
+    style: &Style,
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Result<Vec<u8>, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    let pts = kleinian::generate_labelled_points(gens, iters).map_err(js_error)?;
+    let view = view_from(center_re, center_im, scale);
+    Ok(render_pixels(width, height, &pts, view, style)?.0)
+}
+
+/// Like `render_rgba`, but draws the limit set straight into the image
+/// without storing any points, in the foreground and background colors of
+/// the style.  This uses much less memory, and there is no number of
+/// points to choose, since circles are followed until they are smaller than
+/// a pixel.  Each pixel is returned as a single number, and
+/// `new Uint8ClampedArray(pixels.buffer)` views them as RGBA bytes without
+/// copying.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn render_direct(
+    width: u32,
+    height: u32,
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    style: &Style,
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Result<Vec<u32>, JsValue> {
+    let gens = || make_generators(typ, re1, im1, re2, im2);
+    let view = view_from(center_re, center_im, scale);
+    Ok(direct_pixels(width, height, gens, view, style)?.0)
+}
+
+/// Like `render_rgba`, but returns a PNG file.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn render_png(
+    width: u32,
+    height: u32,
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+    style: &Style,
+    center_re: Option<f64>,
+    center_im: Option<f64>,
+    scale: Option<f64>,
+) -> Result<Vec<u8>, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    let pts = kleinian::generate_labelled_points(gens, iters).map_err(js_error)?;
+    let view = view_from(center_re, center_im, scale);
+    let (pixel_data, pw, ph, _) = render_pixels(width, height, &pts, view, style)?;
+    encode_png(&pixel_data, pw, ph)
+}
+
+/// Returns the presets, as an array of objects with the fields `name`,
+/// `description`, `typ`, `a_re`, `a_im`, `b_re`, and `b_im`, which have the
+/// same meanings as the fields of `State`.
+#[wasm_bindgen]
+pub fn list_presets() -> Result<JsValue, JsValue> {
+    let list = js_sys::Array::new();
+    for p in presets::PRESETS {
+        let obj = js_sys::Object::new();
+        let fields: [(&str, JsValue); 7] = [
+            ("name", p.name.into()),
+            ("description", p.description.into()),
+            ("typ", p.symmetry.as_str().into()),
+            ("a_re", p.ta.re.into()),
+            ("a_im", p.ta.im.into()),
+            ("b_re", p.tb.re.into()),
+            ("b_im", p.tb.im.into()),
+        ];
+        for (key, value) in fields {
+            js_sys::Reflect::set(&obj, &key.into(), &value)?;
+        }
+        list.push(&obj);
+    }
+    Ok(list.into())
+}
+
+/// A limit set computation that proceeds in chunks, so that the page stays
+/// responsive while it runs.  Created by `start`.
+#[wasm_bindgen]
+pub struct Render {
+    queue: CircleQueue,
+    iters: usize,
+    cancelled: bool,
+    /// The time spent computing points, in milliseconds.
+    compute_ms: f64,
+    /// The time taken by the most recent call to `draw`, in milliseconds.
+    draw_ms: f64,
+    /// The radius of the smallest circle found so far.
+    min_radius: f64,
+    /// The largest number of circles that have been in the queue at once.
+    peak_queue_len: usize,
+    /// An index of the points, built by `nearest` and kept until more
+    /// points are computed.
+    picker: Option<Picker>,
+}
+
+struct Picker {
+    len: usize,
+    index: PointIndex,
+    points: Vec<(LimitPoint, Vec<u8>)>,
+}
+
+/// Statistics about a `Render`, so that the page can tune the amount of
+/// work done per frame to the speed of the machine.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Stats {
+    /// The number of points computed so far.
+    pub points: usize,
+    /// The time spent computing points, in milliseconds.
+    pub compute_ms: f64,
+    /// The time taken by the most recent call to `draw`, in milliseconds.
+    pub draw_ms: f64,
+    /// The average rate at which points have been computed.
+    pub points_per_sec: f64,
+    /// The largest number of circles that have been in the queue at once.
+    /// This can be more than `points`, since a circle whose children are
+    /// all skipped, such as a power of an elliptic generator of finite
+    /// order, is removed without being replaced.
+    pub peak_queue_len: usize,
+    /// The radius of the smallest circle found so far.
+    pub min_radius: f64,
+    /// The radius of the largest circle that has not been subdivided,
+    /// which bounds the distance between the points and the limit set.
+    pub max_radius: f64,
+}
+
+/// Starts computing approximately `iters` points of the limit set.  No
+/// work is done until `step` is called.
+#[wasm_bindgen]
+pub fn start(
+    re1: f64,
+    im1: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+) -> Result<Render, JsValue> {
+    let gens = make_generators(typ, re1, im1, re2, im2);
+    let mut queue = CircleQueue::new(gens).map_err(js_error)?;
+    // `nearest` looks up the words of the points.
+    queue.enable_words();
+    Ok(Render {
+        peak_queue_len: queue.len(),
+        queue,
+        iters,
+        cancelled: false,
+        compute_ms: 0.0,
+        draw_ms: 0.0,
+        min_radius: f64::INFINITY,
+        picker: None,
+    })
+}
+
+impl Render {
+    /// Advances the queue until it has `target` circles, calling `f` with
+    /// each new circle, and keeps track of the statistics.
+    fn advance_to<F: FnMut(&kleinian::Circle)>(
+        &mut self,
+        target: usize,
+        mut f: F,
+    ) -> Result<(), JsValue> {
+        let start = js_sys::Date::now();
+        let mut min_radius = self.min_radius;
+        let mut result = Ok(());
+        while self.queue.len() < target {
+            result = self.queue.advance_with(|c| {
+                min_radius = f64::min(min_radius, 1.0 / c.radius_inv().abs());
+                f(c)
+            });
+            if result.is_err() {
+                break;
+            }
+            self.peak_queue_len = self.peak_queue_len.max(self.queue.len());
+        }
+        self.min_radius = min_radius;
+        self.compute_ms += js_sys::Date::now() - start;
+        result.map_err(js_error)
+    }
+}
+
+#[wasm_bindgen]
+impl Render {
+    /// Computes up to `n_points` more points.  Returns the fraction of the
+    /// computation that has been completed, which is 1 when it is done.
+    /// Does nothing if the computation has been cancelled.
+    pub fn step(&mut self, n_points: usize) -> Result<f64, JsValue> {
+        if self.cancelled {
+            return Ok(self.progress());
+        }
+        let target = usize::min(self.queue.len() + n_points, self.iters);
+        self.advance_to(target, |_| ())?;
+        Ok(self.progress())
+    }
+    /// Like `step`, but also passes the centers of the newly created
+    /// circles to `callback` as they are computed, in batches of about
+    /// `batch_size` points.  Each batch is a `Float64Array` of interleaved
+    /// real and imaginary parts.  The centers of circles created early on
+    /// are only rough approximations to the limit set, so a progressive
+    /// visualization should expect to be refined by later batches.
+    pub fn stream(
+        &mut self,
+        n_points: usize,
+        batch_size: usize,
+        callback: &js_sys::Function,
+    ) -> Result<f64, JsValue> {
+        if self.cancelled {
+            return Ok(self.progress());
+        }
+        let target = usize::min(self.queue.len() + n_points, self.iters);
+        let mut batch = Vec::with_capacity(2 * batch_size);
+        // Each step of the queue replaces one circle with three, so it
+        // grows by two thirds of the number of new points.
+        let growth = usize::max(2 * batch_size / 3, 1);
+        while self.queue.len() < target {
+            let next = usize::min(self.queue.len() + growth, target);
+            self.advance_to(next, |c| {
+                let z = c.center_point().affine();
+                batch.extend_from_slice(&[z.re, z.im]);
+            })?;
+            let array = js_sys::Float64Array::from(&batch[..]);
+            callback.call1(&JsValue::NULL, &array)?;
+            batch.clear();
+        }
+        Ok(self.progress())
+    }
+    /// Returns statistics about the computation so far.
+    pub fn stats(&self) -> Stats {
+        let points = self.queue.len();
+        Stats {
+            points,
+            compute_ms: self.compute_ms,
+            draw_ms: self.draw_ms,
+            points_per_sec: if self.compute_ms > 0.0 {
+                1000.0 * points as f64 / self.compute_ms
+            } else {
+                0.0
+            },
+            peak_queue_len: self.peak_queue_len,
+            min_radius: self.min_radius,
+            max_radius: self.queue.max_radius(),
+        }
+    }
+    pub fn progress(&self) -> f64 {
+        f64::min(self.queue.len() as f64 / self.iters as f64, 1.0)
+    }
+    pub fn done(&self) -> bool {
+        self.queue.len() >= self.iters
+    }
+    /// Stops the computation.  Later calls to `step` do no work.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+    /// Returns the points computed so far, as interleaved real and
+    /// imaginary parts.
+    pub fn points(&self) -> Vec<f64> {
+        self.queue.points().flat_map(|z| [z.re, z.im]).collect()
+    }
+    /// Returns the computed point nearest to `re + i im`, as an object with
+    /// the fields `re`, `im`, `radius` (of the circle that the point is the
+    /// center of), and `word` (a string of the letters a, b, A, and B, with
+    /// A and B the inverses of a and b), or null if there are no points.
+    pub fn nearest(&mut self, re: f64, im: f64) -> Result<JsValue, JsValue> {
+        let len = self.queue.len();
+        if self.picker.as_ref().is_none_or(|p| p.len != len) {
+            let points = self.queue.ordered_labelled_points();
+            let zs: Vec<Cpx> = points.iter().map(|(p, _)| p.z).collect();
+            self.picker = Some(Picker {
+                len,
+                index: PointIndex::new(&zs),
+                points,
+            });
+        }
+        let picker = self.picker.as_ref().unwrap();
+        let Some(i) = picker.index.nearest(Cpx::new(re, im)) else {
+            return Ok(JsValue::NULL);
+        };
+        let (p, word) = &picker.points[i];
+        let word: String = word
+            .iter()
+            .map(|&l| ['a', 'b', 'A', 'B'][l as usize])
+            .collect();
+        let obj = js_sys::Object::new();
+        let fields: [(&str, JsValue); 4] = [
+            ("re", p.z.re.into()),
+            ("im", p.z.im.into()),
+            ("radius", p.radius.into()),
+            ("word", word.into()),
+        ];
+        for (key, value) in fields {
+            js_sys::Reflect::set(&obj, &key.into(), &value)?;
+        }
+        Ok(obj.into())
+    }
+    /// Renders the points computed so far as RGBA pixels.  See
+    /// `render_rgba` for the meaning of the parameters.
+    pub fn render_rgba(
+        &self,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let pts: Vec<LimitPoint> = self.queue.labelled_points().collect();
+        let view = view_from(center_re, center_im, scale);
+        Ok(render_pixels(width, height, &pts, view, style)?.0)
+    }
+    /// Encodes the points computed so far as a PNG image of any size,
+    /// independent of the size of the canvas.  The view is given as in
+    /// `draw`, in pixels of the exported image.
+    pub fn export_png(
+        &self,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let pts: Vec<LimitPoint> = self.queue.labelled_points().collect();
+        let view = view_from(center_re, center_im, scale);
+        let (pixel_data, pw, ph, _) = render_pixels(width, height, &pts, view, style)?;
+        encode_png(&pixel_data, pw, ph)
+    }
+}
+
+/// An animation between two groups of the same type.  The group is explored
+/// once, at both ends of the animation, and each frame reuses the words
+/// that were found, which is much faster than exploring the group again.
+/// For groups of type `oi`, the frames follow a `GroupPath`, which keeps the
+/// traces on a consistent branch.  For the other types, the traces are
+/// interpolated linearly.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Morph {
+    from: [f64; 4],
+    to: [f64; 4],
+    typ: String,
+    words: Vec<Vec<u8>>,
+    path: Option<GroupPath>,
+}
+
+/// Prepares an animation from the traces `(re1a + i im1a, re2a + i im2a)`
+/// to `(re1b + i im1b, re2b + i im2b)`.  About `iters` points are drawn in
+/// each frame.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn morph(
+    re1a: f64,
+    im1a: f64,
+    re2a: f64,
+    im2a: f64,
+    re1b: f64,
+    im1b: f64,
+    re2b: f64,
+    im2b: f64,
+    typ: &str,
+    iters: usize,
+) -> Result<Morph, JsValue> {
+    let explore = |re1, im1, re2, im2| -> Result<Vec<Vec<u8>>, kleinian::Error> {
+        let mut queue = CircleQueue::new(make_generators(typ, re1, im1, re2, im2))?;
+        queue.enable_words();
+        while queue.len() < iters / 2 {
+            queue.advance()?;
+        }
+        Ok(queue.words())
     };
-    let pts = kleinian::generate_points(gens, iters);
-    let w = width as usize;
-    let h = height as usize;
-    let trans = kleinian::window::window_transform(&pts, w, h);
-    let mut pixel_data = Vec::new();
-    pixel_data.resize(w * h * 4, 255);
-    for pt in pts {
-        let (x, y) = trans.apply(&pt);
-        let idx = x * h + y;
-        pixel_data[4 * idx] = 0;
-        pixel_data[4 * idx + 1] = 0;
-        pixel_data[4 * idx + 2] = 0;
-    }
-    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixel_data), width, height)?;
-    ctx.put_image_data(&data, 0.0, 0.0)
+    let mut words = explore(re1a, im1a, re2a, im2a).map_err(js_error)?;
+    words.extend(explore(re1b, im1b, re2b, im2b).map_err(js_error)?);
+    words.sort_unstable();
+    words.dedup();
+    let path = match typ.parse().unwrap_or(Symmetry::Oi) {
+        Symmetry::Oi => GroupPath::new(
+            &make_generators(typ, re1a, im1a, re2a, im2a),
+            &make_generators(typ, re1b, im1b, re2b, im2b),
+        ),
+        _ => None,
+    };
+    Ok(Morph {
+        from: [re1a, im1a, re2a, im2a],
+        to: [re1b, im1b, re2b, im2b],
+        typ: typ.to_string(),
+        words,
+        path,
+    })
+}
+
+impl Morph {
+    fn points_at(&self, t: f64) -> Vec<LimitPoint> {
+        let gens = match &self.path {
+            Some(path) => path.generators_at(t),
+            None => {
+                let p: Vec<f64> = (0..4)
+                    .map(|i| self.from[i] + t * (self.to[i] - self.from[i]))
+                    .collect();
+                make_generators(&self.typ, p[0], p[1], p[2], p[3])
+            }
+        };
+        kleinian::circles_for_words(&gens, &self.words)
+            .into_iter()
+            .zip(&self.words)
+            .map(|(c, w)| LimitPoint {
+                z: c.center_point().affine(),
+                radius: 1.0 / c.radius_inv(),
+                generator: w[w.len() - 1],
+                depth: w.len(),
+            })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Morph {
+    /// Returns the points of the frame at time `t`, which runs from 0 to 1,
+    /// as interleaved real and imaginary parts.
+    pub fn frame(&self, t: f64) -> Vec<f64> {
+        self.points_at(t)
+            .into_iter()
+            .flat_map(|p| [p.z.re, p.z.im])
+            .collect()
+    }
+    /// Prepares to render the animation as `duration` seconds of video at
+    /// `fps` frames per second.  See `draw` for the meaning of the other
+    /// parameters.  If no view is given, the view that fits the first frame
+    /// rendered is used for every later frame, so that the picture does not
+    /// jump around.
+    #[allow(clippy::too_many_arguments)]
+    pub fn frames(
+        &self,
+        duration: f64,
+        fps: f64,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> FrameSequence {
+        FrameSequence {
+            morph: self.clone(),
+            count: ((duration * fps).round() as u32).max(1),
+            fps,
+            width,
+            height,
+            style: style.clone(),
+            view: view_from(center_re, center_im, scale),
+        }
+    }
+}
+
+/// The frames of a `Morph` at a fixed frame rate, rendered into buffers that
+/// can be passed to `VideoFrame` or drawn to a canvas being recorded by a
+/// `MediaRecorder`.  Created by `Morph::frames`.
+#[wasm_bindgen]
+pub struct FrameSequence {
+    morph: Morph,
+    count: u32,
+    fps: f64,
+    width: u32,
+    height: u32,
+    style: Style,
+    view: Option<Viewport>,
+}
+
+impl FrameSequence {
+    fn pixels(&mut self, i: u32) -> Result<(Vec<u8>, u32, u32), JsValue> {
+        let t = if self.count > 1 {
+            i as f64 / (self.count - 1) as f64
+        } else {
+            0.0
+        };
+        let pts = self.morph.points_at(t);
+        let (pixel_data, pw, ph, view) =
+            render_pixels(self.width, self.height, &pts, self.view, &self.style)?;
+        self.view = Some(view);
+        Ok((pixel_data, pw, ph))
+    }
+}
+
+#[wasm_bindgen]
+impl FrameSequence {
+    pub fn frame_count(&self) -> u32 {
+        self.count
+    }
+    /// The time at which frame `i` is shown, in microseconds, as expected
+    /// by `VideoFrame`.
+    pub fn timestamp(&self, i: u32) -> f64 {
+        (1e6 * i as f64 / self.fps).round()
+    }
+    /// The duration of each frame, in microseconds.
+    pub fn frame_duration(&self) -> f64 {
+        (1e6 / self.fps).round()
+    }
+    /// The width of the frames in pixels, which is the width passed to
+    /// `Morph::frames` times the pixel ratio of the style.
+    pub fn pixel_width(&self) -> u32 {
+        (self.width as f64 * self.style.pixel_ratio).round() as u32
+    }
+    /// The height of the frames in pixels.
+    pub fn pixel_height(&self) -> u32 {
+        (self.height as f64 * self.style.pixel_ratio).round() as u32
+    }
+    /// Renders frame `i` as RGBA pixels.
+    pub fn render(&mut self, i: u32) -> Result<Vec<u8>, JsValue> {
+        Ok(self.pixels(i)?.0)
+    }
+    /// Returns the view used for the frames, once one has been rendered.
+    pub fn view(&self) -> Option<Viewport> {
+        self.view
+    }
 }
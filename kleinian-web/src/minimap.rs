@@ -0,0 +1,108 @@
+use crate::{make_generators, parse_err};
+use kleinian::group::traces;
+use kleinian::pleating::pleating_ray;
+use kleinian::score::Metric;
+use kleinian::window::CoordTransform;
+use kleinian::Cpx;
+use wasm_bindgen::prelude::*;
+
+/// The transform from tr a to mini-map pixels, with the imaginary axis
+/// pointing up.
+fn transform(
+    width: u32,
+    height: u32,
+    center_re: f64,
+    center_im: f64,
+    scale: f64,
+) -> CoordTransform {
+    CoordTransform::centered(
+        Cpx::new(center_re, center_im),
+        scale,
+        width as usize,
+        height as usize,
+    )
+}
+
+/// Renders a map of the tr a plane, with tr b fixed at `re2 + i im2`, as
+/// RGBA pixels.  Each pixel is shaded by the `kleinian::score::Metric`
+/// named by `metric`, `discreteness` or `score`, computed with `iters`
+/// circles, so that darker regions contain more interesting groups.  The
+/// view is given as in `draw`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn param_map_pixels(
+    width: u32,
+    height: u32,
+    center_re: f64,
+    center_im: f64,
+    scale: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    iters: usize,
+    metric: &str,
+) -> Result<Vec<u8>, JsValue> {
+    let metric: Metric = metric.parse().map_err(parse_err)?;
+    let trans = transform(width, height, center_re, center_im, scale);
+    let mut pixel_data = Vec::with_capacity(4 * (width * height) as usize);
+    for row in 0..height {
+        for col in 0..width {
+            let ta = trans.inverse(col as f64 + 0.5, (height - 1 - row) as f64 + 0.5);
+            let gens = make_generators(typ, ta.re, ta.im, re2, im2);
+            let score = metric.evaluate(gens, iters);
+            let v = (255.0 * (1.0 - score)).round() as u8;
+            pixel_data.extend_from_slice(&[v, v, v, 255]);
+        }
+    }
+    Ok(pixel_data)
+}
+
+/// Returns the value of tr a, as `[re, im]`, at the pixel `(x, y)` of a
+/// map drawn by `param_map` with the same view.
+#[wasm_bindgen]
+pub fn param_map_point(
+    x: f64,
+    y: f64,
+    width: u32,
+    height: u32,
+    center_re: f64,
+    center_im: f64,
+    scale: f64,
+) -> Vec<f64> {
+    let trans = transform(width, height, center_re, center_im, scale);
+    let ta = trans.inverse(x, height as f64 - y);
+    vec![ta.re, ta.im]
+}
+
+/// Returns the p/q pleating ray, computed by `kleinian::pleating`, over a
+/// map drawn by `param_map_pixels` with the same view, as line segments
+/// given by consecutive `x0, y0, x1, y1` in pixels.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn param_map_ray(
+    width: u32,
+    height: u32,
+    center_re: f64,
+    center_im: f64,
+    scale: f64,
+    re2: f64,
+    im2: f64,
+    typ: &str,
+    p: u32,
+    q: u32,
+) -> Vec<f64> {
+    let trans = transform(width, height, center_re, center_im, scale);
+    let min = trans.inverse(0.0, 0.0);
+    let max = trans.inverse(width as f64, height as f64);
+    // A cell of the grid for every two pixels is fine enough to look smooth.
+    let n = (width.max(height) / 2) as usize;
+    let traces = |ta: Cpx| traces(&make_generators(typ, ta.re, ta.im, re2, im2));
+    pleating_ray(p, q, traces, min, max, n)
+        .into_iter()
+        .flatten()
+        .flat_map(|z| {
+            let (x, y) = trans.map(&z);
+            [x, height as f64 - y]
+        })
+        .collect()
+}
@@ -0,0 +1,327 @@
+use js_sys::{Array, Object, Reflect};
+use kleinian::render;
+use std::fmt::Write;
+use wasm_bindgen::prelude::*;
+
+/// The version of the object returned by `State::get_state`, which changes
+/// whenever the meaning of an existing field does.  Adding fields does not
+/// change it.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Everything needed to reproduce a drawing.  It can be encoded as a short
+/// URL-safe string, so that views can be shared as links.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    pub typ: String,
+    pub a_re: f64,
+    pub a_im: f64,
+    pub b_re: f64,
+    pub b_im: f64,
+    pub iters: usize,
+    pub center_re: Option<f64>,
+    pub center_im: Option<f64>,
+    pub scale: Option<f64>,
+    /// A color of the form `#rrggbb`.
+    pub foreground: String,
+    /// A color of the form `#rrggbb`.
+    pub background: String,
+    pub palette: String,
+    pub color_by: String,
+    pub mode: String,
+    pub point_size: usize,
+    pub gamma: f64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            typ: "oi".to_string(),
+            a_re: 2.2,
+            a_im: 0.0,
+            b_re: 2.2,
+            b_im: 0.0,
+            iters: 10000,
+            center_re: None,
+            center_im: None,
+            scale: None,
+            foreground: "#000000".to_string(),
+            background: "#ffffff".to_string(),
+            palette: "gradient".to_string(),
+            color_by: "uniform".to_string(),
+            mode: "points".to_string(),
+            point_size: 1,
+            gamma: 1.0,
+        }
+    }
+}
+
+fn invalid(key: &str) -> JsValue {
+    JsError::new(&format!("invalid value for {} in state", key)).into()
+}
+
+fn parse_pair(key: &str, value: &str) -> Result<(f64, f64), JsValue> {
+    let (x, y) = value.split_once(',').ok_or_else(|| invalid(key))?;
+    Ok((
+        x.parse().map_err(|_| invalid(key))?,
+        y.parse().map_err(|_| invalid(key))?,
+    ))
+}
+
+fn parse_color(key: &str, value: &str) -> Result<String, JsValue> {
+    let color = format!("#{}", value);
+    render::parse_color(&color).map_err(|_| invalid(key))?;
+    Ok(color)
+}
+
+/// Creates an object with the given fields.
+fn object(fields: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    let obj = Object::new();
+    for (key, value) in fields {
+        Reflect::set(&obj, &(*key).into(), value)?;
+    }
+    Ok(obj.into())
+}
+
+/// Returns the field of the object, or `None` if it is missing or null.
+fn field(obj: &JsValue, key: &str) -> Result<Option<JsValue>, JsValue> {
+    if !obj.is_object() {
+        return Ok(None);
+    }
+    let value = Reflect::get(obj, &key.into())?;
+    Ok((!value.is_undefined() && !value.is_null()).then_some(value))
+}
+
+fn number(value: &JsValue, key: &str) -> Result<f64, JsValue> {
+    value.as_f64().ok_or_else(|| invalid(key))
+}
+
+fn string(value: &JsValue, key: &str) -> Result<String, JsValue> {
+    value.as_string().ok_or_else(|| invalid(key))
+}
+
+fn count(value: &JsValue, key: &str) -> Result<usize, JsValue> {
+    let x = number(value, key)?;
+    if x >= 0.0 && x.fract() == 0.0 {
+        Ok(x as usize)
+    } else {
+        Err(invalid(key))
+    }
+}
+
+/// Returns a complex number as an array `[re, im]`.
+fn pair(re: f64, im: f64) -> JsValue {
+    Array::of2(&re.into(), &im.into()).into()
+}
+
+fn parse_array_pair(value: &JsValue, key: &str) -> Result<(f64, f64), JsValue> {
+    if !Array::is_array(value) {
+        return Err(invalid(key));
+    }
+    let array = Array::from(value);
+    if array.length() != 2 {
+        return Err(invalid(key));
+    }
+    Ok((number(&array.get(0), key)?, number(&array.get(1), key)?))
+}
+
+#[wasm_bindgen]
+impl State {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Encodes the state as a string such as
+    /// `t=oi&a=2.2,0&b=2.2,0&n=10000`.  Only the fields that differ from
+    /// the defaults are included.
+    pub fn encode(&self) -> String {
+        let d = State::default();
+        let mut s = format!(
+            "t={}&a={},{}&b={},{}",
+            self.typ, self.a_re, self.a_im, self.b_re, self.b_im
+        );
+        if self.iters != d.iters {
+            let _ = write!(s, "&n={}", self.iters);
+        }
+        if let (Some(re), Some(im), Some(scale)) = (self.center_re, self.center_im, self.scale) {
+            let _ = write!(s, "&v={},{},{}", re, im, scale);
+        }
+        if self.foreground != d.foreground {
+            let _ = write!(s, "&fg={}", self.foreground.trim_start_matches('#'));
+        }
+        if self.background != d.background {
+            let _ = write!(s, "&bg={}", self.background.trim_start_matches('#'));
+        }
+        if self.palette != d.palette {
+            let _ = write!(s, "&p={}", self.palette);
+        }
+        if self.color_by != d.color_by {
+            let _ = write!(s, "&c={}", self.color_by);
+        }
+        if self.mode != d.mode {
+            let _ = write!(s, "&m={}", self.mode);
+        }
+        if self.point_size != d.point_size {
+            let _ = write!(s, "&ps={}", self.point_size);
+        }
+        if self.gamma != d.gamma {
+            let _ = write!(s, "&g={}", self.gamma);
+        }
+        s
+    }
+    /// Parses a string produced by `encode`.  Missing fields get their
+    /// default values.
+    pub fn decode(s: &str) -> Result<State, JsValue> {
+        let mut state = State::default();
+        for field in s.split('&').filter(|f| !f.is_empty()) {
+            let (key, value) = field.split_once('=').ok_or_else(|| invalid(field))?;
+            match key {
+                "t" => state.typ = value.to_string(),
+                "a" => (state.a_re, state.a_im) = parse_pair(key, value)?,
+                "b" => (state.b_re, state.b_im) = parse_pair(key, value)?,
+                "n" => state.iters = value.parse().map_err(|_| invalid(key))?,
+                "v" => {
+                    let (center, scale) = value.rsplit_once(',').ok_or_else(|| invalid(key))?;
+                    let (re, im) = parse_pair(key, center)?;
+                    state.center_re = Some(re);
+                    state.center_im = Some(im);
+                    state.scale = Some(scale.parse().map_err(|_| invalid(key))?);
+                }
+                "fg" => state.foreground = parse_color(key, value)?,
+                "bg" => state.background = parse_color(key, value)?,
+                "p" => {
+                    value.parse::<render::Palette>().map_err(|_| invalid(key))?;
+                    state.palette = value.to_string();
+                }
+                "c" => {
+                    value.parse::<render::ColorBy>().map_err(|_| invalid(key))?;
+                    state.color_by = value.to_string();
+                }
+                "m" => {
+                    value.parse::<render::Mode>().map_err(|_| invalid(key))?;
+                    state.mode = value.to_string();
+                }
+                "ps" => state.point_size = value.parse().map_err(|_| invalid(key))?,
+                "g" => state.gamma = value.parse().map_err(|_| invalid(key))?,
+                // Ignore unknown fields, so that links from newer versions
+                // still mostly work.
+                _ => {}
+            }
+        }
+        Ok(state)
+    }
+    /// Returns the state as a plain object, which can be stored with
+    /// `JSON.stringify` and restored with `set_state`.  The object has the
+    /// fields `version`, `recipe` (the symmetry type), `traces` (with
+    /// fields `a` and `b`, each an array `[re, im]`), `iters`, `viewport`
+    /// (null, or an object with the fields `center`, an array `[re, im]`,
+    /// and `scale`), and `style` (with the fields `foreground`,
+    /// `background`, `palette`, `color_by`, `mode`, `point_size`, and
+    /// `gamma`).
+    pub fn get_state(&self) -> Result<JsValue, JsValue> {
+        let viewport = match (self.center_re, self.center_im, self.scale) {
+            (Some(re), Some(im), Some(scale)) => {
+                object(&[("center", pair(re, im)), ("scale", scale.into())])?
+            }
+            _ => JsValue::NULL,
+        };
+        let traces = object(&[
+            ("a", pair(self.a_re, self.a_im)),
+            ("b", pair(self.b_re, self.b_im)),
+        ])?;
+        let style = object(&[
+            ("foreground", self.foreground.as_str().into()),
+            ("background", self.background.as_str().into()),
+            ("palette", self.palette.as_str().into()),
+            ("color_by", self.color_by.as_str().into()),
+            ("mode", self.mode.as_str().into()),
+            ("point_size", self.point_size.into()),
+            ("gamma", self.gamma.into()),
+        ])?;
+        object(&[
+            ("version", SCHEMA_VERSION.into()),
+            ("recipe", self.typ.as_str().into()),
+            ("traces", traces),
+            ("iters", self.iters.into()),
+            ("viewport", viewport),
+            ("style", style),
+        ])
+    }
+    /// Replaces the state with one returned by `get_state`.  As with
+    /// `decode`, missing fields get their default values and unknown fields
+    /// are ignored.  Objects from a newer version of the schema are
+    /// rejected, and so are invalid values, in which case the state is left
+    /// unchanged.
+    pub fn set_state(&mut self, value: JsValue) -> Result<(), JsValue> {
+        if !value.is_object() {
+            return Err(JsError::new("the state is not an object").into());
+        }
+        if let Some(v) = field(&value, "version")? {
+            if count(&v, "version")? > SCHEMA_VERSION as usize {
+                return Err(JsError::new("the state is from a newer version").into());
+            }
+        }
+        let mut state = State::default();
+        if let Some(v) = field(&value, "recipe")? {
+            state.typ = string(&v, "recipe")?;
+        }
+        if let Some(traces) = field(&value, "traces")? {
+            if let Some(v) = field(&traces, "a")? {
+                (state.a_re, state.a_im) = parse_array_pair(&v, "a")?;
+            }
+            if let Some(v) = field(&traces, "b")? {
+                (state.b_re, state.b_im) = parse_array_pair(&v, "b")?;
+            }
+        }
+        if let Some(v) = field(&value, "iters")? {
+            state.iters = count(&v, "iters")?;
+        }
+        if let Some(viewport) = field(&value, "viewport")? {
+            let center = field(&viewport, "center")?.ok_or_else(|| invalid("viewport"))?;
+            let scale = field(&viewport, "scale")?.ok_or_else(|| invalid("viewport"))?;
+            let (re, im) = parse_array_pair(&center, "center")?;
+            state.center_re = Some(re);
+            state.center_im = Some(im);
+            state.scale = Some(number(&scale, "scale")?);
+        }
+        if let Some(style) = field(&value, "style")? {
+            if let Some(v) = field(&style, "foreground")? {
+                let color = string(&v, "foreground")?;
+                state.foreground = parse_color("foreground", color.trim_start_matches('#'))?;
+            }
+            if let Some(v) = field(&style, "background")? {
+                let color = string(&v, "background")?;
+                state.background = parse_color("background", color.trim_start_matches('#'))?;
+            }
+            if let Some(v) = field(&style, "palette")? {
+                state.palette = string(&v, "palette")?;
+                state
+                    .palette
+                    .parse::<render::Palette>()
+                    .map_err(|_| invalid("palette"))?;
+            }
+            if let Some(v) = field(&style, "color_by")? {
+                state.color_by = string(&v, "color_by")?;
+                state
+                    .color_by
+                    .parse::<render::ColorBy>()
+                    .map_err(|_| invalid("color_by"))?;
+            }
+            if let Some(v) = field(&style, "mode")? {
+                state.mode = string(&v, "mode")?;
+                state
+                    .mode
+                    .parse::<render::Mode>()
+                    .map_err(|_| invalid("mode"))?;
+            }
+            if let Some(v) = field(&style, "point_size")? {
+                state.point_size = count(&v, "point_size")?;
+            }
+            if let Some(v) = field(&style, "gamma")? {
+                state.gamma = number(&v, "gamma")?;
+            }
+        }
+        *self = state;
+        Ok(())
+    }
+}
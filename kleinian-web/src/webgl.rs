@@ -0,0 +1,209 @@
+use crate::{fit_view, view_from, Render, Style, Viewport};
+use kleinian::render::Mode;
+use kleinian::LimitPoint;
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlShader};
+
+const VERTEX_SHADER: &str = r#"#version 300 es
+in vec2 pos;
+uniform vec2 offset;
+uniform vec2 scale;
+uniform float point_size;
+void main() {
+    gl_Position = vec4((pos + offset) * scale, 0.0, 1.0);
+    gl_PointSize = point_size;
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+uniform float intensity;
+out vec4 color;
+void main() {
+    color = vec4(intensity, intensity, intensity, 1.0);
+}
+"#;
+
+fn compile_shader(gl: &Gl, typ: u32, source: &str) -> Result<WebGlShader, JsValue> {
+    let shader = gl
+        .create_shader(typ)
+        .ok_or_else(|| JsError::new("unable to create shader"))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        let log = gl.get_shader_info_log(&shader).unwrap_or_default();
+        Err(JsError::new(&log).into())
+    }
+}
+
+fn link_program(gl: &Gl) -> Result<WebGlProgram, JsValue> {
+    let vs = compile_shader(gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+    let fs = compile_shader(gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+    let program = gl
+        .create_program()
+        .ok_or_else(|| JsError::new("unable to create program"))?;
+    gl.attach_shader(&program, &vs);
+    gl.attach_shader(&program, &fs);
+    gl.link_program(&program);
+    if gl
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        let log = gl.get_program_info_log(&program).unwrap_or_default();
+        Err(JsError::new(&log).into())
+    }
+}
+
+/// Draws points with WebGL2.  The points are uploaded to the GPU once by
+/// `upload`, after which `draw` can be called repeatedly with different
+/// views, which is much faster than rasterizing on the CPU.
+#[wasm_bindgen]
+pub struct GlRenderer {
+    gl: Gl,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    count: i32,
+    /// The points are stored relative to this origin, so that single
+    /// precision is enough when zooming in on a small part of the set.
+    origin: (f64, f64),
+}
+
+#[wasm_bindgen]
+impl GlRenderer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(gl: Gl) -> Result<GlRenderer, JsValue> {
+        let program = link_program(&gl)?;
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| JsError::new("unable to create buffer"))?;
+        Ok(GlRenderer {
+            gl,
+            program,
+            buffer,
+            count: 0,
+            origin: (0.0, 0.0),
+        })
+    }
+    /// Uploads points given as interleaved real and imaginary parts, as
+    /// returned by `compute_points`.  Points that are not finite, such as
+    /// the centers of lines, are left out.
+    pub fn upload(&mut self, points: &[f64]) {
+        let finite: Vec<&[f64]> = points
+            .chunks_exact(2)
+            .filter(|p| p[0].is_finite() && p[1].is_finite())
+            .collect();
+        let n = finite.len();
+        let (sx, sy) = finite
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), p| (sx + p[0], sy + p[1]));
+        self.origin = if n > 0 {
+            (sx / n as f64, sy / n as f64)
+        } else {
+            (0.0, 0.0)
+        };
+        let mut bytes = Vec::with_capacity(8 * n);
+        for p in finite {
+            bytes.extend_from_slice(&((p[0] - self.origin.0) as f32).to_le_bytes());
+            bytes.extend_from_slice(&((p[1] - self.origin.1) as f32).to_le_bytes());
+        }
+        self.gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        self.gl
+            .buffer_data_with_u8_array(Gl::ARRAY_BUFFER, &bytes, Gl::STATIC_DRAW);
+        self.count = n as i32;
+    }
+    /// Draws the uploaded points, with `center` in the middle of the
+    /// viewport and `scale` pixels per unit length.  Points are drawn in
+    /// black on a white background.  If `density` is true, each point only
+    /// darkens its pixels slightly, so that overlapping points accumulate
+    /// and dense regions of the limit set appear darker.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &self,
+        width: u32,
+        height: u32,
+        center_re: f64,
+        center_im: f64,
+        scale: f64,
+        point_size: f32,
+        density: bool,
+    ) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.clear_color(1.0, 1.0, 1.0, 1.0);
+        gl.clear(Gl::COLOR_BUFFER_BIT);
+        gl.use_program(Some(&self.program));
+        let uniform = |name| gl.get_uniform_location(&self.program, name);
+        gl.uniform2f(
+            uniform("offset").as_ref(),
+            (self.origin.0 - center_re) as f32,
+            (self.origin.1 - center_im) as f32,
+        );
+        gl.uniform2f(
+            uniform("scale").as_ref(),
+            (2.0 * scale / width as f64) as f32,
+            (2.0 * scale / height as f64) as f32,
+        );
+        gl.uniform1f(uniform("point_size").as_ref(), point_size);
+        // Points are subtracted from the white background.
+        gl.enable(Gl::BLEND);
+        gl.blend_equation(Gl::FUNC_REVERSE_SUBTRACT);
+        gl.blend_func(Gl::ONE, Gl::ONE);
+        gl.uniform1f(
+            uniform("intensity").as_ref(),
+            if density { 0.05 } else { 1.0 },
+        );
+        let pos = gl.get_attrib_location(&self.program, "pos") as u32;
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        gl.enable_vertex_attrib_array(pos);
+        gl.vertex_attrib_pointer_with_i32(pos, 2, Gl::FLOAT, false, 0, 0);
+        gl.draw_arrays(Gl::POINTS, 0, self.count);
+    }
+}
+
+#[wasm_bindgen]
+impl Render {
+    /// Like `draw`, but uploads the points computed so far to `renderer` and
+    /// draws them with WebGL.  Only the point size and mode of the style are
+    /// used: the points are drawn in black on a white background.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_gl(
+        &mut self,
+        renderer: &mut GlRenderer,
+        width: u32,
+        height: u32,
+        style: &Style,
+        center_re: Option<f64>,
+        center_im: Option<f64>,
+        scale: Option<f64>,
+    ) -> Result<Viewport, JsValue> {
+        let start = js_sys::Date::now();
+        let pts: Vec<LimitPoint> = self.queue.labelled_points().collect();
+        let view = match view_from(center_re, center_im, scale) {
+            Some(v) => v,
+            None => fit_view(&pts, width, height)?,
+        };
+        let coords: Vec<f64> = pts.iter().flat_map(|p| [p.z.re, p.z.im]).collect();
+        renderer.upload(&coords);
+        let ratio = style.pixel_ratio;
+        renderer.draw(
+            (width as f64 * ratio).round() as u32,
+            (height as f64 * ratio).round() as u32,
+            view.center_re,
+            view.center_im,
+            view.scale * ratio,
+            (style.style.point_size as f64 * ratio) as f32,
+            style.style.mode == Mode::Density,
+        );
+        self.draw_ms = js_sys::Date::now() - start;
+        Ok(view)
+    }
+}
@@ -1,5 +1,10 @@
+//! Primitives for building recipes for generators.  Möbius transformations
+//! are represented by 2×2 complex matrices, usually of determinant 1, and
+//! circles by Hermitian matrices, as described in `Circle`.  A matrix M
+//! acts on a circle C by C ↦ (M^†)^{-1} C M^{-1}.
+
 use crate::{Circle, Cpx};
-use nalgebra::Matrix2;
+use nalgebra::{Matrix2, RowVector2};
 
 /// Returns the adjoint of the matrix m.  We only call this function with matrices
 /// of determinant 1, in which case the adjoint is the same as the inverse.
@@ -7,6 +12,8 @@ pub fn inv(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
     Matrix2::new(m[(1, 1)], -m[(0, 1)], -m[(1, 0)], m[(0, 0)])
 }
 
+/// Returns the inverse of the conjugate transpose of m, which must have
+/// determinant 1.
 pub fn inv_dagger(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
     Matrix2::new(
         m[(1, 1)].conj(),
@@ -16,6 +23,7 @@ pub fn inv_dagger(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
     )
 }
 
+/// Returns the conjugate transpose of m.
 pub fn dagger(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
     Matrix2::new(
         m[(0, 0)].conj(),
@@ -25,25 +33,144 @@ pub fn dagger(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
     )
 }
 
-fn row_vector_for_nilpotent(u: &Matrix2<Cpx>) -> nalgebra::RowVector2<Cpx> {
+/// Factors a nonzero nilpotent matrix u as u = -J r^T r, where r is the
+/// row vector that is returned and J = (0 1; -1 0).  The kernel of r is
+/// the kernel of u, so if u = m - 1 for a parabolic m, then r vanishes on
+/// the fixed point (z, 1) of m.  The factorization is unique up to the sign
+/// of r.
+pub fn row_vector_for_nilpotent(u: &Matrix2<Cpx>) -> RowVector2<Cpx> {
     if u[(0, 1)].norm_sqr() >= u[(1, 0)].norm_sqr() {
         let s = (-u[(0, 1)]).sqrt();
-        nalgebra::RowVector2::new(u[(0, 0)] / s, -s)
+        RowVector2::new(u[(0, 0)] / s, -s)
     } else {
         let s = u[(1, 0)].sqrt();
-        nalgebra::RowVector2::new(s, u[(1, 1)] / s)
+        RowVector2::new(s, u[(1, 1)] / s)
     }
 }
 
 /// Returns a circle C containing the fixed points of the parabolic
 /// transforms u and v, such that if b is any transform satisfying bub^{-1} = v,
-/// then bC is tangent to C.
+/// then bC is tangent to C.  Circles whose curvature is too small to be
+/// distinguished from rounding error are returned as lines.
 pub fn circle_for_transforms(u: &Matrix2<Cpx>, v: &Matrix2<Cpx>) -> Circle {
     let un = u - Matrix2::from_diagonal_element(0.5 * u.trace());
     let vn = v - Matrix2::from_diagonal_element(0.5 * v.trace());
     let uv = row_vector_for_nilpotent(&un);
     let vv = row_vector_for_nilpotent(&vn);
     let m = vv.adjoint() * uv;
-    let mh = m + dagger(&m);
+    let mut mh = m + dagger(&m);
+    // The circle is often a line, as it is for the circle of a in
+    // `generators`, but rounding leaves a tiny curvature with an arbitrary
+    // sign, which puts the center far away on an arbitrary side.
+    let size = mh.iter().map(|z| z.norm()).fold(0.0, f64::max);
+    if mh[(0, 0)].norm() < 1e-12 * size {
+        mh[(0, 0)] = Cpx::from(0.0);
+    }
     Circle(mh / (-mh.determinant()).sqrt())
 }
+
+/// Returns the `t`th power of the Möbius transformation with matrix `m`
+/// (of determinant 1), along the one-parameter group of transformations
+/// with the same fixed points.  The sign of `m` is chosen so that its trace
+/// has nonnegative real part, so the first power may be -`m`.
+pub fn power(m: &Matrix2<Cpx>, t: f64) -> Matrix2<Cpx> {
+    let m = if m.trace().re < 0.0 { -m } else { *m };
+    let tr = m.trace();
+    let lambda = 0.5 * (tr + (tr * tr - 4.0).sqrt());
+    let diff = lambda - 1.0 / lambda;
+    let one = Matrix2::identity();
+    if diff.norm() < 1e-9 {
+        // m is parabolic or the identity.
+        return one + (m - one).map(|x| x * t);
+    }
+    // The power is a combination of 1 and m with eigenvalues λ^t and λ^-t.
+    let mu = (t * lambda.ln()).exp();
+    let beta = (mu - 1.0 / mu) / diff;
+    let alpha = mu - beta * lambda;
+    one.map(|x| x * alpha) + m.map(|x| x * beta)
+}
+
+/// Returns the logarithm of the matrix `m`, of determinant 1, which is the
+/// traceless matrix X with exp(X) = ±`m`.  As in `power`, the sign of `m`
+/// is chosen so that its trace has nonnegative real part, and of the
+/// logarithms of that matrix the one whose eigenvalues have imaginary parts
+/// in (-π, π] is returned.  The transformations exp(tX) form the
+/// one-parameter group through the transformation of `m`.
+pub fn log(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
+    let m = if m.trace().re < 0.0 { -m } else { *m };
+    let half = 0.5 * m.trace();
+    // If X has eigenvalues ±s, then exp(X) = cosh(s) + sinh(s) X / s.
+    let s = half.acosh();
+    let sinh = s.sinh();
+    let factor = if sinh.norm() < 1e-9 {
+        // m is parabolic or the identity, and s / sinh(s) is close to 1.
+        Cpx::from(1.0)
+    } else {
+        s / sinh
+    };
+    (m - Matrix2::identity() * half) * factor
+}
+
+/// Returns the exponential of the traceless matrix `x`, which has
+/// determinant 1.  This is the inverse of `log`.
+pub fn exp(x: &Matrix2<Cpx>) -> Matrix2<Cpx> {
+    // x^2 = -det(x), so x has eigenvalues ±s with s^2 = -det(x).
+    let s = (-x.determinant()).sqrt();
+    let factor = if s.norm() < 1e-9 {
+        Cpx::from(1.0)
+    } else {
+        s.sinh() / s
+    };
+    Matrix2::identity() * s.cosh() + x * factor
+}
+
+/// Returns the fixed points of the Möbius transformation with matrix `m`,
+/// which are equal if it is parabolic.  Infinity is represented by a
+/// complex number with infinite real part.
+pub fn fixed_points(m: &Matrix2<Cpx>) -> [Cpx; 2] {
+    let (a, b, c, d) = (m[(0, 0)], m[(0, 1)], m[(1, 0)], m[(1, 1)]);
+    let tr = a + d;
+    let s = (tr * tr - 4.0 * m.determinant()).sqrt();
+    if c == Cpx::from(0.0) {
+        let inf = Cpx::new(f64::INFINITY, 0.0);
+        let other = if a == d { inf } else { b / (d - a) };
+        return [inf, other];
+    }
+    [(a - d - s) / (2.0 * c), (a - d + s) / (2.0 * c)]
+}
+
+/// Returns a matrix of determinant 1 for the Möbius transformation that
+/// fixes `p` and `q` and multiplies (z - p) / (z - q) by `k`, so that it
+/// moves points away from `p` and towards `q` if |`k`| > 1.  Either fixed
+/// point may be infinity, represented as in `fixed_points`, in which case
+/// the factor for it is dropped.  The fixed points must be distinct; see
+/// `from_fixed_point` for parabolic transformations.
+pub fn from_fixed_points(p: Cpx, q: Cpx, k: Cpx) -> Matrix2<Cpx> {
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    let s = k.sqrt();
+    if !q.is_finite() {
+        // z ↦ k(z - p) + p
+        return Matrix2::new(s, p * (one / s - s), zero, one / s);
+    }
+    if !p.is_finite() {
+        return from_fixed_points(q, p, one / k);
+    }
+    // Conjugate to z ↦ kz, which fixes 0 and infinity.
+    let c = Matrix2::new(one, -p, one, -q) / (p - q).sqrt();
+    let d = Matrix2::new(s, zero, zero, one / s);
+    inv(&c) * d * c
+}
+
+/// Returns a matrix of determinant 1 for the parabolic transformation that
+/// fixes `p` and adds `t` to 1 / (z - p).  If `p` is infinity, it is the
+/// translation by `t` instead.
+pub fn from_fixed_point(p: Cpx, t: Cpx) -> Matrix2<Cpx> {
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    if !p.is_finite() {
+        return Matrix2::new(one, t, zero, one);
+    }
+    // Conjugate by z ↦ 1 / (z - p), which takes p to infinity.
+    Matrix2::new(one + t * p, -t * p * p, t, one - t * p)
+}
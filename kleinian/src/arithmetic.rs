@@ -0,0 +1,219 @@
+//! Numerical recognition of the trace field of a group, which is the field
+//! generated over the rationals by the traces of its elements.  Groups
+//! whose traces are algebraic integers in a number field with few complex
+//! places are candidates for being arithmetic, so this is a way to notice
+//! when chosen parameters give a special group.
+//!
+//! The traces are only known approximately, so the minimal polynomials are
+//! found by searching for small integer relations among the powers of a
+//! trace with the LLL algorithm.  A polynomial that is found is very likely
+//! but not certain to be correct, and nothing is found if the degree or
+//! the coefficients are too large for double precision.
+
+use crate::elements::elements;
+use crate::{Cpx, Generator};
+
+/// The weight given to the value of the polynomial relative to the size
+/// of its coefficients when searching for relations.
+const SCALE: f64 = 1e12;
+
+/// The largest relative error allowed for a polynomial to be accepted.
+const TOLERANCE: f64 = 1e-13;
+
+/// The largest acceptable chance that a polynomial was found by accident.
+const FALSE_POSITIVE: f64 = 1e-3;
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Returns the Gram-Schmidt orthogonalization of the rows of `b`, along
+/// with the coefficients expressing `b` in terms of it.
+fn gram_schmidt(b: &[Vec<f64>]) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    let n = b.len();
+    let mut bs: Vec<Vec<f64>> = Vec::with_capacity(n);
+    let mut mu = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        let mut v = b[i].clone();
+        for j in 0..i {
+            mu[i][j] = dot(&b[i], &bs[j]) / dot(&bs[j], &bs[j]);
+            for (x, y) in v.iter_mut().zip(&bs[j]) {
+                *x -= mu[i][j] * y;
+            }
+        }
+        bs.push(v);
+    }
+    (bs, mu)
+}
+
+/// Reduces the rows of `b` with the LLL algorithm, so that the first row
+/// is short.
+fn lll(b: &mut [Vec<f64>]) {
+    const DELTA: f64 = 0.75;
+    let n = b.len();
+    let mut k = 1;
+    while k < n {
+        let (bs, mut mu) = gram_schmidt(b);
+        for j in (0..k).rev() {
+            let q = mu[k][j].round();
+            if q != 0.0 {
+                let bj = b[j].clone();
+                for (x, y) in b[k].iter_mut().zip(&bj) {
+                    *x -= q * y;
+                }
+                let (above, below) = mu.split_at_mut(k);
+                for (x, y) in below[0][..j].iter_mut().zip(&above[j][..j]) {
+                    *x -= q * y;
+                }
+                mu[k][j] -= q;
+            }
+        }
+        let lhs = dot(&bs[k], &bs[k]);
+        let rhs = (DELTA - mu[k][k - 1] * mu[k][k - 1]) * dot(&bs[k - 1], &bs[k - 1]);
+        if lhs >= rhs {
+            k += 1;
+        } else {
+            b.swap(k, k - 1);
+            k = usize::max(k - 1, 1);
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Looks for an integer polynomial of degree exactly `degree` with a root
+/// at `x`, with coefficients listed from the constant term up.
+fn relation(x: Cpx, degree: usize) -> Option<Vec<i64>> {
+    let powers: Vec<Cpx> = (0..=degree).map(|i| x.powu(i as u32)).collect();
+    let mut basis: Vec<Vec<f64>> = powers
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let mut row = vec![0.0; degree + 3];
+            row[i] = 1.0;
+            row[degree + 1] = SCALE * p.re;
+            row[degree + 2] = SCALE * p.im;
+            row
+        })
+        .collect();
+    lll(&mut basis);
+    let mut coeffs: Vec<i64> = basis[0][..=degree].iter().map(|&c| c as i64).collect();
+    if coeffs[degree] == 0 {
+        return None;
+    }
+    let g = coeffs.iter().fold(0, |g, &c| gcd(g, c));
+    let sign = coeffs[degree].signum();
+    for c in coeffs.iter_mut() {
+        *c = sign * *c / g;
+    }
+    let value: Cpx = coeffs.iter().zip(&powers).map(|(&c, p)| c as f64 * p).sum();
+    let size: f64 = coeffs
+        .iter()
+        .zip(&powers)
+        .map(|(&c, p)| (c as f64 * p).norm())
+        .sum();
+    // There are about H^(degree + 1) polynomials with coefficients of size
+    // at most H, and each has a value as small as the tolerance with chance
+    // about TOLERANCE, or its square if x is not real.
+    let height = coeffs.iter().map(|c| c.abs()).max().unwrap_or(1) as f64;
+    let constraints = if x.im.abs() > TOLERANCE * x.norm() {
+        2
+    } else {
+        1
+    };
+    let chance = height.powi(degree as i32 + 1) * TOLERANCE.powi(constraints);
+    (value.norm() <= TOLERANCE * size && chance <= FALSE_POSITIVE).then_some(coeffs)
+}
+
+/// Returns the integer polynomial of smallest degree, up to `max_degree`,
+/// that has a root at `x`, with coefficients listed from the constant term
+/// up, the leading coefficient positive, and no common factor.
+pub fn minimal_polynomial(x: Cpx, max_degree: usize) -> Option<Vec<i64>> {
+    (1..=max_degree).find_map(|d| relation(x, d))
+}
+
+/// A trace, along with its minimal polynomial if one was found.
+#[derive(Clone, Debug)]
+pub struct Trace {
+    /// The word whose trace this is, as a sequence of generator indices in
+    /// the order a, b, a^{-1}, b^{-1}.
+    pub word: Vec<u8>,
+    pub trace: Cpx,
+    pub polynomial: Option<Vec<i64>>,
+}
+
+impl Trace {
+    /// Returns whether the trace was recognized as an algebraic integer.
+    pub fn is_integral(&self) -> bool {
+        self.polynomial
+            .as_ref()
+            .is_some_and(|p| p.last() == Some(&1))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceField {
+    pub traces: Vec<Trace>,
+    /// The minimal polynomial of a generator of the field, if one was
+    /// found.  Its degree is the degree of the field.
+    pub polynomial: Option<Vec<i64>>,
+}
+
+impl TraceField {
+    pub fn degree(&self) -> Option<usize> {
+        self.polynomial.as_ref().map(|p| p.len() - 1)
+    }
+    /// Returns whether all of the traces were recognized as algebraic
+    /// integers, which is necessary for the group to be arithmetic.
+    pub fn integral_traces(&self) -> bool {
+        self.traces.iter().all(Trace::is_integral)
+    }
+}
+
+/// Returns the distinct traces of the reduced words of length 1 to
+/// `max_length`, up to sign.
+fn word_traces(gens: &[Generator; 4], max_length: usize) -> Vec<(Vec<u8>, Cpx)> {
+    let mut found: Vec<(Vec<u8>, Cpx)> = Vec::new();
+    for (w, m) in elements(gens)
+        .skip(1)
+        .take_while(|(w, _)| w.len() <= max_length)
+    {
+        let t = m.trace();
+        let close = |u: &Cpx| (u - t).norm() < 1e-9 * (1.0 + t.norm());
+        if !found.iter().any(|(_, u)| close(u) || close(&-u)) {
+            found.push((w, t));
+        }
+    }
+    found
+}
+
+/// Collects the traces of the words of length up to `max_length` and
+/// tries to recognize the field that they generate, looking for minimal
+/// polynomials of degree up to `max_degree`.  The field is generated by a
+/// combination of the traces of a, b, and ab with small integer
+/// coefficients, which generates the whole field unless the coefficients
+/// are unlucky.
+pub fn trace_field(gens: &[Generator; 4], max_length: usize, max_degree: usize) -> TraceField {
+    let traces: Vec<Trace> = word_traces(gens, max_length)
+        .into_iter()
+        .map(|(word, trace)| Trace {
+            polynomial: minimal_polynomial(trace, max_degree),
+            word,
+            trace,
+        })
+        .collect();
+    // The traces of a, b, and ab generate the trace field of a group with
+    // two generators.
+    let (a, b) = (&gens[0].matrix, &gens[1].matrix);
+    let primitive = a.trace() + 3.0 * b.trace() + 7.0 * (a * b).trace();
+    TraceField {
+        polynomial: minimal_polynomial(primitive, max_degree),
+        traces,
+    }
+}
@@ -0,0 +1,166 @@
+//! The Bers slice of the once-punctured torus.
+//!
+//! A quasifuchsian group of a punctured torus has two surfaces at infinity,
+//! one on each side of its limit set.  The Bers slice fixes the bottom one,
+//! the torus C/(Z + τZ) with the origin removed, and lets the top one vary.
+//! Each group in the slice is the holonomy of a projective structure on the
+//! bottom torus, given by the solutions of y'' + (℘(z)/4 + H) y = 0, where
+//! ℘ is the Weierstrass function of the lattice: the ratio of two solutions
+//! is a map from the universal cover of the punctured torus whose image is
+//! the component of the domain of discontinuity below the limit set.  The
+//! term ℘/4 makes the holonomy around the puncture parabolic, and one value
+//! H_0 of the accessory parameter gives the Fuchsian group of the torus.
+//! The point φ = H - H_0 of the slice is the coefficient of the quadratic
+//! differential φ dz^2 by which the structure differs from the Fuchsian
+//! one, and the slice is the bounded region of φ for which the holonomy is
+//! quasifuchsian.  For the square torus it meets the real axis at about
+//! φ = ±0.78, where a or b becomes parabolic.
+//!
+//! The holonomy is found by solving the equation along the periods, so the
+//! groups vary holomorphically with φ, and paths through the slice can be
+//! animated without tracking roots.
+
+use crate::{generators_from_matrices, matrices_for_traces, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+use std::f64::consts::PI;
+
+/// The number of Runge–Kutta steps taken along each period.
+const STEPS: usize = 1000;
+
+/// The Fuchsian accessory parameter is accepted when the traces are real to
+/// within this.
+const REAL_TOLERANCE: f64 = 1e-10;
+
+/// The most Gauss–Newton steps taken to find the Fuchsian accessory parameter.
+const MAX_STEPS: usize = 50;
+
+/// The Bers slice with a given bottom surface.
+#[derive(Clone, Copy, Debug)]
+pub struct BersSlice {
+    /// The bottom torus is C/(Z + `tau` Z), with `tau` in the upper half
+    /// plane.
+    pub tau: Cpx,
+    /// The accessory parameter H_0 of the Fuchsian group.
+    pub fuchsian: Cpx,
+}
+
+impl BersSlice {
+    /// Returns the slice whose bottom surface is the square torus, for
+    /// which H_0 = 0 by symmetry.
+    pub fn square() -> Self {
+        BersSlice {
+            tau: Cpx::i(),
+            fuchsian: Cpx::from(0.0),
+        }
+    }
+    /// Returns the slice whose bottom surface is C/(Z + `tau` Z).  H_0 is
+    /// found by the Gauss–Newton method, as the accessory parameter for
+    /// which tr a, tr b, and tr ab are real, starting from 0, so it is
+    /// found most easily for tori near the square one.  Returns `Error::DegenerateGenerators`
+    /// if `tau` is not in the upper half plane or the method does not
+    /// converge.
+    pub fn new(tau: Cpx) -> Result<Self, Error> {
+        if tau.im <= 0.0 || tau.im.is_nan() {
+            return Err(Error::DegenerateGenerators);
+        }
+        let traces = |h: Cpx| {
+            let [a, b] = holonomy(tau, h);
+            [a.trace(), b.trace(), (a * b).trace()]
+        };
+        let mut h = Cpx::from(0.0);
+        for _ in 0..MAX_STEPS {
+            let t = traces(h);
+            if t.iter()
+                .all(|t| t.im.abs() < REAL_TOLERANCE * t.norm().max(1.0))
+            {
+                return Ok(BersSlice { tau, fuchsian: h });
+            }
+            // The traces are holomorphic in H, so the derivatives of their
+            // imaginary parts come from their complex derivatives, and the
+            // step is the least squares solution of the three equations in
+            // the real and imaginary parts of H.
+            let eps = 1e-6;
+            let d = traces(h + eps);
+            let rows: Vec<[f64; 3]> = (0..3)
+                .map(|k| {
+                    let dt = (d[k] - t[k]) / eps;
+                    [dt.im, dt.re, t[k].im]
+                })
+                .collect();
+            let dot = |i: usize, j: usize| rows.iter().map(|r| r[i] * r[j]).sum::<f64>();
+            let (g00, g01, g11, r0, r1) = (dot(0, 0), dot(0, 1), dot(1, 1), dot(0, 2), dot(1, 2));
+            let det = g00 * g11 - g01 * g01;
+            h -= Cpx::new(g11 * r0 - g01 * r1, g00 * r1 - g01 * r0) / det;
+            if !h.is_finite() {
+                break;
+            }
+        }
+        Err(Error::DegenerateGenerators)
+    }
+    /// Returns tr a, tr b, and tr ab for the point `phi` of the slice,
+    /// with the signs chosen so that tr a and tr b have non-negative real
+    /// parts.
+    pub fn traces(&self, phi: Cpx) -> [Cpx; 3] {
+        let [mut a, mut b] = holonomy(self.tau, self.fuchsian + phi);
+        if a.trace().re < 0.0 {
+            a = -a;
+        }
+        if b.trace().re < 0.0 {
+            b = -b;
+        }
+        [a.trace(), b.trace(), (a * b).trace()]
+    }
+    /// Returns the generators for the point `phi` of the slice, with the
+    /// matrices normalized as in `generators`.
+    pub fn generators(&self, phi: Cpx) -> [Generator; 4] {
+        let [ta, tb, tab] = self.traces(phi);
+        let (a, b) = matrices_for_traces(ta, tb, tab);
+        generators_from_matrices(a, b)
+    }
+}
+
+/// Returns the Weierstrass ℘ function of the lattice Z + `tau` Z at `z`.
+fn weierstrass_p(z: Cpx, tau: Cpx) -> Cpx {
+    // Moving z into the strip |Im z| <= Im τ / 2 makes the series converge
+    // quickly.
+    let z = z - (z.im / tau.im).round() * tau;
+    let z = z - z.re.round();
+    let two_pi_i = Cpx::new(0.0, 2.0 * PI);
+    let (q, u) = ((two_pi_i * tau).exp(), (two_pi_i * z).exp());
+    let term = |x: Cpx| x / ((1.0 - x) * (1.0 - x));
+    let mut sum = Cpx::from(1.0 / 12.0) + term(u);
+    let mut qn = q;
+    while qn.norm() > 1e-18 {
+        sum += term(qn * u) + term(qn / u) - 2.0 * term(qn);
+        qn *= q;
+    }
+    two_pi_i * two_pi_i * sum
+}
+
+/// Returns the monodromy of y'' + (℘(z)/4 + `h`) y = 0 along the periods 1
+/// and `tau`, starting from the center of the period parallelogram, which
+/// keeps the paths as far as possible from the puncture.  The matrices
+/// act on (y, y'), and are taken with inverses so that they give a
+/// homomorphism from the fundamental group.
+fn holonomy(tau: Cpx, h: Cpx) -> [Matrix2<Cpx>; 2] {
+    let start = 0.5 * (1.0 + tau);
+    [Cpx::from(1.0), tau].map(|period| {
+        // Runge–Kutta for Y' = period (0 1; -Q 0) Y along z = start + s
+        // period, for s from 0 to 1.
+        let deriv = |s: f64, y: &Matrix2<Cpx>| {
+            let q = 0.25 * weierstrass_p(start + s * period, tau) + h;
+            Matrix2::new(y[(1, 0)], y[(1, 1)], -q * y[(0, 0)], -q * y[(0, 1)]) * period
+        };
+        let dt = 1.0 / STEPS as f64;
+        let mut y = Matrix2::identity();
+        for k in 0..STEPS {
+            let s = k as f64 * dt;
+            let k1 = deriv(s, &y);
+            let k2 = deriv(s + 0.5 * dt, &(y + k1 * Cpx::from(0.5 * dt)));
+            let k3 = deriv(s + 0.5 * dt, &(y + k2 * Cpx::from(0.5 * dt)));
+            let k4 = deriv(s + dt, &(y + k3 * Cpx::from(dt)));
+            y += (k1 + k2 * Cpx::from(2.0) + k3 * Cpx::from(2.0) + k4) * Cpx::from(dt / 6.0);
+        }
+        crate::algebra::inv(&y)
+    })
+}
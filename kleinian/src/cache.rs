@@ -0,0 +1,153 @@
+//! A cache of generated points on disk, so that drawing the same group
+//! again, at a different size or in different colors, skips exploring it.
+//!
+//! Each entry is keyed by the exact generators and the number of points.
+//! The generators are determined by the recipe and its parameters, so this
+//! is the same as keying by those, but it also covers Nielsen moves,
+//! choices of circles, and conjugations.  Entries are never removed; delete
+//! the directory to clear the cache.
+
+use crate::{generate_labelled_points, Cpx, Error, Generator, LimitPoint};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Identifies cache files, and changes whenever their format does.
+const MAGIC: &[u8; 8] = b"KLCACHE1";
+
+/// The size of an encoded point: the center, the radius, the generator,
+/// and the depth.
+const RECORD_SIZE: usize = 8 * 3 + 1 + 4;
+
+/// A directory of cached points.
+#[derive(Clone, Debug)]
+pub struct PointCache {
+    dir: PathBuf,
+}
+
+/// Returns the bytes that identify an exploration.
+fn key_bytes(gens: &[Generator; 4], num_points: usize) -> Vec<u8> {
+    let mut key = Vec::new();
+    for g in gens {
+        for z in g.matrix.iter().chain(g.circle.0.iter()) {
+            key.extend_from_slice(&z.re.to_bits().to_le_bytes());
+            key.extend_from_slice(&z.im.to_bits().to_le_bytes());
+        }
+    }
+    key.extend_from_slice(&(num_points as u64).to_le_bytes());
+    key
+}
+
+/// The 64-bit FNV-1a hash, which unlike the hashers of the standard library
+/// is the same in every version, so it can name files.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn write_entry(path: &Path, key: &[u8], pts: &[LimitPoint]) -> io::Result<()> {
+    // Write to a temporary file first, so that a reader never sees a
+    // partly written entry.
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&(key.len() as u64).to_le_bytes())?;
+    out.write_all(key)?;
+    out.write_all(&(pts.len() as u64).to_le_bytes())?;
+    for p in pts {
+        out.write_all(&p.z.re.to_le_bytes())?;
+        out.write_all(&p.z.im.to_le_bytes())?;
+        out.write_all(&p.radius.to_le_bytes())?;
+        out.write_all(&[p.generator])?;
+        out.write_all(&(p.depth as u32).to_le_bytes())?;
+    }
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+fn read_u64<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Returns the points of the entry, or `None` if it is for a different
+/// key, which can only happen if two keys have the same hash.
+fn read_entry(path: &Path, key: &[u8]) -> io::Result<Option<Vec<LimitPoint>>> {
+    let mut input = BufReader::new(File::open(path)?);
+    let mut magic = [0; 8];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Ok(None);
+    }
+    if read_u64(&mut input)? != key.len() as u64 {
+        return Ok(None);
+    }
+    let mut stored = vec![0; key.len()];
+    input.read_exact(&mut stored)?;
+    if stored != key {
+        return Ok(None);
+    }
+    let n = read_u64(&mut input)? as usize;
+    // A damaged file could claim any number of points.
+    let mut pts = Vec::with_capacity(n.min(1 << 20));
+    let mut rec = [0; RECORD_SIZE];
+    let f64_at =
+        |rec: &[u8; RECORD_SIZE], i: usize| f64::from_le_bytes(rec[i..i + 8].try_into().unwrap());
+    for _ in 0..n {
+        input.read_exact(&mut rec)?;
+        pts.push(LimitPoint {
+            z: Cpx::new(f64_at(&rec, 0), f64_at(&rec, 8)),
+            radius: f64_at(&rec, 16),
+            generator: rec[24],
+            depth: u32::from_le_bytes(rec[25..29].try_into().unwrap()) as usize,
+        });
+    }
+    Ok(Some(pts))
+}
+
+impl PointCache {
+    /// Returns the cache in the directory, which is created when the first
+    /// entry is stored.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        PointCache { dir: dir.into() }
+    }
+    /// Returns the path of the file that would hold the entry.
+    pub fn path(&self, gens: &[Generator; 4], num_points: usize) -> PathBuf {
+        let hash = fnv1a(&key_bytes(gens, num_points));
+        self.dir.join(format!("{:016x}.points", hash))
+    }
+    /// Returns the points that `generate_labelled_points` would return,
+    /// from the cache if they are there, and otherwise by exploring the
+    /// group and then storing them.  Problems with the cache are not
+    /// errors: an unreadable entry is generated again, and an entry that
+    /// cannot be stored is just returned.
+    pub fn generate_labelled_points(
+        &self,
+        gens: [Generator; 4],
+        num_points: usize,
+    ) -> Result<Vec<LimitPoint>, Error> {
+        let key = key_bytes(&gens, num_points);
+        let path = self.path(&gens, num_points);
+        if let Ok(Some(pts)) = read_entry(&path, &key) {
+            return Ok(pts);
+        }
+        let pts = generate_labelled_points(gens, num_points)?;
+        let _ = fs::create_dir_all(&self.dir).and_then(|()| write_entry(&path, &key, &pts));
+        Ok(pts)
+    }
+    /// Like `generate_labelled_points`, but only returns the points, as
+    /// `generate_points` does.
+    pub fn generate_points(
+        &self,
+        gens: [Generator; 4],
+        num_points: usize,
+    ) -> Result<Vec<Cpx>, Error> {
+        Ok(self
+            .generate_labelled_points(gens, num_points)?
+            .into_iter()
+            .map(|p| p.z)
+            .collect())
+    }
+}
@@ -1,11 +1,20 @@
 use crate::algebra::{inv, inv_dagger};
+use crate::projective::{ProjectivePoint, MAX_MODULUS};
+use crate::reflection::{conj, MobiusOrAnti};
 use crate::Cpx;
 use core::ops::Mul;
 use nalgebra::Matrix2;
 
+/// A circle or line, represented by a Hermitian matrix C such that it is
+/// the set of points z with (z̄ 1) C (z 1)^T = 0.  The matrix is scaled so
+/// that its determinant is -1, which makes the entry in the top left the
+/// curvature, up to sign.  Lines have curvature 0.
 #[derive(Clone, Copy, Debug)]
 pub struct Circle(pub Matrix2<Cpx>);
 
+/// The relative error allowed by `Circle::is_valid`.
+const VALID_TOLERANCE: f64 = 1e-6;
+
 impl Mul<Circle> for Matrix2<Cpx> {
     type Output = Circle;
     fn mul(self, c: Circle) -> Circle {
@@ -13,11 +22,80 @@ impl Mul<Circle> for Matrix2<Cpx> {
     }
 }
 
+impl Mul<Circle> for MobiusOrAnti {
+    type Output = Circle;
+    fn mul(self, c: Circle) -> Circle {
+        // Conjugation maps the circle with matrix C to the one with matrix
+        // C̄, which is its transpose.
+        let c = if self.anti { Circle(conj(&c.0)) } else { c };
+        self.matrix * c
+    }
+}
+
 impl Circle {
+    /// Returns the circle with the given center and radius.
+    pub fn new(center: Cpx, radius: f64) -> Self {
+        let r = Cpx::from(radius);
+        Circle(
+            Matrix2::new(
+                Cpx::from(1.0),
+                -center,
+                -center.conj(),
+                Cpx::from(center.norm_sqr() - radius * radius),
+            ) / r,
+        )
+    }
     pub fn radius_inv(&self) -> f64 {
         self.0[(0, 0)].re.abs()
     }
+    /// Returns the radius, which is infinite for lines.
+    pub fn radius(&self) -> f64 {
+        1.0 / self.radius_inv()
+    }
+    /// Returns the center, which is not finite for lines, and inaccurate
+    /// for circles that are nearly lines.  See `center_point`.
     pub fn center(&self) -> Cpx {
         -self.0[(0, 1)] / self.0[(0, 0)].re
     }
+    /// Returns the center as a point of the Riemann sphere, which is
+    /// infinity for lines.
+    pub fn center_point(&self) -> ProjectivePoint {
+        ProjectivePoint(-self.0[(0, 1)], Cpx::from(self.0[(0, 0)].re))
+    }
+    /// Returns whether the circle is a line, up to rounding error: whether
+    /// its curvature is too small to be distinguished from 0.
+    pub fn is_line(&self) -> bool {
+        let size = self.0.iter().map(|z| z.norm()).fold(0.0, f64::max);
+        self.radius_inv() <= 1e-12 * size
+    }
+    /// Returns whether the circle is a line, or reaches farther from the
+    /// origin than `projective::MAX_MODULUS`, so that it passes through or
+    /// near infinity.
+    pub fn passes_near_infinity(&self) -> bool {
+        self.is_line() || self.center().norm() + self.radius() > MAX_MODULUS
+    }
+    /// Returns whether the matrix is finite and Hermitian with determinant
+    /// -1, up to a small error relative to its entries.  Rounding error in
+    /// long products of generators makes deep circles drift away from this.
+    pub fn is_valid(&self) -> bool {
+        let m = &self.0;
+        if !m.iter().all(|z| z.is_finite()) {
+            return false;
+        }
+        let size = m.iter().map(|z| z.norm()).fold(0.0, f64::max);
+        let skew = (m[(0, 1)] - m[(1, 0)].conj())
+            .norm()
+            .max(m[(0, 0)].im.abs())
+            .max(m[(1, 1)].im.abs());
+        let det = m.determinant();
+        skew <= VALID_TOLERANCE * size && (det + 1.0).norm() <= VALID_TOLERANCE * size * size
+    }
+    /// Returns the nearest circle: the Hermitian part of the matrix,
+    /// rescaled to have determinant -1.  Returns `None` if the determinant
+    /// of the Hermitian part is not negative, since it is then not a circle.
+    pub fn repair(&self) -> Option<Circle> {
+        let h = (self.0 + self.0.adjoint()).map(|z| z * 0.5);
+        let det = h.determinant().re;
+        (det < 0.0).then(|| Circle(h / Cpx::from((-det).sqrt())))
+    }
 }
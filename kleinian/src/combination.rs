@@ -0,0 +1,99 @@
+//! Building new groups from old ones with the combination theorems of
+//! Klein and Maskit.  The groups produced have more than two generators,
+//! and are explored with `CircleQueue::with_generators`.  Generators are
+//! given as in that function, as a list of the generators followed by
+//! their inverses in the same order.
+
+use crate::algebra::inv;
+use crate::{check_generators, Circle, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+
+/// Returns whether the disks bounded by the circles are disjoint, except
+/// perhaps for a point of tangency.  A line bounds two half-planes, and it
+/// is not recorded which one is meant, so lines are never disjoint from
+/// anything.
+fn disjoint(c: &Circle, d: &Circle) -> bool {
+    let (ci, di) = (c.radius_inv(), d.radius_inv());
+    if ci == 0.0 || di == 0.0 {
+        return false;
+    }
+    let dist = (c.center() - d.center()).norm();
+    dist >= (1.0 / ci + 1.0 / di) * (1.0 - 1e-9)
+}
+
+/// Returns the generators of the group generated by both groups, which is
+/// their free product when every circle of `g` is disjoint from every
+/// circle of `h`: each group then maps the circles of the other into its
+/// own circles, and the circles of both together bound a fundamental
+/// domain.  This is the first combination theorem.  If any circle is a
+/// line, conjugate the groups first so that all of the circles are bounded.
+/// The generators of `g` come first, followed by those of `h`, and then
+/// their inverses in the same order.
+pub fn klein_combination(g: &[Generator], h: &[Generator]) -> Result<Vec<Generator>, Error> {
+    check_generators(g)?;
+    check_generators(h)?;
+    for c in g {
+        for d in h {
+            if !disjoint(&c.circle, &d.circle) {
+                return Err(Error::OverlappingCircles);
+            }
+        }
+    }
+    let (kg, kh) = (g.len() / 2, h.len() / 2);
+    let copy = |x: &Generator| Generator {
+        matrix: x.matrix,
+        circle: x.circle,
+    };
+    Ok(g[..kg]
+        .iter()
+        .chain(&h[..kh])
+        .chain(&g[kg..])
+        .chain(&h[kh..])
+        .map(copy)
+        .collect())
+}
+
+/// Returns the generators of the group generated by `g` and one more
+/// generator f, which maps the outside of `c1` onto the inside of `c2`,
+/// rotated by the angle `twist` around the center of `c2`.  When `c1` and
+/// `c2` are disjoint from each other and from the circles of `g`, this is
+/// an HNN extension, the second combination theorem, and the circles of
+/// `g` together with `c1` and `c2` bound a fundamental domain.  Both
+/// circles must be bounded.  The generators of `g` come first, followed by
+/// f, and then their inverses in the same order.
+pub fn hnn_extension(
+    g: &[Generator],
+    c1: Circle,
+    c2: Circle,
+    twist: f64,
+) -> Result<Vec<Generator>, Error> {
+    check_generators(g)?;
+    if !disjoint(&c1, &c2)
+        || g.iter()
+            .any(|x| !disjoint(&x.circle, &c1) || !disjoint(&x.circle, &c2))
+    {
+        return Err(Error::OverlappingCircles);
+    }
+    // f(z) = q + rs e^{iθ} / (z - p) takes the circle of radius r around p
+    // to the circle of radius s around q, swapping the insides.
+    let (p, r) = (c1.center(), 1.0 / c1.radius_inv());
+    let (q, s) = (c2.center(), 1.0 / c2.radius_inv());
+    let k = r * s * Cpx::from_polar(1.0, twist);
+    let f = Matrix2::new(q, k - q * p, Cpx::from(1.0), -p) / (-k).sqrt();
+    let copy = |x: &Generator| Generator {
+        matrix: x.matrix,
+        circle: x.circle,
+    };
+    let rank = g.len() / 2;
+    let mut gens: Vec<Generator> = g[..rank].iter().map(copy).collect();
+    gens.push(Generator {
+        matrix: f,
+        circle: c2,
+    });
+    gens.extend(g[rank..].iter().map(copy));
+    gens.push(Generator {
+        matrix: inv(&f),
+        circle: c1,
+    });
+    Ok(gens)
+}
@@ -0,0 +1,73 @@
+//! Conjugation of generators by Möbius transformations.  Conjugate groups
+//! have limit sets that are images of each other, so conjugation changes
+//! the framing of a picture, by moving, turning, and bending the limit set,
+//! without changing the group.  Random conjugations from a seed give many
+//! compositions of the same group that can be reproduced exactly.
+
+use crate::algebra::inv;
+use crate::{Cpx, Generator};
+use nalgebra::Matrix2;
+
+/// Returns the generators conjugated by the transformation with matrix `m`
+/// (of determinant 1), so that each matrix g becomes m g m^{-1} and each
+/// circle c becomes its image m c.
+pub fn conjugate<const N: usize>(gens: &[Generator; N], m: &Matrix2<Cpx>) -> [Generator; N] {
+    let mi = inv(m);
+    std::array::from_fn(|i| Generator {
+        matrix: m * gens[i].matrix * mi,
+        circle: *m * gens[i].circle,
+    })
+}
+
+/// The SplitMix64 generator, which is written out here so that a seed gives
+/// the same transformation in every version.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+    /// Returns a number uniformly distributed in [-1, 1).
+    fn next_signed(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+    /// Returns a complex number with both parts in [-1, 1).
+    fn next_cpx(&mut self) -> Cpx {
+        Cpx::new(self.next_signed(), self.next_signed())
+    }
+}
+
+/// Returns the matrix of a random Möbius transformation, determined by
+/// `seed`.  The transformation scales and rotates the plane, then moves
+/// the point at infinity to a finite point, which bends lines into circles,
+/// and then translates.  `wildness` is the size of each of these steps:
+/// the identity for 0, a gentle change of framing for 0.2 or so, and a
+/// drastic one for 1 or more, in the units of the plane for the limit sets
+/// of the recipes, which have size about 1.  With a large wildness, the
+/// point that is moved to infinity may lie on the limit set, which then
+/// stretches across the whole plane and does not fit well in a window.
+pub fn random_mobius(seed: u64, wildness: f64) -> Matrix2<Cpx> {
+    let mut rng = SplitMix64(seed);
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    let z = rng.next_cpx();
+    let log = wildness * Cpx::new(0.5 * z.re, std::f64::consts::PI * z.im);
+    let s = (0.5 * log).exp();
+    let scale = Matrix2::new(s, zero, zero, one / s);
+    let bend = Matrix2::new(one, zero, wildness * rng.next_cpx(), one);
+    let shift = Matrix2::new(one, wildness * rng.next_cpx(), zero, one);
+    shift * bend * scale
+}
+
+/// Returns the generators conjugated by `random_mobius(seed, wildness)`.
+pub fn random_conjugate<const N: usize>(
+    gens: &[Generator; N],
+    seed: u64,
+    wildness: f64,
+) -> [Generator; N] {
+    conjugate(gens, &random_mobius(seed, wildness))
+}
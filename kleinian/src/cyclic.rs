@@ -0,0 +1,131 @@
+//! Groups generated by a single Möbius transformation.  These are much
+//! simpler than the groups drawn by the rest of the library, but they show
+//! the basic kinds of motion that the generators of those groups make.
+
+use crate::algebra::{from_fixed_point, from_fixed_points};
+use crate::{Circle, Cpx};
+use nalgebra::Matrix2;
+
+/// The classification of Möbius transformations, other than the identity,
+/// up to conjugacy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Rotation around two fixed points.
+    Elliptic,
+    /// Translation away from a single fixed point.
+    Parabolic,
+    /// Expansion away from one fixed point and towards the other.
+    Hyperbolic,
+    /// A combination of elliptic and hyperbolic motion, so that orbits
+    /// spiral between the fixed points.
+    Loxodromic,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Elliptic => "elliptic",
+            Kind::Parabolic => "parabolic",
+            Kind::Hyperbolic => "hyperbolic",
+            Kind::Loxodromic => "loxodromic",
+        }
+    }
+}
+
+/// Applies the Möbius transformation with matrix `m` to `z`.
+pub fn mobius(m: &Matrix2<Cpx>, z: Cpx) -> Cpx {
+    (m[(0, 0)] * z + m[(0, 1)]) / (m[(1, 0)] * z + m[(1, 1)])
+}
+
+/// Applies the Möbius transformation with matrix `m` to every point of
+/// `pts`, in place.  This gives the same results as `mobius`, but the loop
+/// is written in real arithmetic without branches so that the compiler
+/// can vectorize it, which makes it several times faster on long slices.
+/// The flows of `flow`, the vertices of the paths of `warp`, and the
+/// points animated by the web demo are mapped with it.
+pub fn apply_all(m: &Matrix2<Cpx>, pts: &mut [Cpx]) {
+    let (a, b, c, d) = (m[(0, 0)], m[(0, 1)], m[(1, 0)], m[(1, 1)]);
+    for z in pts {
+        let (x, y) = (z.re, z.im);
+        let nr = a.re * x - a.im * y + b.re;
+        let ni = a.re * y + a.im * x + b.im;
+        let dr = c.re * x - c.im * y + d.re;
+        let di = c.re * y + c.im * x + d.im;
+        let s = 1.0 / (dr * dr + di * di);
+        *z = Cpx::new((nr * dr + ni * di) * s, (ni * dr - nr * di) * s);
+    }
+}
+
+/// The group generated by a Möbius transformation, described by its fixed
+/// points and its multiplier.
+#[derive(Clone, Copy, Debug)]
+pub struct CyclicGroup {
+    p: Cpx,
+    q: Cpx,
+    k: Cpx,
+}
+
+impl CyclicGroup {
+    /// Returns the group generated by the transformation that fixes `p` and
+    /// `q` and multiplies (z - p) / (z - q) by `k`.  If `p` and `q` are
+    /// equal, the transformation is parabolic, and adds `k` to 1 / (z - p)
+    /// instead.  The matrices are as in `algebra::from_fixed_points` and
+    /// `algebra::from_fixed_point`.
+    pub fn new(p: Cpx, q: Cpx, k: Cpx) -> Self {
+        CyclicGroup { p, q, k }
+    }
+    pub fn is_parabolic(&self) -> bool {
+        self.p == self.q
+    }
+    pub fn kind(&self) -> Kind {
+        if self.is_parabolic() {
+            Kind::Parabolic
+        } else if (self.k.norm() - 1.0).abs() < 1e-12 {
+            Kind::Elliptic
+        } else if self.k.im.abs() < 1e-12 && self.k.re > 0.0 {
+            Kind::Hyperbolic
+        } else {
+            Kind::Loxodromic
+        }
+    }
+    /// Returns a matrix of determinant 1 for the `t`th power of the
+    /// generator.  Fractional powers interpolate between the iterates, so
+    /// that the motion can be animated.
+    pub fn matrix(&self, t: f64) -> Matrix2<Cpx> {
+        if self.is_parabolic() {
+            from_fixed_point(self.p, self.k * t)
+        } else {
+            from_fixed_points(self.p, self.q, self.k.powf(t))
+        }
+    }
+    /// Returns the images of `z` under the powers of the generator from
+    /// -`n` to `n`.
+    pub fn orbit(&self, z: Cpx, n: usize) -> Vec<Cpx> {
+        let (m, mi) = (self.matrix(1.0), self.matrix(-1.0));
+        let mut pts = iterate(z, n, |z| mobius(&mi, *z));
+        pts.reverse();
+        pts.pop();
+        pts.extend(iterate(z, n, |z| mobius(&m, *z)));
+        pts
+    }
+    /// Like `orbit`, but for a circle.
+    pub fn circle_orbit(&self, c: Circle, n: usize) -> Vec<Circle> {
+        let (m, mi) = (self.matrix(1.0), self.matrix(-1.0));
+        let mut circles = iterate(c, n, |c| mi * *c);
+        circles.reverse();
+        circles.pop();
+        circles.extend(iterate(c, n, |c| m * *c));
+        circles
+    }
+}
+
+/// Returns `x` followed by the first `n` iterates of `f`.
+fn iterate<T, F: Fn(&T) -> T>(x: T, n: usize, f: F) -> Vec<T> {
+    let mut v = Vec::with_capacity(n + 1);
+    v.push(x);
+    for i in 0..n {
+        let y = f(&v[i]);
+        v.push(y);
+    }
+    v
+}
@@ -0,0 +1,122 @@
+//! Deformations of a group along the axis of one of its generators.
+//!
+//! Cutting the punctured torus along the closed geodesic of b and gluing
+//! it back with a twist replaces a by aE, where E is a transformation with
+//! the same axis as b.  Since E commutes with b, the commutator of the
+//! generators does not change.  If E translates along the axis by t, this
+//! is an earthquake, which keeps a Fuchsian group Fuchsian.  If E rotates
+//! around the axis by θ, this is bending by the angle θ, which makes a
+//! Fuchsian group quasifuchsian when θ is small.  In general E has complex
+//! translation length τ = t + iθ, and this is called a quakebend.  Scaling
+//! τ gives a path through the deformation space that can be animated.
+//!
+//! The same can be done along any simple closed curve on the torus, which
+//! is the geodesic of a Farey word W_{p/q}, as in `pleating`.  The bending
+//! measure of a quasifuchsian group obtained this way is supported on that
+//! curve, with weight θ, so bending a Fuchsian group along p/q by a growing
+//! angle moves it from a round circle towards the p/q cusp, where
+//! W_{p/q} becomes parabolic.
+
+use crate::{generators_from_matrices, Cpx, Generator};
+use nalgebra::Matrix2;
+
+/// The generator whose axis the group is deformed along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    A,
+    B,
+}
+
+/// Returns the complex translation length of the transformation with
+/// matrix `m` (of determinant 1), which is the number ℓ with positive real
+/// part (or imaginary part in [0, 2π] if `m` is elliptic) such that
+/// tr m = ±2 cosh(ℓ/2).
+pub fn complex_length(m: &Matrix2<Cpx>) -> Cpx {
+    2.0 * eigenvalue(m).ln()
+}
+
+/// Returns the eigenvalue λ of `m` with |λ| ≥ 1.
+fn eigenvalue(m: &Matrix2<Cpx>) -> Cpx {
+    let t = m.trace();
+    let lambda = 0.5 * (t + (t * t - 4.0).sqrt());
+    if lambda.norm() < 1.0 {
+        1.0 / lambda
+    } else {
+        lambda
+    }
+}
+
+/// Returns the transformation with the same fixed points as `m` that
+/// translates along its axis by the complex length `tau`, in the direction
+/// that `m` does.  Returns `None` if `m` is parabolic or the identity, so
+/// that it does not have an axis.
+pub fn along_axis(m: &Matrix2<Cpx>, tau: Cpx) -> Option<Matrix2<Cpx>> {
+    // The result is a combination of 1 and m, with eigenvalue e^{τ/2} on
+    // the eigenvector of m with eigenvalue λ.
+    let lambda = eigenvalue(m);
+    let diff = lambda - 1.0 / lambda;
+    if diff.norm() < 1e-9 {
+        return None;
+    }
+    let mu = (0.5 * tau).exp();
+    let beta = (mu - 1.0 / mu) / diff;
+    let alpha = mu - beta * lambda;
+    Some(Matrix2::from_diagonal_element(alpha) + m.map(|x| beta * x))
+}
+
+/// Deforms the group along the axis of a or b by the complex length
+/// `tau`, replacing a by aE or b by bE respectively.  Real `tau` is an
+/// earthquake and imaginary `tau` is bending by the angle `tau.im`.  The generators must come from `generators` or
+/// `generators_from_matrices`, so that the commutator is parabolic.
+/// Returns `None` if the generator is parabolic.
+pub fn quakebend(gens: &[Generator; 4], axis: Axis, tau: Cpx) -> Option<[Generator; 4]> {
+    let (a, b) = (gens[0].matrix, gens[1].matrix);
+    Some(match axis {
+        Axis::B => generators_from_matrices(a * along_axis(&b, tau)?, b),
+        Axis::A => generators_from_matrices(a, b * along_axis(&a, tau)?),
+    })
+}
+
+/// Like `quakebend`, but along the geodesic of the Farey word W_{p/q},
+/// where W_{0/1} = a and W_{1/0} = b, so that p/q = 1/0 is the same as
+/// `Axis::B` and p/q = 0/1 is the same as `Axis::A`.  The result is
+/// generated by W_{p/q} and its Farey parent W_{l/m} with l/m < p/q, with
+/// W_{l/m} replaced by W_{l/m}E, so its circles are those of that pair
+/// rather than of a and b.  `p` and `q` must be coprime, and not both 0.
+/// Returns `None` if W_{p/q} is parabolic.
+pub fn quakebend_farey(gens: &[Generator; 4], p: u32, q: u32, tau: Cpx) -> Option<[Generator; 4]> {
+    let (a, b) = (gens[0].matrix, gens[1].matrix);
+    if (p, q) == (0, 1) {
+        return quakebend(gens, Axis::A, tau);
+    }
+    let (parent, word) = farey_pair(p, q, a, b);
+    Some(generators_from_matrices(
+        parent * along_axis(&word, tau)?,
+        word,
+    ))
+}
+
+/// Returns the Farey parent W_{l/m} with l/m < p/q, and W_{p/q}, for p/q
+/// other than 0/1.  The descent is the same as in `pleating::farey_trace`.
+fn farey_pair(p: u32, q: u32, a: Matrix2<Cpx>, b: Matrix2<Cpx>) -> (Matrix2<Cpx>, Matrix2<Cpx>) {
+    let (mut l, mut m, mut r, mut s) = (0, 1, 1, 0);
+    let (mut wl, mut wr) = (a, b);
+    loop {
+        if (p, q) == (r, s) {
+            return (wl, wr);
+        }
+        let (c, d) = (l + r, m + s);
+        let wm = wl * wr;
+        match (p as u64 * d as u64).cmp(&(c as u64 * q as u64)) {
+            std::cmp::Ordering::Equal => return (wl, wm),
+            std::cmp::Ordering::Less => {
+                (r, s) = (c, d);
+                wr = wm;
+            }
+            std::cmp::Ordering::Greater => {
+                (l, m) = (c, d);
+                wl = wm;
+            }
+        }
+    }
+}
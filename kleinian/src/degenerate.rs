@@ -0,0 +1,75 @@
+//! Doubly degenerate groups, found as fixed points of the action of a
+//! mapping class on traces.
+//!
+//! A sequence of Nielsen moves is an automorphism of the free group, and
+//! acts on the traces (tr a, tr b, tr ab) as in `NielsenMove::apply_traces`.
+//! If the automorphism is pseudo-Anosov, such as a ↦ ab followed by
+//! b ↦ ba, the mapping torus of the punctured torus is a hyperbolic
+//! 3-manifold, the complement of the figure eight knot in that example,
+//! and the fundamental group of the fiber is a doubly degenerate group: its
+//! limit set is the whole sphere, although it is the limit of
+//! quasifuchsian groups whose limit sets are curves.  The automorphism
+//! maps this group to itself, so its traces are a fixed point of the
+//! action, which is found here by Newton's method.  Near the fixed point,
+//! the images of the group under the automorphism are pushed further
+//! along the invariant laminations, so iterating the action itself does
+//! not converge to it.
+
+use crate::nielsen::NielsenMove;
+use crate::Cpx;
+use nalgebra::{Matrix3, Vector3};
+
+/// The monodromy of the figure eight knot complement, a ↦ ab followed by
+/// b ↦ ba, which acts on the homology of the torus by (2 1; 1 1).
+pub const FIGURE_EIGHT: [NielsenMove; 2] = [NielsenMove::MultiplyA, NielsenMove::MultiplyB];
+
+/// A starting point from which `fixed_traces` finds the traces of the
+/// fiber group of the figure eight knot complement, (3 + √3 i) / 2,
+/// (3 - √3 i) / 2, and (3 + √3 i) / 2.
+pub const FIGURE_EIGHT_START: [Cpx; 3] =
+    [Cpx::new(1.5, 0.8), Cpx::new(1.5, -0.8), Cpx::new(1.5, 0.8)];
+
+/// Returns the traces after applying the moves in order.
+pub fn apply_moves(moves: &[NielsenMove], traces: [Cpx; 3]) -> [Cpx; 3] {
+    moves.iter().fold(traces, |t, m| m.apply_traces(t))
+}
+
+/// Takes `steps` steps of Newton's method, from `start`, towards traces
+/// that are fixed by the moves and satisfy ta^2 + tb^2 + tab^2 = ta tb tab,
+/// so that the commutator is parabolic.  The moves preserve that
+/// equation, so a fixed point of the action on traces is not isolated,
+/// and the equation for tab being fixed is replaced with it.  About 10
+/// steps are enough from a good starting point, and the traces are
+/// returned as they are if the method breaks down.
+pub fn fixed_traces(moves: &[NielsenMove], start: [Cpx; 3], steps: usize) -> [Cpx; 3] {
+    let f = |t: [Cpx; 3]| {
+        let image = apply_moves(moves, t);
+        Vector3::new(
+            image[0] - t[0],
+            image[1] - t[1],
+            t[0] * t[0] + t[1] * t[1] + t[2] * t[2] - t[0] * t[1] * t[2],
+        )
+    };
+    let mut t = start;
+    for _ in 0..steps {
+        let value = f(t);
+        // The action is polynomial, so the columns of the Jacobian are
+        // complex derivatives, found by differences.
+        let eps = 1e-7;
+        let mut jacobian = Matrix3::zeros();
+        for k in 0..3 {
+            let mut shifted = t;
+            shifted[k] += eps;
+            jacobian.set_column(k, &((f(shifted) - value) / Cpx::from(eps)));
+        }
+        let Some(step) = jacobian.lu().solve(&value) else {
+            break;
+        };
+        let next = [t[0] - step[0], t[1] - step[1], t[2] - step[2]];
+        if !next.iter().all(|z| z.is_finite()) {
+            break;
+        }
+        t = next;
+    }
+    t
+}
@@ -0,0 +1,110 @@
+//! Depth-first exploration of a group, for the explorers that keep a stack
+//! of words instead of a queue of circles, such as `raster` and `stream`.
+//! The words are visited in the order in which their circles occur along
+//! the limit set, and the same words are skipped as by `CircleQueue`.
+
+use crate::queue::{max_runs, Relation};
+use crate::{check_generators, Circle, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+
+/// A word that has not been expanded yet.
+pub(crate) struct Branch {
+    /// The product of all letters but the last.
+    matrix: Matrix2<Cpx>,
+    last: u8,
+    /// The last letter is the letter before it plus `step`, modulo 4, where
+    /// `step` is 3, 4, or 5.  It is 0 for words with one letter.
+    step: u8,
+    /// The number of letters at the end of the word that are each reached
+    /// from the one before by `step`, counting the first of them.
+    run: usize,
+    /// The number of letters in the word.
+    pub(crate) length: usize,
+}
+
+/// Explores a group depth first, stopping at circles with radius less than
+/// `min_radius` in `visit`.
+pub(crate) struct DepthFirst<'a> {
+    gens: &'a [Generator; 4],
+    min_radius: f64,
+    /// The number of times in a row that each letter may occur, as in
+    /// `max_runs`.
+    max_run: Vec<usize>,
+    /// The number of letters in a row that may follow the commutator, as
+    /// in `Relation`.
+    max_cycle: [usize; 2],
+}
+
+impl<'a> DepthFirst<'a> {
+    pub(crate) fn new(gens: &'a [Generator; 4], min_radius: f64) -> Result<Self, Error> {
+        check_generators(gens)?;
+        Ok(DepthFirst {
+            gens,
+            min_radius,
+            max_run: max_runs(gens),
+            max_cycle: Relation::find(gens).map_or([usize::MAX; 2], |r| r.max_run),
+        })
+    }
+    /// Returns the words with one letter, in reverse order, so that they
+    /// are popped from a stack in order.
+    pub(crate) fn roots(&self) -> Vec<Branch> {
+        (0..4)
+            .rev()
+            .filter(|&i| self.max_run[i as usize] > 0)
+            .map(|i| Branch {
+                matrix: Matrix2::identity(),
+                last: i,
+                step: 0,
+                run: 1,
+                length: 1,
+            })
+            .collect()
+    }
+    /// Returns the circle of the branch, which contains the limit points of
+    /// the words that start with it.
+    pub(crate) fn circle(&self, branch: &Branch) -> Circle {
+        branch.matrix * self.gens[branch.last as usize].circle
+    }
+    /// Pushes the children of the branch onto the stack in reverse, so that
+    /// they are popped in order.
+    pub(crate) fn push_children(&self, branch: &Branch, stack: &mut Vec<Branch>) {
+        let matrix = branch.matrix * self.gens[branch.last as usize].matrix;
+        for i in (3..6).rev() {
+            let letter = (branch.last + i) % 4;
+            let run = if i == branch.step { branch.run + 1 } else { 2 };
+            let max = match i {
+                4 => self.max_run[letter as usize],
+                5 => self.max_cycle[0],
+                _ => self.max_cycle[1],
+            };
+            if run <= max {
+                stack.push(Branch {
+                    matrix,
+                    last: letter,
+                    step: i,
+                    run,
+                    length: branch.length + 1,
+                });
+            }
+        }
+    }
+    /// Returns the center of the circle of the branch if its radius is less
+    /// than `min_radius`.  Otherwise, pushes its children onto the stack, as
+    /// in `push_children`, and returns `None`.
+    pub(crate) fn visit(
+        &self,
+        branch: &Branch,
+        stack: &mut Vec<Branch>,
+    ) -> Result<Option<Cpx>, Error> {
+        let circle = self.circle(branch);
+        let ri = circle.radius_inv();
+        if ri.is_nan() {
+            return Err(Error::NumericalBreakdown);
+        }
+        if ri * self.min_radius > 1.0 {
+            return Ok(Some(circle.center_point().affine()));
+        }
+        self.push_children(branch, stack);
+        Ok(None)
+    }
+}
@@ -0,0 +1,182 @@
+//! Detection of numerical trouble during exploration.  When a picture
+//! looks wrong, the cause is usually a product of generators that has
+//! overflowed, or a circle that has become NaN, and this records where in
+//! the group that first happened.
+
+use crate::{Circle, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+use std::fmt;
+
+/// At most this many problems are recorded, although all are counted.
+const MAX_RECORDED: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// The product of the letters of the word has an entry that is NaN or
+    /// infinite.
+    Matrix,
+    /// The circle of the word has an entry that is NaN or infinite.
+    Circle,
+    /// The circle of the word is finite, but has drifted away from being
+    /// Hermitian with determinant -1, as checked by `Circle::is_valid`.
+    InvalidCircle,
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    /// The word, as a sequence of generator indices in the order a, b,
+    /// a^{-1}, b^{-1}, or as in `CircleQueue::with_generators` for groups
+    /// with more generators.
+    pub word: Vec<u8>,
+}
+
+impl Problem {
+    pub fn depth(&self) -> usize {
+        self.word.len()
+    }
+}
+
+/// A record of the problems found while exploring a group.  Enable it with
+/// `CircleQueue::enable_diagnostics`, or use `diagnose`.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    /// The number of matrices and circles checked.
+    pub matrices_checked: usize,
+    pub circles_checked: usize,
+    /// The number of non-finite matrices and circles found.
+    pub bad_matrices: usize,
+    pub bad_circles: usize,
+    /// The number of finite circles that are not valid.
+    pub invalid_circles: usize,
+    /// The first problems found, in the order in which they were found.
+    pub problems: Vec<Problem>,
+    /// The error that stopped the exploration, if any.
+    pub error: Option<Error>,
+    /// The number of generators, not counting inverses, which determines
+    /// how the words are written.
+    pub rank: usize,
+}
+
+fn is_finite(m: &Matrix2<Cpx>) -> bool {
+    m.iter().all(|z| z.is_finite())
+}
+
+/// Words longer than this are shortened in the summary.
+const MAX_SHOWN: usize = 32;
+
+/// Writes the word with a, b, A, B standing for a, b, a^{-1}, b^{-1}, and
+/// with c, C and so on for any further generators.
+fn write_word(f: &mut fmt::Formatter, word: &[u8], rank: usize) -> fmt::Result {
+    let rank = rank.max(1) as u8;
+    for &l in word.iter().take(MAX_SHOWN) {
+        let c = if l < rank { b'a' + l } else { b'A' + l - rank };
+        write!(f, "{}", c as char)?;
+    }
+    if word.len() > MAX_SHOWN {
+        write!(f, "...")?;
+    }
+    Ok(())
+}
+
+impl Diagnostics {
+    /// Returns whether no problems were found.
+    pub fn is_clean(&self) -> bool {
+        self.bad_matrices == 0
+            && self.bad_circles == 0
+            && self.invalid_circles == 0
+            && self.error.is_none()
+    }
+    fn record(&mut self, kind: ProblemKind, word: impl FnOnce() -> Vec<u8>) {
+        if self.problems.len() < MAX_RECORDED {
+            self.problems.push(Problem { kind, word: word() });
+        }
+    }
+    pub(crate) fn check_matrix(&mut self, m: &Matrix2<Cpx>, word: impl FnOnce() -> Vec<u8>) {
+        self.matrices_checked += 1;
+        if !is_finite(m) {
+            self.bad_matrices += 1;
+            self.record(ProblemKind::Matrix, word);
+        }
+    }
+    pub(crate) fn check_circle(&mut self, c: &Circle, word: impl FnOnce() -> Vec<u8>) {
+        self.circles_checked += 1;
+        if !is_finite(&c.0) {
+            self.bad_circles += 1;
+            self.record(ProblemKind::Circle, word);
+        } else if !c.is_valid() {
+            self.invalid_circles += 1;
+            self.record(ProblemKind::InvalidCircle, word);
+        }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "checked {} matrices and {} circles",
+            self.matrices_checked, self.circles_checked
+        )?;
+        if self.bad_matrices == 0 && self.bad_circles == 0 {
+            writeln!(f, "no NaN or infinite entries found")?;
+        } else {
+            writeln!(
+                f,
+                "{} matrices and {} circles with NaN or infinite entries",
+                self.bad_matrices, self.bad_circles
+            )?;
+        }
+        if self.invalid_circles > 0 {
+            writeln!(
+                f,
+                "{} circles no longer Hermitian with determinant -1",
+                self.invalid_circles
+            )?;
+        }
+        if !self.problems.is_empty() {
+            if let Some(p) = self.problems.iter().min_by_key(|p| p.depth()) {
+                writeln!(f, "shallowest recorded problem at depth {}", p.depth())?;
+            }
+            for p in &self.problems {
+                let kind = match p.kind {
+                    ProblemKind::Matrix => "matrix",
+                    ProblemKind::Circle => "circle",
+                    ProblemKind::InvalidCircle => "invalid circle",
+                };
+                write!(f, "  {} of ", kind)?;
+                write_word(f, &p.word, self.rank)?;
+                writeln!(f, " (depth {})", p.depth())?;
+            }
+        }
+        if let Some(e) = self.error {
+            writeln!(f, "exploration stopped: {}", e)?;
+        }
+        Ok(())
+    }
+}
+
+/// Explores the group as `generate_points` does, with diagnostics enabled,
+/// and returns what was found.  Exploration stops early if it fails.
+pub fn diagnose(gens: [Generator; 4], num_points: usize) -> Diagnostics {
+    let mut queue = match crate::CircleQueue::new(gens) {
+        Ok(queue) => queue,
+        Err(e) => {
+            return Diagnostics {
+                error: Some(e),
+                ..Default::default()
+            }
+        }
+    };
+    queue.enable_diagnostics();
+    let mut error = None;
+    while queue.len() < num_points {
+        if let Err(e) = queue.advance() {
+            error = Some(e);
+            break;
+        }
+    }
+    let mut diagnostics = queue.diagnostics().cloned().unwrap_or_default();
+    diagnostics.error = error;
+    diagnostics
+}
@@ -0,0 +1,124 @@
+//! Estimation of the Hausdorff dimension of the limit set with McMullen's
+//! eigenvalue algorithm ("Hausdorff dimension and conformal dynamics III:
+//! Computation of dimension", 1998).
+//!
+//! The limit set is covered by the circles of the words of a fixed length
+//! n.  The circle of a word l v is the image under l of the circles of the
+//! words v x, so the transfer operator of the group is approximated by the
+//! matrix T(s) whose (l v, v x) entry is |l'(z)|^s, where z is the center
+//! of the circle of v x.  The dimension is the value of s for which the
+//! largest eigenvalue of T(s) is 1.  This converges much faster than
+//! counting circles, as in `score::discreteness`, but it assumes that the
+//! group is free on the generators, so it does not apply to groups with
+//! elliptic generators.
+
+use crate::elements::elements;
+use crate::{circles_for_words, Cpx, Generator};
+use nalgebra::Matrix2;
+use std::collections::HashMap;
+
+/// Returns the absolute value of the derivative of the Möbius
+/// transformation with matrix `m` (of determinant 1) at `z`.
+pub fn derivative(m: &Matrix2<Cpx>, z: Cpx) -> f64 {
+    1.0 / (m[(1, 0)] * z + m[(1, 1)]).norm_sqr()
+}
+
+/// Returns the reduced words of length `n`, in lexicographic order.
+fn reduced_words(gens: &[Generator; 4], n: usize) -> Vec<Vec<u8>> {
+    elements(gens)
+        .map(|(word, _)| word)
+        .skip_while(|word| word.len() < n)
+        .take_while(|word| word.len() == n)
+        .collect()
+}
+
+/// The sparse matrix of logarithms of derivatives.  Row i has an entry
+/// for each of the three words that its circle is divided into.
+struct Transfer {
+    /// `(column, log |l'(z)|)` for each nonzero entry.
+    rows: Vec<[(usize, f64); 3]>,
+}
+
+impl Transfer {
+    fn new(gens: &[Generator; 4], n: usize) -> Option<Self> {
+        let words = reduced_words(gens, n);
+        let centers: Vec<Cpx> = circles_for_words(gens, &words)
+            .iter()
+            .map(|c| c.center())
+            .collect();
+        if !centers.iter().all(|z| z.is_finite()) {
+            return None;
+        }
+        let index: HashMap<&[u8], usize> =
+            words.iter().enumerate().map(|(i, w)| (&w[..], i)).collect();
+        let rows = words
+            .iter()
+            .map(|w| {
+                let (&first, rest) = w.split_first().unwrap();
+                let last = *w.last().unwrap();
+                let mut row = [(0, 0.0); 3];
+                let next = (0..4u8).filter(|&x| x != (last + 2) % 4);
+                for (entry, x) in row.iter_mut().zip(next) {
+                    let mut v = rest.to_vec();
+                    v.push(x);
+                    let j = index[&v[..]];
+                    let d = derivative(&gens[first as usize].matrix, centers[j]);
+                    *entry = (j, d.ln());
+                }
+                row
+            })
+            .collect();
+        Some(Transfer { rows })
+    }
+    /// Returns the largest eigenvalue of T(s), computed by power iteration
+    /// starting from `v`, which is replaced by the eigenvector.
+    fn spectral_radius(&self, s: f64, v: &mut Vec<f64>) -> f64 {
+        const MAX_ITERS: usize = 10000;
+        let weights: Vec<[(usize, f64); 3]> = self
+            .rows
+            .iter()
+            .map(|r| r.map(|(j, l)| (j, (s * l).exp())))
+            .collect();
+        let mut lambda = 0.0;
+        for _ in 0..MAX_ITERS {
+            let w: Vec<f64> = weights
+                .iter()
+                .map(|r| r.iter().map(|&(j, t)| t * v[j]).sum())
+                .collect();
+            let norm = w.iter().sum::<f64>() / v.iter().sum::<f64>();
+            let done = (norm - lambda).abs() <= 1e-14 * norm;
+            lambda = norm;
+            let total: f64 = w.iter().sum();
+            *v = w.into_iter().map(|x| x / total).collect();
+            if done {
+                break;
+            }
+        }
+        lambda
+    }
+}
+
+/// Estimates the Hausdorff dimension of the limit set, using the circles
+/// of the words of length `n`.  The matrix has 4·3^(n-1) rows, so `n`
+/// between 6 and 10 is reasonable.  The error decreases geometrically in
+/// `n` for groups without parabolic elements.  Returns `None` if the
+/// circles are not finite.
+pub fn hausdorff_dimension(gens: &[Generator; 4], n: usize) -> Option<f64> {
+    let transfer = Transfer::new(gens, n.max(1))?;
+    let mut v = vec![1.0; transfer.rows.len()];
+    // The largest eigenvalue is decreasing in s, and the limit set lies in
+    // the sphere, so its dimension is between 0 and 2.
+    let (mut lo, mut hi) = (0.0, 2.0);
+    if transfer.spectral_radius(hi, &mut v) >= 1.0 {
+        return Some(hi);
+    }
+    while hi - lo > 1e-12 {
+        let mid = 0.5 * (lo + hi);
+        if transfer.spectral_radius(mid, &mut v) > 1.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(0.5 * (lo + hi))
+}
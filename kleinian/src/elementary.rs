@@ -0,0 +1,109 @@
+//! Detection of elementary groups, whose limit sets have at most two
+//! points.  Exploring such a group never gets anywhere: the circles either
+//! stop shrinking or pile up on a point or two, and the picture is a few
+//! dots made of rounding error.  Frontends can check for this first and
+//! say so instead.
+
+use crate::algebra::inv;
+use crate::{elliptic_order, Cpx, Generator};
+use nalgebra::Matrix2;
+use std::fmt;
+
+/// Quantities that should be 0 are treated as 0 when they are smaller than
+/// this, relative to the size of the matrices.
+const TOLERANCE: f64 = 1e-9;
+
+/// Finite groups with more elements than this, as transformations, are
+/// not looked for.  The other finite groups are cyclic or dihedral, and
+/// are found when their generators have at most this order.
+const MAX_FINITE_ORDER: usize = 2000;
+
+/// The ways in which a group generated by a and b can be elementary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Elementary {
+    /// The group is finite, with the given number of transformations.  Its
+    /// limit set is empty.
+    Finite(usize),
+    /// a and b commute, so the group has at most two limit points, which
+    /// are the fixed points of a and b.
+    Abelian,
+    /// a and b share a fixed point, which is a limit point, and the limit
+    /// set has at most one other point.
+    CommonFixedPoint,
+}
+
+impl Elementary {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Elementary::Finite(_) => "finite",
+            Elementary::Abelian => "abelian",
+            Elementary::CommonFixedPoint => "common fixed point",
+        }
+    }
+}
+
+impl fmt::Display for Elementary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Elementary::Finite(n) => write!(f, "finite, of order {}", n),
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
+/// Returns the largest absolute value of the entries.
+fn size(m: &Matrix2<Cpx>) -> f64 {
+    m.iter().map(|z| z.norm()).fold(0.0, f64::max)
+}
+
+/// Returns whether the matrices give the same transformation.
+fn same_transformation(m: &Matrix2<Cpx>, n: &Matrix2<Cpx>) -> bool {
+    let tol = TOLERANCE * (1.0 + size(m));
+    size(&(m - n)) < tol || size(&(m + n)) < tol
+}
+
+/// Returns the number of transformations in the group generated by the
+/// matrices, if it is at most `MAX_FINITE_ORDER`.
+fn finite_order(gens: &[Matrix2<Cpx>]) -> Option<usize> {
+    let mut found = vec![Matrix2::identity()];
+    let mut next = 0;
+    while next < found.len() {
+        let m = found[next];
+        next += 1;
+        for g in gens {
+            let p = m * g;
+            if !found.iter().any(|q| same_transformation(&p, q)) {
+                if found.len() == MAX_FINITE_ORDER {
+                    return None;
+                }
+                found.push(p);
+            }
+        }
+    }
+    Some(found.len())
+}
+
+/// Returns how the group generated by a and b, the first two generators,
+/// is elementary, or `None` if it is not.  A group that is finite is
+/// reported as such even if it is also abelian.  Groups that are
+/// elementary only because they preserve a pair of points, such as the
+/// infinite dihedral groups, are not detected.
+pub fn elementary(gens: &[Generator; 4]) -> Option<Elementary> {
+    let (a, b) = (gens[0].matrix, gens[1].matrix);
+    if elliptic_order(&a).is_some() && elliptic_order(&b).is_some() {
+        if let Some(n) = finite_order(&[a, b]) {
+            return Some(Elementary::Finite(n));
+        }
+    }
+    let scale = 1.0 + size(&a) * size(&b);
+    if size(&(a * b - b * a)) < TOLERANCE * scale {
+        return Some(Elementary::Abelian);
+    }
+    // The trace of the commutator is 2 exactly when a and b have a common
+    // fixed point.
+    let t = (a * b * inv(&a) * inv(&b)).trace();
+    if (t - 2.0).norm() < TOLERANCE * scale * scale {
+        return Some(Elementary::CommonFixedPoint);
+    }
+    None
+}
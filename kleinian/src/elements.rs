@@ -0,0 +1,59 @@
+//! Enumeration of the elements of a group by their words, for analysis
+//! that does not need circles, such as statistics of traces or counting
+//! the points of an orbit in a ball.
+
+use crate::{Cpx, Generator};
+use nalgebra::Matrix2;
+use std::collections::VecDeque;
+
+/// An iterator over the reduced words in the generators, and their
+/// matrices, returned by `elements`.
+pub struct Elements {
+    gens: Vec<Matrix2<Cpx>>,
+    /// The words that have been found but not returned, in order.
+    pending: VecDeque<(Vec<u8>, Matrix2<Cpx>)>,
+}
+
+/// Returns the reduced words in the generators, which are listed as in
+/// `CircleQueue::with_generators`, along with the product of the matrices
+/// of their letters.  The words are returned in shortlex order: shorter
+/// words first, and words of the same length in lexicographic order of the
+/// generator indices, starting with the empty word and the identity.  In a
+/// free group every element is returned once; otherwise, elements with
+/// several reduced words are returned once for each.  The number of words
+/// of each length grows exponentially, as does the memory used to list
+/// them, so the iterator should be stopped at a moderate length.
+pub fn elements(gens: &[Generator]) -> Elements {
+    Elements {
+        gens: gens.iter().map(|g| g.matrix).collect(),
+        pending: VecDeque::from([(Vec::new(), Matrix2::identity())]),
+    }
+}
+
+/// Returns the inverse of the word, for `rank` generators followed by
+/// their inverses, as in `elements`.
+pub fn inverse_word(word: &[u8], rank: usize) -> Vec<u8> {
+    let n = 2 * rank;
+    word.iter()
+        .rev()
+        .map(|&l| ((l as usize + rank) % n) as u8)
+        .collect()
+}
+
+impl Iterator for Elements {
+    type Item = (Vec<u8>, Matrix2<Cpx>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let (word, m) = self.pending.pop_front()?;
+        let n = self.gens.len() as u8;
+        let rank = n / 2;
+        for (l, g) in (0..n).zip(&self.gens) {
+            if word.last() == Some(&((l + rank) % n)) {
+                continue;
+            }
+            let mut w = word.clone();
+            w.push(l);
+            self.pending.push_back((w, m * g));
+        }
+        Some((word, m))
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt;
+
+/// The ways in which computing a limit set can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The generators or their circles are not finite, which usually means
+    /// that the parameters are at a singularity of the recipe.
+    DegenerateGenerators,
+    /// A circle of undefined size appeared while exploring the group, which
+    /// usually means that the group is not discrete.
+    NumericalBreakdown,
+    /// There are no finite points to fit into a window.
+    NoFinitePoints,
+    /// The circles of groups being combined overlap, so the combined group
+    /// might not be discrete.
+    OverlappingCircles,
+    /// The description of a subgroup does not give one of finite index, or
+    /// the group is not free on its generators.
+    InvalidSubgroup,
+    /// The queue ran out of circles to expand, because the children of all
+    /// of them were skipped, which happens when the group is finite.
+    EmptyQueue,
+}
+
+impl Error {
+    /// Returns a short machine-readable name for the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::DegenerateGenerators => "degenerate-generators",
+            Error::NumericalBreakdown => "numerical-breakdown",
+            Error::NoFinitePoints => "no-finite-points",
+            Error::OverlappingCircles => "overlapping-circles",
+            Error::InvalidSubgroup => "invalid-subgroup",
+            Error::EmptyQueue => "empty-queue",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Error::DegenerateGenerators => "the generators are degenerate",
+            Error::NumericalBreakdown => "a degenerate circle was produced during exploration",
+            Error::NoFinitePoints => "there are no finite points",
+            Error::OverlappingCircles => "the circles of the groups being combined overlap",
+            Error::InvalidSubgroup => "the subgroup does not have finite index",
+            Error::EmptyQueue => "there are no circles left to expand",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,108 @@
+//! Inline display of limit sets in Jupyter notebooks that use the evcxr
+//! Rust kernel.  The kernel shows any value with an `evcxr_display`
+//! method, so the last expression of a cell can be a `Picture`:
+//!
+//! ```ignore
+//! :dep kleinian = { path = "...", features = ["evcxr"] }
+//! let pts = kleinian::generate_labelled_points(gens, 100000)?;
+//! kleinian::evcxr::Picture::new(pts, 600, 600, Default::default())?
+//! ```
+
+use crate::render::{self, Style};
+use crate::window::{self, CoordTransform};
+use crate::{Cpx, Error, LimitPoint};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+fn show(mime: &str, content: &str) {
+    println!(
+        "EVCXR_BEGIN_CONTENT {}\n{}\nEVCXR_END_CONTENT",
+        mime, content
+    );
+}
+
+/// Points of a limit set, along with how to draw them.  Displays as a PNG
+/// image.
+#[derive(Clone)]
+pub struct Picture {
+    pub points: Vec<LimitPoint>,
+    pub transform: CoordTransform,
+    pub width: usize,
+    pub height: usize,
+    pub style: Style,
+}
+
+impl Picture {
+    /// Returns a `width` by `height` picture of the points, with the view
+    /// chosen to fit all of them.
+    pub fn new(
+        points: Vec<LimitPoint>,
+        width: usize,
+        height: usize,
+        style: Style,
+    ) -> Result<Self, Error> {
+        let zs: Vec<Cpx> = points.iter().map(|p| p.z).collect();
+        let transform = window::window_transform(&zs, width, height)?;
+        Ok(Picture {
+            points,
+            transform,
+            width,
+            height,
+            style,
+        })
+    }
+    /// Shows `scale` pixels per unit length, centered on `center`.
+    pub fn centered(mut self, center: Cpx, scale: f64) -> Self {
+        self.transform = CoordTransform::centered(center, scale, self.width, self.height);
+        self
+    }
+    pub fn to_rgba(&self) -> Vec<u8> {
+        render::render_rgba(
+            &self.points,
+            &self.transform,
+            self.width,
+            self.height,
+            &self.style,
+        )
+    }
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba())?;
+        writer.finish()?;
+        Ok(out)
+    }
+    pub fn to_svg(&self) -> String {
+        crate::svg::to_svg(
+            &self.points,
+            &self.transform,
+            self.width,
+            self.height,
+            &self.style,
+        )
+    }
+    /// Returns a value that displays as an SVG image instead.  Density
+    /// mode is not supported in SVG output.
+    pub fn svg(&self) -> Svg {
+        Svg(self.to_svg())
+    }
+    pub fn evcxr_display(&self) {
+        match self.to_png() {
+            Ok(png) => show("image/png", &STANDARD.encode(png)),
+            Err(e) => show("text/plain", &e.to_string()),
+        }
+    }
+}
+
+/// An SVG document, which displays as an image.
+#[derive(Clone, Debug)]
+pub struct Svg(pub String);
+
+impl Svg {
+    pub fn evcxr_display(&self) {
+        show("image/svg+xml", &self.0);
+    }
+}
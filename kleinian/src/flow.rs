@@ -0,0 +1,88 @@
+//! Streamlines of the flow of a Möbius transformation.  A transformation
+//! lies on a one-parameter group of transformations with the same fixed
+//! points, and the orbits of points under this group are the curves along
+//! which the transformation moves the plane.  For a loxodromic
+//! transformation they are the double spirals from the repelling fixed
+//! point to the attracting one.
+
+use crate::algebra::{exp, fixed_points, inv, log};
+use crate::cyclic::apply_all;
+use crate::Cpx;
+use nalgebra::Matrix2;
+
+/// Returns a matrix for a transformation that takes the fixed points of
+/// `m` to 0 and infinity, so that `m` becomes a multiplication, along with
+/// `false`.  If `m` is parabolic, the matrix instead takes its fixed point
+/// to infinity, so that `m` becomes a translation, and `true` is returned.
+fn normalizer(m: &Matrix2<Cpx>) -> (Matrix2<Cpx>, bool) {
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    let [p, q] = fixed_points(m);
+    if !p.is_finite() && !q.is_finite() {
+        // `fixed_points` returns infinity twice for translations.
+        (Matrix2::identity(), true)
+    } else if !p.is_finite() {
+        (Matrix2::new(one, -q, zero, one), false)
+    } else if !q.is_finite() {
+        (Matrix2::new(one, -p, zero, one), false)
+    } else if (p - q).norm() < 1e-9 * (1.0 + p.norm()) {
+        (Matrix2::new(zero, one, one, -p), true)
+    } else {
+        (Matrix2::new(one, -p, one, -q), false)
+    }
+}
+
+/// Returns `num_lines` streamlines of the flow of the transformation with
+/// matrix `m`, of determinant 1, as polylines.  Each line is the orbit of a
+/// point z under exp(t log `m`) for `samples` evenly spaced values of t
+/// from -`powers` to `powers`, so the transformation moves each point of a
+/// line (`samples` - 1) / (2 `powers`) points further along it.  The lines
+/// are spread evenly around the fixed points: in a coordinate in which
+/// `m` fixes 0 and infinity, the points z lie on the unit circle.  Lines of a
+/// hyperbolic transformation are arcs between the fixed points, and those
+/// of a loxodromic transformation spiral around them.  All of the lines of
+/// an elliptic transformation are the same circle.  If `m` is parabolic,
+/// then in a coordinate in which it is a translation, the points z lie
+/// evenly spaced on a line perpendicular to it.
+pub fn streamlines(
+    m: &Matrix2<Cpx>,
+    num_lines: usize,
+    powers: f64,
+    samples: usize,
+) -> Vec<Vec<Cpx>> {
+    let (c, parabolic) = normalizer(m);
+    let ci = inv(&c);
+    let mut seeds: Vec<Cpx> = if !parabolic {
+        // The half step keeps the seeds away from 1, which is infinity in
+        // the original coordinate when neither fixed point is.
+        (0..num_lines)
+            .map(|i| {
+                let theta = std::f64::consts::TAU * (i as f64 + 0.5) / num_lines as f64;
+                Cpx::from_polar(1.0, theta)
+            })
+            .collect()
+    } else {
+        let normal = c * m * ci;
+        let shift = normal[(0, 1)] / normal[(1, 1)];
+        let center = 0.5 * num_lines as f64;
+        (0..num_lines)
+            .map(|i| Cpx::i() * shift * (i as f64 + 0.5 - center))
+            .collect()
+    };
+    apply_all(&ci, &mut seeds);
+    let x = log(m);
+    let mut lines = vec![Vec::with_capacity(samples); num_lines];
+    for j in 0..samples {
+        let t = if samples > 1 {
+            powers * (2.0 * j as f64 / (samples - 1) as f64 - 1.0)
+        } else {
+            0.0
+        };
+        let mut pts = seeds.clone();
+        apply_all(&exp(&(x * Cpx::from(t))), &mut pts);
+        for (line, z) in lines.iter_mut().zip(pts) {
+            line.push(z);
+        }
+    }
+    lines
+}
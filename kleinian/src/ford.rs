@@ -0,0 +1,146 @@
+//! Numerical approximation of the Ford fundamental domain, which is the
+//! part of upper half-space lying above the isometric hemispheres of all
+//! of the group elements.  Only the elements whose words are no longer
+//! than a given length are used, so the approximation may have faces that
+//! would be cut off by longer words.
+//!
+//! The isometric circle of the transformation with matrix (a b; c d) is
+//! the circle |cz + d| = 1 on which the transformation preserves lengths.
+//! The transformation maps the isometric hemisphere of g to the isometric
+//! hemisphere of g^{-1}, which is how the faces of the domain are paired.
+
+use crate::elements::{elements, inverse_word};
+use crate::gltf::{self, MeshOptions};
+use crate::h3::{Hemisphere, Point};
+use crate::{Circle, Cpx, Generator};
+use nalgebra::Matrix2;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::{self, Write};
+
+/// Returns the isometric hemisphere of the transformation, or `None` if
+/// the transformation fixes infinity.
+pub fn isometric_hemisphere(m: &Matrix2<Cpx>) -> Option<Hemisphere> {
+    let c = m[(1, 0)];
+    let radius = 1.0 / c.norm();
+    (radius.is_finite() && c.is_finite()).then(|| Hemisphere {
+        center: -m[(1, 1)] / c,
+        radius,
+    })
+}
+
+/// A face of the Ford domain.
+#[derive(Clone, Debug)]
+pub struct Face {
+    /// The word of the element whose isometric hemisphere contains the
+    /// face, as a sequence of generator indices.
+    pub word: Vec<u8>,
+    pub matrix: Matrix2<Cpx>,
+    pub hemisphere: Hemisphere,
+    /// The index of the face that the element maps this face to, if that
+    /// face was found.
+    pub paired: Option<usize>,
+}
+
+pub struct FordDomain {
+    pub faces: Vec<Face>,
+}
+
+/// Returns whether `p` lies strictly inside the hemisphere.
+fn inside(h: &Hemisphere, p: &Point) -> bool {
+    (p.z - h.center).norm_sqr() + p.t * p.t < h.radius * h.radius * (1.0 - 1e-9)
+}
+
+/// Points spread over the hemisphere, used to decide whether any of it is
+/// visible from above.
+fn samples(h: Hemisphere) -> impl Iterator<Item = Point> {
+    const RINGS: usize = 6;
+    const SEGMENTS: usize = 24;
+    let ring = (1..RINGS).flat_map(move |i| {
+        let phi = 0.5 * PI * i as f64 / RINGS as f64;
+        (0..SEGMENTS).map(move |j| {
+            let theta = 2.0 * PI * j as f64 / SEGMENTS as f64;
+            let dz = Cpx::from_polar(phi.cos(), theta);
+            Point::new(h.center + h.radius * dz, h.radius * phi.sin())
+        })
+    });
+    std::iter::once(h.top()).chain(ring)
+}
+
+impl FordDomain {
+    /// Approximates the Ford domain using the elements whose words have
+    /// length at most `max_length`.  The number of such elements grows
+    /// like 3^`max_length`, and the time like its square.
+    pub fn new(gens: &[Generator; 4], max_length: usize) -> Self {
+        // The identity, which comes first, has no isometric hemisphere.
+        let mut candidates: Vec<Face> = elements(gens)
+            .skip(1)
+            .take_while(|(word, _)| word.len() <= max_length)
+            .filter_map(|(word, matrix)| {
+                Some(Face {
+                    hemisphere: isometric_hemisphere(&matrix)?,
+                    word,
+                    matrix,
+                    paired: None,
+                })
+            })
+            .collect();
+        candidates
+            .sort_unstable_by(|a, b| a.hemisphere.center.re.total_cmp(&b.hemisphere.center.re));
+        let max_radius = candidates
+            .iter()
+            .map(|f| f.hemisphere.radius)
+            .fold(0.0, f64::max);
+        let visible: Vec<bool> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let h = &f.hemisphere;
+                // Only hemispheres whose centers are this close in the real
+                // direction can overlap this one.
+                let reach = h.radius + max_radius;
+                let lo =
+                    candidates.partition_point(|g| g.hemisphere.center.re < h.center.re - reach);
+                let hi =
+                    candidates.partition_point(|g| g.hemisphere.center.re <= h.center.re + reach);
+                let others: Vec<&Hemisphere> = (lo..hi)
+                    .filter(|&j| j != i)
+                    .map(|j| &candidates[j].hemisphere)
+                    .filter(|g| (g.center - h.center).norm() < g.radius + h.radius)
+                    .collect();
+                samples(*h).any(|p| !others.iter().any(|g| inside(g, &p)))
+            })
+            .collect();
+        let mut faces: Vec<Face> = candidates
+            .into_iter()
+            .zip(visible)
+            .filter_map(|(f, v)| v.then_some(f))
+            .collect();
+        let index: HashMap<Vec<u8>, usize> = faces
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.word.clone(), i))
+            .collect();
+        for f in &mut faces {
+            f.paired = index.get(&inverse_word(&f.word, 2)).copied();
+        }
+        FordDomain { faces }
+    }
+    /// Returns the isometric circles of the faces.
+    pub fn circles(&self) -> Vec<Circle> {
+        self.faces
+            .iter()
+            .map(|f| Circle::new(f.hemisphere.center, f.hemisphere.radius))
+            .collect()
+    }
+    /// Returns whether `p` lies on or above all of the faces.
+    pub fn contains(&self, p: &Point) -> bool {
+        !self.faces.iter().any(|f| inside(&f.hemisphere, p))
+    }
+    /// Writes the faces as a binary glTF file, as in `gltf::write_glb`, but
+    /// with the parts of each hemisphere that lie under other faces left
+    /// out.
+    pub fn write_glb<W: Write>(&self, out: &mut W, opts: &MeshOptions) -> io::Result<()> {
+        gltf::write_glb_filtered(out, &self.circles(), opts, |p| self.contains(p))
+    }
+}
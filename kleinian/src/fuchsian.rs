@@ -0,0 +1,182 @@
+//! Fuchsian groups, which preserve a circle.  A group generated by a and b
+//! preserves a circle when it is conjugate to a group of real matrices,
+//! which is usually the case when tr a, tr b, and tr ab are real, and then
+//! the limit set lies on the circle.  Conjugating the circle to the unit
+//! circle puts the group in the form of the Poincaré disk model, in which
+//! each matrix is (α β; β̄ ᾱ) up to sign, so the hyperbolic plane that the
+//! group acts on is the unit disk.
+
+use crate::algebra::{fixed_points, inv};
+use crate::conjugate::conjugate;
+use crate::{cayley, Circle, Cpx, Error, Generator};
+use nalgebra::{Matrix2, SMatrix};
+
+/// The circle is accepted when the equations for it hold up to this,
+/// relative to the size of the matrices.
+const TOLERANCE: f64 = 1e-9;
+
+/// Returns a basis over the reals of the 2×2 Hermitian matrices.
+fn hermitian_basis() -> [Matrix2<Cpx>; 4] {
+    let (zero, one, i) = (Cpx::from(0.0), Cpx::from(1.0), Cpx::i());
+    [
+        Matrix2::new(one, zero, zero, zero),
+        Matrix2::new(zero, zero, zero, one),
+        Matrix2::new(zero, one, one, zero),
+        Matrix2::new(zero, i, -i, zero),
+    ]
+}
+
+/// Returns the circle preserved by the transformations of a and b, the
+/// first two generators, or `None` if there is no such circle.  If there
+/// are several, as for some elementary groups, one of them is returned.
+pub fn invariant_circle(gens: &[Generator; 4]) -> Option<Circle> {
+    // A matrix m preserves the circle C when m^† C m = C, which is four
+    // real linear equations in the entries of C, and the circle is the
+    // null vector of the system for a and b.
+    let basis = hermitian_basis();
+    let mut sys = SMatrix::<f64, 8, 4>::zeros();
+    let mut size: f64 = 1.0;
+    for (k, g) in gens[..2].iter().enumerate() {
+        let m = &g.matrix;
+        size = m.iter().map(|z| z.norm()).fold(size, f64::max);
+        for (j, e) in basis.iter().enumerate() {
+            let d = m.adjoint() * e * m - e;
+            let col = [d[(0, 0)].re, d[(1, 1)].re, d[(0, 1)].re, d[(0, 1)].im];
+            for (i, x) in col.into_iter().enumerate() {
+                sys[(4 * k + i, j)] = x;
+            }
+        }
+    }
+    let svd = sys.svd(false, true);
+    let v_t = svd.v_t?;
+    let k = svd.singular_values.imin();
+    if svd.singular_values[k] > TOLERANCE * size * size || svd.singular_values[k].is_nan() {
+        return None;
+    }
+    let h = (0..4).fold(Matrix2::zeros(), |h, j| {
+        h + basis[j] * Cpx::from(v_t[(k, j)])
+    });
+    // Hermitian matrices with positive determinant, such as those
+    // preserved by groups of rotations of the sphere, do not give circles.
+    let det = h.determinant().re;
+    (det < 0.0).then(|| Circle(h / Cpx::from((-det).sqrt())))
+}
+
+/// Returns a matrix of determinant 1 for a transformation that takes the
+/// circle to the unit circle.
+pub fn disk_normalizer(c: &Circle) -> Matrix2<Cpx> {
+    // If C = T^† J T with J = diag(1, -1), which is the matrix of the unit
+    // circle, then T takes C to the unit circle.  T comes from the
+    // eigenvectors of C, whose eigenvalues have opposite signs.
+    let eigen = c.0.symmetric_eigen();
+    let (pos, neg) = if eigen.eigenvalues[0] > 0.0 {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let row = |i: usize| {
+        eigen.eigenvectors.column(i).adjoint() * Cpx::from(eigen.eigenvalues[i].abs().sqrt())
+    };
+    let (r0, r1) = (row(pos), row(neg));
+    let t = Matrix2::new(r0[0], r0[1], r1[0], r1[1]);
+    t / t.determinant().sqrt()
+}
+
+/// Returns the generators conjugated so that the circle preserved by the
+/// group is the unit circle, or `None` if the group does not preserve a
+/// circle.
+pub fn normalize_to_disk(gens: &[Generator; 4]) -> Option<[Generator; 4]> {
+    let c = invariant_circle(gens)?;
+    Some(conjugate(gens, &disk_normalizer(&c)))
+}
+
+/// Returns the generators of a Fuchsian group whose quotient is a closed
+/// surface of genus 2, for drawing the limit set, a circle, and then
+/// deforming it into quasifuchsian groups.  The surface is made of two tori
+/// with a hole, glued along a separating curve; the group is generated by
+/// a_1, b_1 for the first torus and a_2, b_2 for the second, with the one
+/// relation a_1 b_1 a_1^{-1} b_1^{-1} a_2 b_2 a_2^{-1} b_2^{-1} = 1.  The
+/// parameters are in the style of Fenchel–Nielsen coordinates: `lengths`
+/// are the hyperbolic lengths of the curves of a_1, b_1, a_2, and b_2,
+/// `separating` is the length of the separating curve, and `twist` is the
+/// distance by which the second torus is slid along it before gluing.  The
+/// lengths of the curves of each torus, and of its boundary, determine it
+/// up to the choice of tr a_i b_i, which is the larger of the two roots.
+///
+/// The generators are followed by their inverses, as
+/// `CircleQueue::with_generators` expects, which skips the words with more
+/// than half of the relation in a row.  The group is conjugated so that it
+/// preserves the unit circle, and the circle of each generator is the
+/// isometric circle of its inverse, as in `Generator::from_matrices`.
+/// Returns `Error::DegenerateGenerators` if there is no torus with the
+/// given lengths, which happens when the separating curve is too long for
+/// them.
+pub fn generators_genus2(
+    lengths: [f64; 4],
+    separating: f64,
+    twist: f64,
+) -> Result<Vec<Generator>, Error> {
+    let [a1, b1] =
+        holed_torus(lengths[0], lengths[1], separating, true).ok_or(Error::DegenerateGenerators)?;
+    let [a2, b2] = holed_torus(lengths[2], lengths[3], separating, false)
+        .ok_or(Error::DegenerateGenerators)?;
+    // The commutator of the second torus is the inverse of that of the
+    // first, so the relation holds.  Turning the second torus over to the
+    // negative real axis puts it on the other side of the separating curve,
+    // and sliding it along the imaginary axis, the curve, twists it.
+    let (zero, i) = (Cpx::from(0.0), Cpx::i());
+    let s = Cpx::from((0.5 * twist).exp());
+    let slide = Matrix2::new(i * s, zero, zero, -i / s);
+    let to_disk = |m: Matrix2<Cpx>| cayley() * m * inv(&cayley());
+    let matrices = [
+        to_disk(a1),
+        to_disk(b1),
+        to_disk(slide * a2 * inv(&slide)),
+        to_disk(slide * b2 * inv(&slide)),
+    ];
+    Generator::from_matrices(&matrices)
+}
+
+/// Returns a and b for a torus with a hole whose curves a, b, and boundary
+/// have hyperbolic lengths `la`, `lb`, and `boundary`, as real matrices,
+/// conjugated so that the boundary is the imaginary axis.  The commutator
+/// aba^{-1}b^{-1} moves points towards ∞ if `outward` and towards 0
+/// otherwise, and the limit set of the torus is on the positive real axis.
+fn holed_torus(la: f64, lb: f64, boundary: f64, outward: bool) -> Option<[Matrix2<Cpx>; 2]> {
+    let (x, y) = (2.0 * (0.5 * la).cosh(), 2.0 * (0.5 * lb).cosh());
+    let k = -2.0 * (0.5 * boundary).cosh();
+    let disc = x * x * y * y - 4.0 * (x * x + y * y - 2.0 - k);
+    if disc < 0.0 || disc.is_nan() {
+        return None;
+    }
+    let z = 0.5 * (x * y + disc.sqrt());
+    // a is diagonal, and b is chosen to have the right traces, so that both
+    // are real.
+    let l = 0.5 * (x + (x * x - 4.0).sqrt());
+    let b11 = (z - y / l) / (l - 1.0 / l);
+    let b22 = y - b11;
+    let real = |p: f64, q: f64, r: f64, s: f64| Matrix2::new(p, q, r, s).map(Cpx::from);
+    let a = real(l, 0.0, 0.0, 1.0 / l);
+    let b = real(b11, 1.0, b11 * b22 - 1.0, b22);
+    let commutator = a * b * inv(&a) * inv(&b);
+    let (zero, one, i) = (Cpx::from(0.0), Cpx::from(1.0), Cpx::i());
+    let conj = |m: &Matrix2<Cpx>, x: &Matrix2<Cpx>| m * x * inv(m);
+    // Move the fixed points of the commutator to 0 and ∞, the right way
+    // round, and turn the group over if its limit set is on the negative
+    // real axis.  The fixed points are real, so the matrices stay real.
+    let [p, q] = fixed_points(&commutator);
+    let mut m = if p.is_finite() && q.is_finite() {
+        Matrix2::new(one, -p, one, -q) / (p - q).sqrt()
+    } else if p.is_finite() {
+        Matrix2::new(one, -p, zero, one)
+    } else {
+        Matrix2::new(zero, i, i, -i * q)
+    };
+    if (conj(&m, &commutator)[(0, 0)].norm() > 1.0) != outward {
+        m = Matrix2::new(zero, i, i, zero) * m;
+    }
+    if fixed_points(&conj(&m, &a))[0].re < 0.0 {
+        m = Matrix2::new(i, zero, zero, -i) * m;
+    }
+    Some([conj(&m, &a), conj(&m, &b)])
+}
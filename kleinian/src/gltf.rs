@@ -0,0 +1,182 @@
+//! Export of the hemispheres in upper half-space whose boundaries are the
+//! circles, as a binary glTF (`.glb`) file that can be opened in Blender or
+//! three.js.  These hemispheres bound the images of the fundamental domain.
+//!
+//! The complex plane is the horizontal plane y = 0, with the real axis
+//! along x and the imaginary axis along -z, so that upper half-space is
+//! y > 0 as usual in glTF.
+
+use crate::h3::Point;
+use crate::{Circle, Cpx};
+use std::f64::consts::PI;
+use std::io::{self, Write};
+
+/// Controls how finely the hemispheres are tessellated.  The number of
+/// segments around each hemisphere is proportional to its radius, between `min_segments` and `max_segments`, so that the
+/// many small hemispheres don't dominate the size of the file.
+#[derive(Clone, Copy, Debug)]
+pub struct MeshOptions {
+    /// The number of segments around the largest hemisphere.
+    pub max_segments: usize,
+    pub min_segments: usize,
+    /// Hemispheres smaller than this are left out.
+    pub min_radius: f64,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        MeshOptions {
+            max_segments: 32,
+            min_segments: 6,
+            min_radius: 0.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Mesh {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Adds a hemisphere with `segments` segments around and a quarter as
+    /// many from the equator to the pole.  Each quadrilateral is left out
+    /// unless `keep` is true at its middle.
+    fn add_hemisphere<F: Fn(&Point) -> bool>(&mut self, c: &Circle, segments: usize, keep: &F) {
+        let z = c.center();
+        let r = 1.0 / c.radius_inv();
+        let rings = (segments / 4).max(1);
+        let base = self.positions.len() as u32;
+        for i in 0..=rings {
+            let phi = 0.5 * PI * i as f64 / rings as f64;
+            for j in 0..segments {
+                let theta = 2.0 * PI * j as f64 / segments as f64;
+                let n = [phi.cos() * theta.cos(), phi.sin(), -phi.cos() * theta.sin()];
+                self.normals.push(n.map(|x| x as f32));
+                self.positions.push([
+                    (z.re + r * n[0]) as f32,
+                    (r * n[1]) as f32,
+                    (-z.im + r * n[2]) as f32,
+                ]);
+            }
+        }
+        let seg = segments as u32;
+        for i in 0..rings as u32 {
+            let phi = 0.5 * PI * (i as f64 + 0.5) / rings as f64;
+            for j in 0..seg {
+                let theta = 2.0 * PI * (j as f64 + 0.5) / segments as f64;
+                let mid = Point::new(z + r * Cpx::from_polar(phi.cos(), theta), r * phi.sin());
+                if !keep(&mid) {
+                    continue;
+                }
+                let a = base + i * seg + j;
+                let b = base + i * seg + (j + 1) % seg;
+                let (c, d) = (a + seg, b + seg);
+                self.indices.extend_from_slice(&[a, b, d, a, d, c]);
+            }
+        }
+    }
+}
+
+fn pad(v: &mut Vec<u8>, fill: u8) {
+    while !v.len().is_multiple_of(4) {
+        v.push(fill);
+    }
+}
+
+/// Writes the hemispheres bounded by the circles as a binary glTF file
+/// with a single mesh.  Circles that are lines or have no finite size are
+/// skipped.
+pub fn write_glb<W: Write>(out: &mut W, circles: &[Circle], opts: &MeshOptions) -> io::Result<()> {
+    write_glb_filtered(out, circles, opts, |_| true)
+}
+
+/// Like `write_glb`, but only keeps the parts of the hemispheres where
+/// `keep` is true.
+pub fn write_glb_filtered<W: Write, F: Fn(&Point) -> bool>(
+    out: &mut W,
+    circles: &[Circle],
+    opts: &MeshOptions,
+    keep: F,
+) -> io::Result<()> {
+    let finite = |c: &&Circle| {
+        let r = 1.0 / c.radius_inv();
+        r.is_finite() && r >= opts.min_radius && c.center().is_finite()
+    };
+    let max_r = circles
+        .iter()
+        .filter(finite)
+        .map(|c| 1.0 / c.radius_inv())
+        .fold(0.0, f64::max);
+    let mut mesh = Mesh::default();
+    for c in circles.iter().filter(finite) {
+        let t = 1.0 / (c.radius_inv() * max_r);
+        let segments = ((t * opts.max_segments as f64) as usize)
+            .clamp(opts.min_segments.max(3), opts.max_segments.max(3));
+        mesh.add_hemisphere(c, segments, &keep);
+    }
+    let (mut min, mut max) = ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]);
+    for p in &mesh.positions {
+        for k in 0..3 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    if mesh.positions.is_empty() {
+        (min, max) = ([0.0; 3], [0.0; 3]);
+    }
+
+    let mut bin = Vec::new();
+    for p in mesh.positions.iter().flatten() {
+        bin.extend_from_slice(&p.to_le_bytes());
+    }
+    let normals_offset = bin.len();
+    for n in mesh.normals.iter().flatten() {
+        bin.extend_from_slice(&n.to_le_bytes());
+    }
+    let indices_offset = bin.len();
+    for i in &mesh.indices {
+        bin.extend_from_slice(&i.to_le_bytes());
+    }
+    let vertices = mesh.positions.len();
+    let json = format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"kleinian"}},"#,
+            r#""scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0}}],"#,
+            r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"indices":2}}]}}],"#,
+            r#""accessors":["#,
+            r#"{{"bufferView":0,"componentType":5126,"count":{v},"type":"VEC3","min":{min:?},"max":{max:?}}},"#,
+            r#"{{"bufferView":1,"componentType":5126,"count":{v},"type":"VEC3"}},"#,
+            r#"{{"bufferView":2,"componentType":5125,"count":{i},"type":"SCALAR"}}],"#,
+            r#""bufferViews":["#,
+            r#"{{"buffer":0,"byteOffset":0,"byteLength":{no},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{no},"byteLength":{nl},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{io},"byteLength":{il},"target":34963}}],"#,
+            r#""buffers":[{{"byteLength":{b}}}]}}"#
+        ),
+        v = vertices,
+        min = min,
+        max = max,
+        i = mesh.indices.len(),
+        no = normals_offset,
+        nl = indices_offset - normals_offset,
+        io = indices_offset,
+        il = bin.len() - indices_offset,
+        b = bin.len(),
+    );
+    let mut json = json.into_bytes();
+    pad(&mut json, b' ');
+    pad(&mut bin, 0);
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    out.write_all(b"glTF")?;
+    out.write_all(&2u32.to_le_bytes())?;
+    out.write_all(&(total as u32).to_le_bytes())?;
+    out.write_all(&(json.len() as u32).to_le_bytes())?;
+    out.write_all(b"JSON")?;
+    out.write_all(&json)?;
+    out.write_all(&(bin.len() as u32).to_le_bytes())?;
+    out.write_all(b"BIN\0")?;
+    out.write_all(&bin)
+}
@@ -0,0 +1,83 @@
+//! Generators along with the quantities derived from them that frontends
+//! often want to show, such as the traces that are not chosen directly.
+
+use crate::algebra::{fixed_points, inv};
+use crate::{Cpx, Generator};
+use nalgebra::Matrix2;
+
+/// Which root of the quadratic equation for tr ab a group uses.  Given tr a,
+/// tr b, and the trace of the commutator, tr ab is one of the two roots
+/// (tr a tr b ± s) / 2, where s is the principal square root of the
+/// discriminant.  `generators` always uses `Minus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Branch {
+    Minus,
+    Plus,
+}
+
+/// Generators along with derived data.
+pub struct GeneratorSet {
+    /// The generators, in the order a, b, a^{-1}, b^{-1}.
+    pub generators: [Generator; 4],
+    pub trace_a: Cpx,
+    pub trace_b: Cpx,
+    pub trace_ab: Cpx,
+    /// The trace of aba^{-1}b^{-1}, which is -2 for groups from
+    /// `generators`.
+    pub trace_commutator: Cpx,
+    /// The commutator and its cyclic conjugates b^{-1}aba^{-1},
+    /// aba^{-1}b^{-1}, ba^{-1}b^{-1}a, and a^{-1}b^{-1}ab.  The circle of
+    /// each generator passes through the fixed points of two consecutive
+    /// ones when the circles come from `circle_for_transforms`.
+    pub commutators: [Matrix2<Cpx>; 4],
+    /// The fixed points of each of `commutators`.
+    pub fixed_points: [[Cpx; 2]; 4],
+    pub branch: Branch,
+}
+
+impl GeneratorSet {
+    pub fn new(generators: [Generator; 4]) -> Self {
+        let (a, b) = (generators[0].matrix, generators[1].matrix);
+        let (ai, bi) = (inv(&a), inv(&b));
+        let commutators = [
+            bi * a * b * ai,
+            a * b * ai * bi,
+            b * ai * bi * a,
+            ai * bi * a * b,
+        ];
+        let (ta, tb, tab) = (a.trace(), b.trace(), (a * b).trace());
+        let trace_commutator = commutators[1].trace();
+        // tr ab = (tr a tr b - s) / 2, and the branch is Minus if s is the
+        // principal square root.  When the discriminant is a negative real
+        // number, s is imaginary, and the principal root is the one with
+        // positive imaginary part.
+        let s = ta * tb - 2.0 * tab;
+        let branch = if s.re > 1e-9 * s.norm() || (s.re > -1e-9 * s.norm() && s.im >= 0.0) {
+            Branch::Minus
+        } else {
+            Branch::Plus
+        };
+        GeneratorSet {
+            generators,
+            trace_a: ta,
+            trace_b: tb,
+            trace_ab: tab,
+            trace_commutator,
+            fixed_points: commutators.map(|k| fixed_points(&k)),
+            commutators,
+            branch,
+        }
+    }
+}
+
+/// Returns the traces `[tr a, tr b, tr ab]` of the generators.
+pub fn traces(gens: &[Generator; 4]) -> [Cpx; 3] {
+    let (a, b) = (&gens[0].matrix, &gens[1].matrix);
+    [a.trace(), b.trace(), (a * b).trace()]
+}
+
+impl From<GeneratorSet> for [Generator; 4] {
+    fn from(set: GeneratorSet) -> Self {
+        set.generators
+    }
+}
@@ -0,0 +1,81 @@
+//! The action of the group on upper half-space H³, the space whose boundary
+//! is the Riemann sphere on which the limit set lies.  Each Möbius
+//! transformation extends to an isometry of H³, each circle bounds a
+//! hemisphere, and the images of a point of H³ accumulate on the limit
+//! set.
+
+use crate::{Circle, CircleQueue, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+
+/// A point of upper half-space, with `z` the coordinate along the
+/// boundary plane and `t > 0` the height above it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub z: Cpx,
+    pub t: f64,
+}
+
+impl Point {
+    pub fn new(z: Cpx, t: f64) -> Self {
+        Point { z, t }
+    }
+    /// Returns the hyperbolic distance between the points.
+    pub fn distance(&self, other: &Point) -> f64 {
+        let d = (self.z - other.z).norm_sqr() + (self.t - other.t).powi(2);
+        (1.0 + d / (2.0 * self.t * other.t)).acosh()
+    }
+}
+
+/// Applies the isometry of H³ extending the Möbius transformation with
+/// matrix `m`, which should have determinant 1.
+pub fn act(m: &Matrix2<Cpx>, p: &Point) -> Point {
+    let (a, b, c, d) = (m[(0, 0)], m[(0, 1)], m[(1, 0)], m[(1, 1)]);
+    let t2 = p.t * p.t;
+    let denom = (c * p.z + d).norm_sqr() + c.norm_sqr() * t2;
+    Point {
+        z: ((a * p.z + b) * (c * p.z + d).conj() + a * c.conj() * t2) / denom,
+        t: p.t / denom,
+    }
+}
+
+/// A hemisphere in upper half-space, which is a hyperbolic plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hemisphere {
+    pub center: Cpx,
+    pub radius: f64,
+}
+
+impl Hemisphere {
+    /// Returns the hemisphere bounded by the circle.
+    pub fn from_circle(c: &Circle) -> Self {
+        Hemisphere {
+            center: c.center(),
+            radius: 1.0 / c.radius_inv(),
+        }
+    }
+    /// Returns the highest point of the hemisphere.
+    pub fn top(&self) -> Point {
+        Point::new(self.center, self.radius)
+    }
+}
+
+/// Explores the group until there are at least `num_points` words, and
+/// returns the images of `base` under the elements of the words.  The
+/// images are the points of the orbit nearest to the limit set that are
+/// reached by the exploration.
+pub fn orbit(gens: [Generator; 4], base: &Point, num_points: usize) -> Result<Vec<Point>, Error> {
+    let mut queue = CircleQueue::new(gens)?;
+    while queue.len() < num_points {
+        queue.advance()?;
+    }
+    Ok(queue.elements().map(|m| act(&m, base)).collect())
+}
+
+/// Like `generate_circles`, but returns the hemispheres bounded by the
+/// circles, which bound the images of the fundamental domain in H³.
+pub fn hemispheres(gens: [Generator; 4], num_points: usize) -> Result<Vec<Hemisphere>, Error> {
+    Ok(crate::generate_circles(gens, num_points)?
+        .iter()
+        .map(Hemisphere::from_circle)
+        .collect())
+}
@@ -0,0 +1,159 @@
+//! A k-d tree over points of the plane, for finding the points of the
+//! limit set near a given point, for example to find out which word's point
+//! was clicked.
+
+use crate::Cpx;
+
+/// Ranges with at most this many points are searched one point at a time.
+const LEAF_SIZE: usize = 8;
+
+/// A static k-d tree.  The points are stored in an array, with the median
+/// of each range (along the axis for its depth) in the middle of the range,
+/// and smaller and larger points on either side.
+#[derive(Clone, Debug)]
+pub struct PointIndex {
+    /// The indices of the points in the slice that the index was built
+    /// from, in tree order.
+    ids: Vec<usize>,
+    points: Vec<Cpx>,
+}
+
+fn coord(z: &Cpx, axis: usize) -> f64 {
+    if axis == 0 {
+        z.re
+    } else {
+        z.im
+    }
+}
+
+fn build(items: &mut [(usize, Cpx)], axis: usize) {
+    if items.len() <= LEAF_SIZE {
+        return;
+    }
+    let mid = items.len() / 2;
+    items.select_nth_unstable_by(mid, |a, b| coord(&a.1, axis).total_cmp(&coord(&b.1, axis)));
+    let (lo, hi) = items.split_at_mut(mid);
+    build(lo, 1 - axis);
+    build(&mut hi[1..], 1 - axis);
+}
+
+impl PointIndex {
+    /// Builds an index of the points.  Points that are not finite are left
+    /// out.  The indices returned by queries are positions in `points`.
+    pub fn new(points: &[Cpx]) -> Self {
+        let mut items: Vec<(usize, Cpx)> = points
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, z)| z.is_finite())
+            .collect();
+        build(&mut items, 0);
+        PointIndex {
+            ids: items.iter().map(|(i, _)| *i).collect(),
+            points: items.iter().map(|(_, z)| *z).collect(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+    /// Returns the index of the point nearest to `z`, or `None` if there
+    /// are no points.
+    pub fn nearest(&self, z: Cpx) -> Option<usize> {
+        let mut best = (f64::INFINITY, None);
+        self.nearest_in(0, self.points.len(), 0, z, &mut best);
+        best.1
+    }
+    fn nearest_in(
+        &self,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        z: Cpx,
+        best: &mut (f64, Option<usize>),
+    ) {
+        let mut consider = |i: usize| {
+            let d = (self.points[i] - z).norm_sqr();
+            if d < best.0 {
+                *best = (d, Some(self.ids[i]));
+            }
+        };
+        if hi - lo <= LEAF_SIZE {
+            (lo..hi).for_each(consider);
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        consider(mid);
+        let diff = coord(&z, axis) - coord(&self.points[mid], axis);
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.nearest_in(near.0, near.1, 1 - axis, z, best);
+        if diff * diff < best.0 {
+            self.nearest_in(far.0, far.1, 1 - axis, z, best);
+        }
+    }
+    /// Returns the indices of the points in the rectangle with corners
+    /// `min` and `max`, in no particular order.
+    pub fn in_rect(&self, min: Cpx, max: Cpx) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.rect_in(0, self.points.len(), 0, [min, max], &mut |i| {
+            out.push(self.ids[i]);
+        });
+        out
+    }
+    /// Returns the indices of the points within distance `r` of `z`, in no
+    /// particular order.
+    pub fn within(&self, z: Cpx, r: f64) -> Vec<usize> {
+        let corner = Cpx::new(r, r);
+        let mut out = Vec::new();
+        self.rect_in(
+            0,
+            self.points.len(),
+            0,
+            [z - corner, z + corner],
+            &mut |i| {
+                if (self.points[i] - z).norm_sqr() <= r * r {
+                    out.push(self.ids[i]);
+                }
+            },
+        );
+        out
+    }
+    /// Calls `f` with the tree positions of the points in the rectangle.
+    fn rect_in<F: FnMut(usize)>(
+        &self,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        rect: [Cpx; 2],
+        f: &mut F,
+    ) {
+        let inside = |z: &Cpx| {
+            z.re >= rect[0].re && z.re <= rect[1].re && z.im >= rect[0].im && z.im <= rect[1].im
+        };
+        if hi - lo <= LEAF_SIZE {
+            for i in lo..hi {
+                if inside(&self.points[i]) {
+                    f(i);
+                }
+            }
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let c = coord(&self.points[mid], axis);
+        if inside(&self.points[mid]) {
+            f(mid);
+        }
+        if coord(&rect[0], axis) <= c {
+            self.rect_in(lo, mid, 1 - axis, rect, f);
+        }
+        if coord(&rect[1], axis) >= c {
+            self.rect_in(mid + 1, hi, 1 - axis, rect, f);
+        }
+    }
+}
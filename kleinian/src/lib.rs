@@ -1,13 +1,67 @@
-mod algebra;
+pub mod algebra;
+pub mod arithmetic;
+pub mod bers;
+pub mod cache;
 mod circle;
+pub mod combination;
+pub mod conjugate;
+pub mod cyclic;
+pub mod deform;
+pub mod degenerate;
+mod depth_first;
+pub mod diagnostics;
+pub mod dimension;
+pub mod elementary;
+pub mod elements;
+mod error;
+#[cfg(feature = "evcxr")]
+pub mod evcxr;
+pub mod flow;
+pub mod ford;
+pub mod fuchsian;
+pub mod gltf;
+pub mod group;
+pub mod h3;
+pub mod index;
+pub mod maskit;
+pub mod morph;
+pub mod nielsen;
+pub mod partition;
+pub mod plan;
+pub mod pleating;
+pub mod pointcloud;
+pub mod postprocess;
+pub mod presets;
+pub mod projective;
 mod queue;
+pub mod raster;
+pub mod reflection;
+pub mod render;
+pub mod score;
+pub mod section;
+pub mod seed;
+pub mod sensitivity;
+pub mod stream;
+pub mod subgroup;
+pub mod svg;
+pub mod systole;
+#[cfg(feature = "arrow")]
+pub mod table;
+pub mod trace;
+pub mod validation;
+#[cfg(feature = "rerun")]
+pub mod viewer;
+pub mod warp;
 pub mod window;
 
 use crate::algebra::{circle_for_transforms, inv};
-use crate::circle::Circle;
-use crate::queue::CircleQueue;
+pub use crate::circle::Circle;
+pub use crate::error::Error;
+pub use crate::queue::CircleQueue;
+use crate::render::UnknownName;
 use nalgebra::Matrix2;
 use num_complex::Complex;
+use std::str::FromStr;
 
 pub type Cpx = Complex<f64>;
 
@@ -22,15 +76,272 @@ pub struct Generator {
     pub circle: Circle,
 }
 
-pub fn generate_points(gens: [Generator; 4], num_points: usize) -> Vec<Cpx> {
-    let mut queue = CircleQueue::new(gens);
+/// The commutator of two matrices is treated as parabolic with trace -2 by
+/// `Generator::from_matrices` if its trace is within this of -2.
+const COMMUTATOR_TOLERANCE: f64 = 1e-9;
+
+impl Generator {
+    /// Returns the generators for the given matrices, followed by their
+    /// inverses in the same order, as `CircleQueue::with_generators`
+    /// expects.  For two matrices, the result converts with `try_into` to
+    /// the array that `generate_points` takes.  The matrices are divided by
+    /// square roots of their determinants, so they need not be in SL(2, C).
+    /// For two matrices whose commutator is parabolic with trace -2, the
+    /// circles are found from the commutators as in
+    /// `generators_from_matrices`.  Otherwise the circle of each generator
+    /// is the isometric circle of its inverse, as in
+    /// `seed::SeedCircles::Isometric`, which works well for Schottky groups;
+    /// a generator that fixes ∞ has no isometric circle, so the group
+    /// should first be conjugated with `conjugate::conjugate` to move the
+    /// fixed point.  Returns `Error::DegenerateGenerators` for singular or
+    /// non-finite matrices, or a generator that fixes ∞.
+    pub fn from_matrices(matrices: &[Matrix2<Cpx>]) -> Result<Vec<Generator>, Error> {
+        let normalized = matrices
+            .iter()
+            .map(|m| {
+                let s = m.determinant().sqrt();
+                if s == Cpx::from(0.0) || !s.is_finite() {
+                    return Err(Error::DegenerateGenerators);
+                }
+                Ok(m / s)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let all: Vec<Matrix2<Cpx>> = normalized
+            .iter()
+            .copied()
+            .chain(normalized.iter().map(inv))
+            .collect();
+        if let [a, b] = normalized[..] {
+            let k = a * b * all[2] * all[3];
+            if (k.trace() + 2.0).norm() < COMMUTATOR_TOLERANCE {
+                let gens = generators_from_matrices(a, b);
+                check_generators(&gens)?;
+                return Ok(gens.into());
+            }
+        }
+        let n = all.len();
+        let gens = (0..n)
+            .map(|i| {
+                let h = ford::isometric_hemisphere(&all[(i + n / 2) % n])
+                    .ok_or(Error::DegenerateGenerators)?;
+                Ok(Generator {
+                    matrix: all[i],
+                    circle: Circle::new(h.center, h.radius),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        check_generators(&gens)?;
+        Ok(gens)
+    }
+}
+
+/// Checks that the generators and their circles are finite.  (A circle
+/// may still be a line, which has infinite radius.)  Also checks that there
+/// are a positive, even number of them, as `CircleQueue::with_generators`
+/// requires.
+pub fn check_generators(gens: &[Generator]) -> Result<(), Error> {
+    if gens.is_empty() || !gens.len().is_multiple_of(2) {
+        return Err(Error::DegenerateGenerators);
+    }
+    for g in gens {
+        if !g.matrix.iter().all(|z| z.is_finite()) || !g.circle.0.iter().all(|z| z.is_finite()) {
+            return Err(Error::DegenerateGenerators);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a word in the generators, such as `"abAB"` for the commutator,
+/// where a, b, c, and so on stand for the first `rank` generators and A, B,
+/// C for their inverses, into the indices of its letters, in the order of
+/// `CircleQueue::with_generators`.  The empty word is the identity.
+/// Returns `None` if the word has any other characters.
+pub fn parse_letters(s: &str, rank: usize) -> Option<Vec<u8>> {
+    s.chars()
+        .map(|c| {
+            let (base, offset) = match c {
+                'a'..='z' => ('a', 0),
+                'A'..='Z' => ('A', rank),
+                _ => return None,
+            };
+            let i = c as usize - base as usize;
+            (i < rank).then(|| (i + offset) as u8)
+        })
+        .collect()
+}
+
+/// Returns the product of the matrices of the letters of the word, which
+/// are indices into `gens`, as returned by `parse_letters`.  Returns
+/// `None` if a letter is not an index into `gens`.
+pub fn word_matrix(word: &[u8], gens: &[Generator]) -> Option<Matrix2<Cpx>> {
+    word.iter().try_fold(Matrix2::identity(), |m, &l| {
+        gens.get(l as usize).map(|g| m * g.matrix)
+    })
+}
+
+/// Returns the product of the generators spelled by the word, written as
+/// in `parse_letters`, such as `"abAB"` for the commutator, with the
+/// generators and their inverses in `gens` as in
+/// `CircleQueue::with_generators`.  Returns `None` if the word has any
+/// other characters.
+pub fn parse_word(s: &str, gens: &[Generator]) -> Option<Matrix2<Cpx>> {
+    word_matrix(&parse_letters(s, gens.len() / 2)?, gens)
+}
+
+/// Elliptic generators whose orders are larger than this are treated as
+/// having infinite order.
+const MAX_ELLIPTIC_ORDER: usize = 1000;
+
+/// If the Möbius transformation with matrix `m` (of determinant 1) is
+/// elliptic of finite order, returns its order.  The trace of such a
+/// transformation is 2 cos(πp/k) for coprime p and k, and k is the order.
+pub fn elliptic_order(m: &Matrix2<Cpx>) -> Option<usize> {
+    let tr = m.trace();
+    if tr.im.abs() > 1e-9 || tr.re.abs() >= 2.0 {
+        return None;
+    }
+    let x = (0.5 * tr.re).acos() / std::f64::consts::PI;
+    (2..=MAX_ELLIPTIC_ORDER).find(|&k| {
+        let kx = k as f64 * x;
+        (kx - kx.round()).abs() < 1e-9 * k as f64
+    })
+}
+
+/// A point of the limit set, along with information about the circle that
+/// it is the center of.
+#[derive(Clone, Copy, Debug)]
+pub struct LimitPoint {
+    pub z: Cpx,
+    pub radius: f64,
+    /// The index of the generator whose circle was mapped to get this
+    /// circle, in the order a, b, a^{-1}, b^{-1}.
+    pub generator: u8,
+    /// The length of the word that maps the generator's circle to this one.
+    pub depth: usize,
+}
+
+/// Explores the group until there are at least `num_points` circles,
+/// recording their words if `words` is set, as in
+/// `CircleQueue::enable_words`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(num_points = num_points))
+)]
+fn explore(gens: [Generator; 4], num_points: usize, words: bool) -> Result<CircleQueue, Error> {
+    let mut queue = CircleQueue::new(gens)?;
+    if words {
+        queue.enable_words();
+    }
     while queue.len() < num_points {
-        queue.advance()
+        queue.advance()?;
+    }
+    Ok(queue)
+}
+
+pub fn generate_points(gens: [Generator; 4], num_points: usize) -> Result<Vec<Cpx>, Error> {
+    Ok(explore(gens, num_points, false)?
+        .circles()
+        .map(|c| c.center_point().affine())
+        .collect())
+}
+
+/// Like `generate_points`, but also returns information about where each
+/// point came from, which can be used to color the points.
+pub fn generate_labelled_points(
+    gens: [Generator; 4],
+    num_points: usize,
+) -> Result<Vec<LimitPoint>, Error> {
+    Ok(explore(gens, num_points, false)?
+        .labelled_points()
+        .collect())
+}
+
+/// Returns the circles of the given words, as returned by
+/// `CircleQueue::words`.  Consecutive words usually share long prefixes, and
+/// the products of shared prefixes are only computed once, so this is much
+/// faster than exploring the group again.  This makes it possible to reuse
+/// one exploration for a family of nearby groups.  With the `rayon`
+/// feature, the words are split among threads.
+pub fn circles_for_words(gens: &[Generator; 4], words: &[Vec<u8>]) -> Vec<Circle> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        words
+            .par_chunks(1 << 12)
+            .flat_map_iter(|chunk| circles_for_words_serial(gens, chunk))
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    circles_for_words_serial(gens, words)
+}
+
+fn circles_for_words_serial(gens: &[Generator; 4], words: &[Vec<u8>]) -> Vec<Circle> {
+    // prefixes[k] is the product of the first k letters of the previous word.
+    let mut prefixes = vec![Matrix2::identity()];
+    let mut prev: &[u8] = &[];
+    let mut circles = Vec::with_capacity(words.len());
+    for word in words {
+        let Some((&last, init)) = word.split_last() else {
+            continue;
+        };
+        let common = prev.iter().zip(init).take_while(|(a, b)| a == b).count();
+        prefixes.truncate(common + 1);
+        for &l in &init[common..] {
+            let m = prefixes[prefixes.len() - 1] * gens[l as usize].matrix;
+            prefixes.push(m);
+        }
+        circles.push(prefixes[init.len()] * gens[last as usize].circle);
+        prev = init;
     }
-    queue.circles().map(|c| c.center()).collect()
+    circles
 }
 
-pub fn generate_points_from_traces(ta: Cpx, tb: Cpx, num_points: usize) -> Vec<Cpx> {
+/// Returns the circles of a limit set approximation, ordered so that
+/// consecutive circles are adjacent along the limit set.  When the limit set
+/// is a curve, joining the centers in order traces out the curve.
+pub fn generate_circles(gens: [Generator; 4], num_points: usize) -> Result<Vec<Circle>, Error> {
+    Ok(explore(gens, num_points, true)?.ordered_circles().collect())
+}
+
+/// Returns the `n` largest images of the circle `c` under the group, such
+/// as the orbit of a geodesic or a horocycle, largest first.  `c` itself is
+/// the image under the identity.
+///
+/// The group is explored in the same order as `generate_circles`.  If `c`
+/// lies outside the circles of the generators, its image under a word lies
+/// inside the circle of that word, so once `n` images are larger than every
+/// circle left in the queue, none of the remaining images can be larger.
+/// Otherwise the images found are only approximately the largest ones.
+pub fn orbit_of_circle(gens: [Generator; 4], c: Circle, n: usize) -> Result<Vec<Circle>, Error> {
+    use ordered_float::NotNan;
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let mut queue = CircleQueue::new(gens)?;
+    let mut images = Vec::new();
+    // The radii of the `n` largest images so far, smallest on top.
+    let mut largest = BinaryHeap::with_capacity(n + 1);
+    let mut image = c;
+    loop {
+        if let Ok(r) = NotNan::new(1.0 / image.radius_inv()) {
+            images.push((*r, image));
+            largest.push(Reverse(r));
+            if largest.len() > n {
+                largest.pop();
+            }
+        }
+        let done = largest.len() == n && largest.peek().is_none_or(|r| *r.0 >= queue.max_radius());
+        if done || queue.is_empty() {
+            break;
+        }
+        image = queue.expand(|_| ())? * c;
+    }
+    images.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+    images.truncate(n);
+    Ok(images.into_iter().map(|(_, c)| c).collect())
+}
+
+pub fn generate_points_from_traces(ta: Cpx, tb: Cpx, num_points: usize) -> Result<Vec<Cpx>, Error> {
     let gens = generators(ta, tb);
     generate_points(gens, num_points)
 }
@@ -40,18 +351,170 @@ pub fn generate_points_from_traces(ta: Cpx, tb: Cpx, num_points: usize) -> Vec<C
 /// is taken from p229 of Indra's Pearls by Mumford, Series, and
 /// Wright.
 pub fn generators(ta: Cpx, tb: Cpx) -> [Generator; 4] {
-    let c0 = ta * ta + tb * tb;
+    generators_with_root(ta, tb, TabRoot::Minus)
+}
+
+/// Which of the `tab_roots` to use for tr ab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabRoot {
+    /// The first root, which `generators` uses.
+    Minus,
+    /// The second root, which adds the square root of the discriminant.
+    Plus,
+}
+
+impl TabRoot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TabRoot::Minus => "minus",
+            TabRoot::Plus => "plus",
+        }
+    }
+}
+
+impl FromStr for TabRoot {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "minus" => Ok(TabRoot::Minus),
+            "plus" => Ok(TabRoot::Plus),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
+
+/// Like `generators`, but with the given root for tr ab.  The other root is
+/// tr ab^{-1} for the generators of `generators`, so `TabRoot::Plus` gives
+/// a conjugate of the same group with b in effect replaced by b^{-1}.  The
+/// circles are found for the new pair of generators, though, so they are
+/// not the same, and since the first root jumps to the second across the
+/// branch cut, both are needed to reach every marked group with the given
+/// traces.  For ta = tb = 2, `generators_apollonian` has the first root,
+/// tr ab = 2 - 2i, and `TabRoot::Plus` replaces its b with b^{-1}, which
+/// gives the second, 2 + 2i.
+pub fn generators_with_root(ta: Cpx, tb: Cpx, root: TabRoot) -> [Generator; 4] {
+    if is_parabolic_trace(ta) && is_parabolic_trace(tb) {
+        let [a, b, ai, bi] = generators_apollonian();
+        return match root {
+            TabRoot::Minus => [a, b, ai, bi],
+            TabRoot::Plus => [a, bi, ai, b],
+        };
+    }
+    let tab = tab_roots(ta, tb)[root as usize];
+    let (a, b) = matrices_for_traces(ta, tb, tab);
+    generators_from_matrices(a, b)
+}
+
+/// Returns the two values of tr ab for which tr aba^{-1}b^{-1} = -2, which
+/// are the roots of tab^2 - ta tb tab + ta^2 + tb^2 = 0.  The first, which
+/// subtracts the principal square root of the discriminant, is the one
+/// that `generators` uses.  The roots add up to ta tb, so if one is tr ab
+/// then the other is tr ab^{-1}, and they give the same group with b
+/// replaced by its inverse.  The choice changes the circles, and the first
+/// root jumps to the second where the discriminant crosses the branch cut
+/// of the square root, which matters when following a path of groups.
+pub fn tab_roots(ta: Cpx, tb: Cpx) -> [Cpx; 2] {
     let c1 = ta * tb;
-    let tab = 0.5 * (c1 - (c1 * c1 - 4.0 * c0).sqrt());
+    let s = (c1 * c1 - 4.0 * (ta * ta + tb * tb)).sqrt();
+    [0.5 * (c1 - s), 0.5 * (c1 + s)]
+}
+
+/// Traces are accepted by `generators_jorgensen` if they satisfy the
+/// equation for tr ab up to this, relative to the size of its terms.
+const MARKOV_TOLERANCE: f64 = 1e-9;
+
+/// Returns the quadruple [a,b,a^{-1},b^{-1}] with tr a = `ta`, tr b = `tb`,
+/// and tr ab = `tab`, from Jørgensen's normalization in Indra's Pearls:
+/// a = (ta - tb/tab, ta/tab^2; ta, tb/tab) and
+/// b = (tb - ta/tab, -tb/tab^2; -tb, ta/tab).  `tab` must be one of
+/// `tab_roots(ta, tb)`, so that the commutator is parabolic with trace -2,
+/// and `generators` gives the same group, up to conjugation, with the
+/// first root.  The only division is by `tab`, so this stays accurate in
+/// regions where the normalization of `generators` divides by nearly 0.
+/// The circles are chosen as in `generators_from_matrices`.  In this
+/// normalization, aba^{-1}b^{-1} fixes ∞ and a^{-1}b^{-1}ab fixes 0, so
+/// the circles of a and b are lines, and as in `generators_riley`
+/// everything is conjugated by the Cayley transform, which takes those
+/// fixed points to 1 and -1.  Returns `Error::DegenerateGenerators` if
+/// `tab` is not a root or is 0.
+pub fn generators_jorgensen(ta: Cpx, tb: Cpx, tab: Cpx) -> Result<[Generator; 4], Error> {
+    let terms = [ta * ta, tb * tb, tab * tab, ta * tb * tab];
+    let size = terms.iter().map(|t| t.norm()).fold(0.0, f64::max);
+    let residual = (terms[0] + terms[1] + terms[2] - terms[3]).norm();
+    if tab == Cpx::from(0.0) || residual > MARKOV_TOLERANCE * size || residual.is_nan() {
+        return Err(Error::DegenerateGenerators);
+    }
+    let a = Matrix2::new(ta - tb / tab, ta / (tab * tab), ta, tb / tab);
+    let b = Matrix2::new(tb - ta / tab, -tb / (tab * tab), -tb, ta / tab);
+    let gens = conjugate::conjugate(&generators_from_matrices(a, b), &cayley());
+    check_generators(&gens)?;
+    Ok(gens)
+}
+
+/// Traces within this distance of 2 are treated as exactly 2.
+const PARABOLIC_TOLERANCE: f64 = 1e-12;
+
+fn is_parabolic_trace(t: Cpx) -> bool {
+    (t - 2.0).norm() < PARABOLIC_TOLERANCE
+}
+
+/// Returns the generators of the double cusp group with tr a = tr b = 2,
+/// whose limit set is the Apollonian gasket, which `generators` also
+/// returns for those traces.  The general formula is degenerate here: the
+/// circles are found from square roots of zero, so their signs depend on
+/// how the rounding went.  Instead, the matrices and circles are given
+/// exactly: a fixes 0, b fixes -i, and the circle of a is the real axis.
+/// The four circles are mutually tangent, and they and their images are
+/// the circles of the gasket, so each circle contains the circles below
+/// it and the queue spends nothing on overlaps.
+pub fn generators_apollonian() -> [Generator; 4] {
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    let i = Cpx::i();
+    let a = Matrix2::new(one, zero, -2.0 * i, one);
+    let b = Matrix2::new(one - i, one, one, one + i);
+    let circle = |m00: f64, m01: Cpx, m11: f64| {
+        Circle(Matrix2::new(m00.into(), m01, m01.conj(), m11.into()))
+    };
+    [
+        Generator {
+            matrix: a,
+            circle: circle(0.0, i, 0.0),
+        },
+        Generator {
+            matrix: b,
+            circle: circle(-1.0, one - i, -1.0),
+        },
+        Generator {
+            matrix: inv(&a),
+            circle: circle(-4.0, -i, 0.0),
+        },
+        Generator {
+            matrix: inv(&b),
+            circle: circle(1.0, one + i, 1.0),
+        },
+    ]
+}
+
+/// Returns matrices a and b with tr a = `ta`, tr b = `tb`, and
+/// tr ab = `tab`, normalized as in `generators`.  The traces must satisfy
+/// ta^2 + tb^2 + tab^2 = ta tb tab, which makes the commutator parabolic.
+pub(crate) fn matrices_for_traces(ta: Cpx, tb: Cpx, tab: Cpx) -> (Matrix2<Cpx>, Matrix2<Cpx>) {
     let i = Cpx::i();
     let z0 = (tab - 2.0) * tb / (tb * tab - 2.0 * ta + 2.0 * i * tab);
     let htb = 0.5 * tb;
     let htab = 0.5 * tab;
     let b = Matrix2::new(htb - i, htb, htb, htb + i);
     let ab = Matrix2::new(htab, (htab - 1.0) / z0, (htab + 1.0) * z0, htab);
-    let bi = inv(&b);
-    let a = ab * bi;
+    (ab * inv(&b), b)
+}
+
+/// Returns the quadruple [a,b,a^{-1},b^{-1}], with circles chosen as in
+/// `generators`.  The matrices must have determinant 1, and the commutator
+/// aba^{-1}b^{-1} must be parabolic with trace -2.
+pub fn generators_from_matrices(a: Matrix2<Cpx>, b: Matrix2<Cpx>) -> [Generator; 4] {
     let ai = inv(&a);
+    let bi = inv(&b);
     let k1 = bi * a * b * ai;
     let k2 = a * b * ai * bi;
     let k3 = b * ai * bi * a;
@@ -80,6 +543,101 @@ pub fn generators(ta: Cpx, tb: Cpx) -> [Generator; 4] {
     ]
 }
 
+/// Like `generators`, but with tr aba^{-1}b^{-1} = `tabab` rather than -2,
+/// which is Grandma's four-alarm recipe from p261 of Indra's Pearls.  tr ab
+/// is the root of tab^2 - ta tb tab + ta^2 + tb^2 - 2 - `tabab` = 0 chosen
+/// as in `generators`, and for `tabab` = -2 this gives the same generators.
+/// With a loxodromic commutator, the quotient is a torus with a hole rather
+/// than a puncture.  With an elliptic commutator, the group is not free.
+/// If the commutator has finite order, as for `generators_orbifold`, the
+/// explorers skip the words that the relation shortens; otherwise the words
+/// that they explore run around the relation forever, and such groups can
+/// only be studied with `elements` and `systole`.  The circles
+/// are found from the commutators as in `generators_from_matrices`, which
+/// also gives usable circles when the commutator is loxodromic.
+pub fn generators_with_commutator(ta: Cpx, tb: Cpx, tabab: Cpx) -> [Generator; 4] {
+    if (tabab + 2.0).norm() < PARABOLIC_TOLERANCE {
+        return generators(ta, tb);
+    }
+    let i = Cpx::i();
+    let c0 = ta * ta + tb * tb - 2.0 - tabab;
+    let c1 = ta * tb;
+    let tab = 0.5 * (c1 - (c1 * c1 - 4.0 * c0).sqrt());
+    let q = (2.0 - tabab).sqrt();
+    let r = (2.0 + tabab).sqrt();
+    let z0 = (tab - 2.0) * (tb + r) / (tb * tab - 2.0 * ta + i * q * tab);
+    let a = Matrix2::new(
+        0.5 * ta,
+        (ta * tab - 2.0 * tb + 2.0 * i * q) / ((2.0 * tab + 4.0) * z0),
+        (ta * tab - 2.0 * tb - 2.0 * i * q) * z0 / (2.0 * tab - 4.0),
+        0.5 * ta,
+    );
+    let b = Matrix2::new(
+        0.5 * (tb - i * q),
+        (tb * tab - 2.0 * ta - i * q * tab) / ((2.0 * tab + 4.0) * z0),
+        (tb * tab - 2.0 * ta + i * q * tab) * z0 / (2.0 * tab - 4.0),
+        0.5 * (tb + i * q),
+    );
+    generators_from_matrices(a, b)
+}
+
+/// Returns the generators for the point `phi` of the Bers slice of the
+/// square punctured torus, as in `bers::BersSlice::square`.  At 0 the group
+/// is Fuchsian, with tr a = tr b = 2√2 and tr ab = 4, and the top surface of
+/// the quasifuchsian groups nearby changes holomorphically with `phi`.
+pub fn generators_bers(phi: Cpx) -> [Generator; 4] {
+    bers::BersSlice::square().generators(phi)
+}
+
+/// Returns the generators for the point `d` of the Earle slice, the groups
+/// in which a and b are conjugate, with tr ab = `d` and
+/// tr a = tr b = d / √(d - 2), which satisfy the equation for tr ab in
+/// `tab_roots`.  The transformation that conjugates a to b and b to a is
+/// then a half turn about an axis, which maps the limit set to itself, and
+/// for real `d` > 2 the group is Fuchsian and the quotient is a rhombic
+/// torus, the square one at `d` = 4.  Since `d` and 2d / (d - 2) are the two
+/// values of tr ab, they give the same group with b replaced by b^{-1}.
+pub fn generators_earle(d: Cpx) -> [Generator; 4] {
+    let t = d / (d - 2.0).sqrt();
+    let (a, b) = matrices_for_traces(t, t, d);
+    generators_from_matrices(a, b)
+}
+
+/// Returns the generators of the p/q double cusp group on the boundary of
+/// the Maskit slice, in which b and the Farey word W_{p/q} are both
+/// parabolic, as found by `maskit::cusp`.  Its limit set is made of
+/// tangent circles.  `p` and `q` must be coprime.  Panics if `q` is 0.
+pub fn generators_double_cusp(p: u32, q: u32) -> [Generator; 4] {
+    maskit::generators(maskit::cusp(p, q))
+}
+
+/// Returns the generators of the fiber group of the figure eight knot
+/// complement, a doubly degenerate group whose limit set is the whole
+/// sphere, so that the points fill the plane rather than lie on a curve.
+/// The traces are found by `steps` steps of Newton's method, as in
+/// `degenerate::fixed_traces`, and fewer steps give a group nearby, which
+/// is usually not discrete.
+pub fn generators_degenerate(steps: usize) -> [Generator; 4] {
+    use crate::degenerate::{fixed_traces, FIGURE_EIGHT, FIGURE_EIGHT_START};
+    let [ta, tb, tab] = fixed_traces(&FIGURE_EIGHT, FIGURE_EIGHT_START, steps);
+    let (a, b) = matrices_for_traces(ta, tb, tab);
+    generators_from_matrices(a, b)
+}
+
+/// Like `generators_with_commutator`, with tr aba^{-1}b^{-1} = -2 cos(π/n),
+/// so that the commutator is a rotation of order `n`.  The quotient is then
+/// a torus with a cone point of angle 2π/n rather than a puncture, which
+/// `generators` is the limit of as n grows.  The explorers skip the words
+/// with more than half of the relator (aba^{-1}b^{-1})^n in a row, which
+/// would otherwise repeat the same circles over and over.  A few words are still
+/// equal to others, since the relation can also be used in other ways, but
+/// too few for the exploration to blow up.  Panics if `n` is less than 2.
+pub fn generators_orbifold(ta: Cpx, tb: Cpx, n: usize) -> [Generator; 4] {
+    assert!(n >= 2, "the order of the commutator must be at least 2");
+    let tabab = -2.0 * (std::f64::consts::PI / n as f64).cos();
+    generators_with_commutator(ta, tb, Cpx::from(tabab))
+}
+
 /// Returns a quadruple of matrices [a,b,a^{-1},b^{-1}] such that
 /// tr a = `ta`, tr b = `tb`, and tr abab^{-1} = -2.
 pub fn generators_xx(ta: Cpx, tb: Cpx) -> [Generator; 4] {
@@ -158,3 +716,145 @@ pub fn generators_x(ta: Cpx) -> [Generator; 4] {
         },
     ]
 }
+
+/// Returns the quadruple [a,b,a^{-1},b^{-1}], where a and b generate the
+/// given cyclic groups, so that they are specified by their fixed points
+/// and multipliers.  The circles are the isometric circles, as in
+/// `seed::SeedCircles::Isometric`, and the group is a Schottky group if
+/// they are disjoint.  A transformation that fixes infinity has no
+/// isometric circle, so that is an error.
+pub fn generators_from_fixed_points(
+    a: &cyclic::CyclicGroup,
+    b: &cyclic::CyclicGroup,
+) -> Result<[Generator; 4], Error> {
+    let (a, b) = (a.matrix(1.0), b.matrix(1.0));
+    let matrices = [a, b, inv(&a), inv(&b)];
+    let generator = |i: usize| {
+        // The circle of g is the isometric circle of g^{-1}.
+        let h = ford::isometric_hemisphere(&matrices[(i + 2) % 4])
+            .ok_or(Error::DegenerateGenerators)?;
+        Ok(Generator {
+            matrix: matrices[i],
+            circle: Circle::new(h.center, h.radius),
+        })
+    };
+    let gens = [generator(0)?, generator(1)?, generator(2)?, generator(3)?];
+    check_generators(&gens)?;
+    Ok(gens)
+}
+
+/// Returns the generators of the classical Schottky group that pairs the
+/// circles of each pair, followed by their inverses in the same order, as
+/// `CircleQueue::with_generators` expects.  The generator for a pair
+/// (C, D) maps C onto D, and the outside of C onto the disk bounded by D,
+/// so its circle is D and the circle of its inverse is C.  The disk bounded
+/// by a circle is the side on which (z̄ 1) C (z 1)^T is negative, which is
+/// the inside for circles made by `Circle::new`.  The group is a Schottky
+/// group, with a limit set that is a Cantor set, if the disks are disjoint.
+pub fn generators_schottky(circle_pairs: &[(Circle, Circle)]) -> Vec<Generator> {
+    // The normalizers take each circle to the unit circle and its disk to
+    // the unit disk, and z -> 1/z takes the outside of the unit circle to
+    // the inside.
+    let i = Cpx::i();
+    let flip = Matrix2::new(Cpx::from(0.0), i, i, Cpx::from(0.0));
+    let matrices: Vec<Matrix2<Cpx>> = circle_pairs
+        .iter()
+        .map(|(c, d)| inv(&fuchsian::disk_normalizer(d)) * flip * fuchsian::disk_normalizer(c))
+        .collect();
+    let forward = matrices
+        .iter()
+        .zip(circle_pairs)
+        .map(|(m, (_, d))| Generator {
+            matrix: *m,
+            circle: *d,
+        });
+    let backward = matrices
+        .iter()
+        .zip(circle_pairs)
+        .map(|(m, (c, _))| Generator {
+            matrix: inv(m),
+            circle: *c,
+        });
+    forward.chain(backward).collect()
+}
+
+/// Returns the quadruple [a,b,a^{-1},b^{-1}] for the group in the Riley
+/// slice with parameter `c`, generated by the parabolic transformations
+/// z -> z + 1 and z -> z / (cz + 1), so that tr ab = 2 + c.  The circles
+/// of a are the lines Re z = ±1/2, which a translates into each other, and
+/// those of b are its isometric circles, which touch at its fixed point 0.
+/// These are disjoint apart from that point when c is real and at least
+/// 4, and they still give good pictures in much of the rest of the slice.
+/// Lines would never get smaller under powers of a, so everything is
+/// conjugated by the Cayley transform z -> (z - i) / (z + i), which takes
+/// the fixed points ∞ and 0 to 1 and -1, and the real axis, which is the
+/// limit set when c is at least 4, to the unit circle.
+pub fn generators_riley(c: Cpx) -> [Generator; 4] {
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    let a = Matrix2::new(one, one, zero, one);
+    let b = Matrix2::new(one, zero, c, one);
+    // The line Re z = x, as a circle.
+    let line = |x: f64| Circle(Matrix2::new(zero, one, one, Cpx::from(-2.0 * x)));
+    let radius = 1.0 / c.norm();
+    let gens = [
+        Generator {
+            matrix: a,
+            circle: line(0.5),
+        },
+        Generator {
+            matrix: b,
+            circle: Circle::new(1.0 / c, radius),
+        },
+        Generator {
+            matrix: inv(&a),
+            circle: line(-0.5),
+        },
+        Generator {
+            matrix: inv(&b),
+            circle: Circle::new(-1.0 / c, radius),
+        },
+    ];
+    conjugate::conjugate(&gens, &cayley())
+}
+
+/// Returns the matrix of the Cayley transform z -> (z - i) / (z + i), of
+/// determinant 1, which takes ∞ and 0 to 1 and -1 and the real axis to the
+/// unit circle.
+pub(crate) fn cayley() -> Matrix2<Cpx> {
+    let (one, i) = (Cpx::from(1.0), Cpx::i());
+    Matrix2::new(one, -i, one, i) / (2.0 * i).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_letters_accepts_generators_and_inverses() {
+        assert_eq!(parse_letters("abAB", 2), Some(vec![0, 1, 2, 3]));
+        assert_eq!(parse_letters("acC", 3), Some(vec![0, 2, 5]));
+        assert_eq!(parse_letters("", 2), Some(vec![]));
+        assert_eq!(parse_letters("abc", 2), None);
+        assert_eq!(parse_letters("a b", 2), None);
+    }
+
+    #[test]
+    fn parse_word_multiplies_out_the_word() {
+        let gens = generators(Cpx::new(1.91, 0.05), Cpx::new(1.91, 0.05));
+        let [a, b, ai, bi] = gens.each_ref().map(|g| g.matrix);
+        let m = parse_word("abAB", &gens).unwrap();
+        assert!((m - a * b * ai * bi).norm() < 1e-12);
+        let m = parse_word("aBBA", &gens).unwrap();
+        assert!((m - a * bi * bi * ai).norm() < 1e-12);
+        assert_eq!(parse_word("", &gens), Some(Matrix2::identity()));
+        assert_eq!(parse_word("abc", &gens), None);
+    }
+
+    #[test]
+    fn word_matrix_rejects_letters_out_of_range() {
+        let gens = generators(Cpx::new(1.91, 0.05), Cpx::new(1.91, 0.05));
+        assert!(word_matrix(&[0, 3], &gens).is_some());
+        assert_eq!(word_matrix(&[0, 4], &gens), None);
+    }
+}
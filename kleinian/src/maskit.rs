@@ -0,0 +1,304 @@
+//! The Maskit slice, the groups with tr b = 2 in which the limit set is
+//! made of circles chained together by a, with tr a = μ.  The slice is
+//! the region of μ for which the group is discrete, free, and b is the only
+//! accidental parabolic, and it is invariant under μ ↦ μ ± 2i, which gives
+//! the same group with a replaced by ab^{∓1}.  With tr b = 2, the equation
+//! for tr ab in `tab_roots` has the roots μ ± 2i, and the slice uses
+//! tr ab = μ - 2i, as `generators` does.
+//!
+//! The boundary of the slice is the closure of its cusps.  At the p/q cusp,
+//! the Farey word W_{p/q} of `pleating` is parabolic as well as b, so the
+//! group is a double cusp group, and its limit set is made of circles
+//! packed together, as in the pictures of Indra's Pearls.  The trace of
+//! W_{p/q} is a polynomial of degree q in μ, and the cusp is the root of
+//! tr W_{p/q} = 2 with the largest real part.
+
+use crate::algebra::inv;
+use crate::{generators_from_matrices, matrices_for_traces, Cpx, Generator};
+
+/// The most iterations of the Aberth method.
+const MAX_ITERATIONS: usize = 1000;
+
+/// The Aberth method stops when no root moves by more than this, relative
+/// to its size.
+const ROOT_TOLERANCE: f64 = 1e-13;
+
+/// The most steps of Newton's method taken by `find_cusp`.
+const MAX_NEWTON_STEPS: usize = 100;
+
+/// Newton's method stops when a step is smaller than this, relative to the
+/// size of the root.
+const NEWTON_TOLERANCE: f64 = 1e-15;
+
+/// Returns [tr a, tr b, tr ab] for the point μ of the slice.
+pub fn traces(mu: Cpx) -> [Cpx; 3] {
+    [mu, Cpx::from(2.0), mu - Cpx::new(0.0, 2.0)]
+}
+
+/// Returns the generators for the point μ of the slice, with the matrices
+/// normalized as in `generators`.
+pub fn generators(mu: Cpx) -> [Generator; 4] {
+    // At the 1/1 cusp, μ = 2 + 2i, tr ab = 2 and `matrices_for_traces`
+    // divides 0 by 0, so near it the matrices come from μ - 2i instead,
+    // with a replaced by ab^{-1}, which has the same commutator.
+    if (mu - Cpx::new(2.0, 2.0)).norm() < 1.0 {
+        let [ta, tb, tab] = traces(mu - Cpx::new(0.0, 2.0));
+        let (a, b) = matrices_for_traces(ta, tb, tab);
+        return generators_from_matrices(a * inv(&b), b);
+    }
+    let [ta, tb, tab] = traces(mu);
+    let (a, b) = matrices_for_traces(ta, tb, tab);
+    generators_from_matrices(a, b)
+}
+
+/// A polynomial in μ, with the coefficient of μ^k in entry k.
+type Polynomial = Vec<Cpx>;
+
+fn add(p: &[Cpx], q: &[Cpx], sign: f64) -> Polynomial {
+    let mut sum = vec![Cpx::from(0.0); p.len().max(q.len())];
+    for (k, c) in p.iter().enumerate() {
+        sum[k] += c;
+    }
+    for (k, c) in q.iter().enumerate() {
+        sum[k] += sign * c;
+    }
+    sum
+}
+
+fn mul(p: &[Cpx], q: &[Cpx]) -> Polynomial {
+    let mut product = vec![Cpx::from(0.0); p.len() + q.len() - 1];
+    for (j, a) in p.iter().enumerate() {
+        for (k, b) in q.iter().enumerate() {
+            product[j + k] += a * b;
+        }
+    }
+    product
+}
+
+/// Returns the coefficients of tr W_{p/q} as a polynomial in μ, found with
+/// the same recursion as `pleating::farey_trace`.  The polynomial is
+/// monic, but for large q its coefficients are too large for its values to
+/// be computed accurately from them.  `p` and `q` must be coprime, and `q` must
+/// not be 0.  Panics if `q` is 0.
+pub fn farey_polynomial(p: u32, q: u32) -> Vec<Cpx> {
+    assert!(q != 0, "the Farey polynomial of {p}/0 is not defined");
+    let (mut l, mut m, mut r, mut s) = (0, 1, 1, 0);
+    let mu = vec![Cpx::from(0.0), Cpx::from(1.0)];
+    let two = vec![Cpx::from(2.0)];
+    let mut tl = mu.clone();
+    let mut tr = two.clone();
+    // tr ab^{-1} = tr a tr b - tr ab = μ + 2i.
+    let mut td = vec![Cpx::new(0.0, 2.0), Cpx::from(1.0)];
+    loop {
+        if (p, q) == (l, m) {
+            return tl;
+        }
+        let tm = add(&mul(&tl, &tr), &td, -1.0);
+        let (a, b) = (l + r, m + s);
+        match (p as u64 * b as u64).cmp(&(a as u64 * q as u64)) {
+            std::cmp::Ordering::Equal => return tm,
+            std::cmp::Ordering::Less => {
+                (r, s) = (a, b);
+                (tr, td) = (tm, tr);
+            }
+            std::cmp::Ordering::Greater => {
+                (l, m) = (a, b);
+                (tl, td) = (tm, tl);
+            }
+        }
+    }
+}
+
+/// Returns the roots of the monic polynomial with coefficients `poly`,
+/// with multiplicity, found by the Aberth method.  `eval` gives the value
+/// and derivative of the polynomial, which may be more accurate than the
+/// coefficients.
+fn roots<F: Fn(Cpx) -> (Cpx, Cpx)>(poly: &[Cpx], eval: F) -> Vec<Cpx> {
+    let n = poly.len() - 1;
+    // The usual starting points, spread around a circle that contains the
+    // roots, with Fujiwara's bound as its radius, at angles that avoid
+    // symmetries of the polynomial.
+    let radius = (1..=n)
+        .map(|k| 2.0 * poly[n - k].norm().powf(1.0 / k as f64))
+        .fold(0.0, f64::max);
+    let mut z: Vec<Cpx> = (0..n)
+        .map(|k| {
+            Cpx::from_polar(
+                radius,
+                0.4 + 2.0 * std::f64::consts::PI * k as f64 / n as f64,
+            )
+        })
+        .collect();
+    for _ in 0..MAX_ITERATIONS {
+        let mut moved = 0.0f64;
+        for k in 0..n {
+            let (f, df) = eval(z[k]);
+            // Dividing by the size of df first keeps the quotient from
+            // overflowing far from the roots, where the values are huge.
+            let size = df.norm();
+            let newton = (f / size) * (df.conj() / size);
+            let repulsion: Cpx = (0..n)
+                .filter(|&j| j != k)
+                .map(|j| 1.0 / (z[k] - z[j]))
+                .sum();
+            let step = newton / (1.0 - newton * repulsion);
+            if step.is_finite() {
+                z[k] -= step;
+                moved = moved.max(step.norm() / z[k].norm().max(1.0));
+            }
+        }
+        if moved < ROOT_TOLERANCE {
+            break;
+        }
+    }
+    z
+}
+
+/// Returns μ at the p/q cusp, as described above.  `p` and `q` must be
+/// coprime, and `q` must not be 0, since the 1/0 cusp is the whole slice.
+/// Panics if `q` is 0.
+pub fn cusp(p: u32, q: u32) -> Cpx {
+    assert!(q != 0, "there is no {p}/0 cusp");
+    let mut poly = farey_polynomial(p, q);
+    poly[0] -= 2.0;
+    let eval = |mu: Cpx| {
+        let (t, dt) = farey_trace_and_derivative(p, q, mu);
+        (t - 2.0, dt)
+    };
+    let best = roots(&poly, eval)
+        .into_iter()
+        .fold(Cpx::new(f64::NEG_INFINITY, 0.0), |best, z| {
+            if z.re > best.re {
+                z
+            } else {
+                best
+            }
+        });
+    find_cusp(p, q, best)
+}
+
+/// Returns tr W_{p/q} at μ, and its derivative with respect to μ, by the
+/// recursion of `pleating::farey_trace`, differentiated term by term.
+fn farey_trace_and_derivative(p: u32, q: u32, mu: Cpx) -> (Cpx, Cpx) {
+    let one = Cpx::from(1.0);
+    let zero = Cpx::from(0.0);
+    let (mut l, mut m, mut r, mut s) = (0, 1, 1, 0);
+    let (mut tl, mut tr, mut td) = (
+        (mu, one),
+        (Cpx::from(2.0), zero),
+        (mu + Cpx::new(0.0, 2.0), one),
+    );
+    loop {
+        if (p, q) == (l, m) {
+            return tl;
+        }
+        if (p, q) == (r, s) {
+            return tr;
+        }
+        let tm = (tl.0 * tr.0 - td.0, tl.1 * tr.0 + tl.0 * tr.1 - td.1);
+        let (a, b) = (l + r, m + s);
+        match (p as u64 * b as u64).cmp(&(a as u64 * q as u64)) {
+            std::cmp::Ordering::Equal => return tm,
+            std::cmp::Ordering::Less => {
+                (r, s) = (a, b);
+                (tr, td) = (tm, tr);
+            }
+            std::cmp::Ordering::Greater => {
+                (l, m) = (a, b);
+                (tl, td) = (tm, tl);
+            }
+        }
+    }
+}
+
+/// Returns the root of tr W_{p/q} = 2 found by Newton's method from
+/// `initial_guess`, which is the p/q cusp if the guess is close enough to
+/// it.  Newton's method can converge to any of the q roots, or not at all,
+/// so the result should be checked, for example with
+/// `pleating::farey_trace`, unless the guess comes from `cusp` or from a
+/// nearby cusp.  `p` and `q` must be coprime, and not both 0.
+pub fn find_cusp(p: u32, q: u32, initial_guess: Cpx) -> Cpx {
+    let mut mu = initial_guess;
+    for _ in 0..MAX_NEWTON_STEPS {
+        let (t, dt) = farey_trace_and_derivative(p, q, mu);
+        let step = (t - 2.0) / dt;
+        if !step.is_finite() {
+            break;
+        }
+        mu -= step;
+        if step.norm() <= NEWTON_TOLERANCE * mu.norm().max(1.0) {
+            break;
+        }
+    }
+    mu
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Matrix2;
+
+    /// Returns the matrix of W_{p/q} for the generators, which is
+    /// W_{l/m} W_{r/s} for the Farey neighbors l/m < p/q < r/s, starting
+    /// from W_{0/1} = a and W_{1/0} = b, as in `pleating::farey_trace`.
+    fn farey_matrix(p: u32, q: u32, gens: &[Generator; 4]) -> Matrix2<Cpx> {
+        let (mut l, mut m, mut r, mut s) = (0, 1, 1, 0);
+        let (mut wl, mut wr) = (gens[0].matrix, gens[1].matrix);
+        loop {
+            if (p, q) == (l, m) {
+                return wl;
+            }
+            let wm = wl * wr;
+            let (a, b) = (l + r, m + s);
+            match (p * b).cmp(&(a * q)) {
+                std::cmp::Ordering::Equal => return wm,
+                std::cmp::Ordering::Less => (r, s, wr) = (a, b, wm),
+                std::cmp::Ordering::Greater => (l, m, wl) = (a, b, wm),
+            }
+        }
+    }
+
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    #[test]
+    fn cusps_are_roots_of_farey_traces() {
+        for q in 1..=8 {
+            for p in (0..=q).filter(|&p| gcd(p, q) == 1) {
+                let t = crate::pleating::farey_trace(p, q, traces(cusp(p, q)));
+                assert!((t - 2.0).norm() < 1e-9, "tr W_{p}/{q} = {t}");
+            }
+        }
+        let known = [
+            (0, 1, Cpx::from(2.0)),
+            (1, 1, Cpx::new(2.0, 2.0)),
+            (1, 2, Cpx::new(3f64.sqrt(), 1.0)),
+        ];
+        for (p, q, mu) in known {
+            assert!((cusp(p, q) - mu).norm() < 1e-12, "{p}/{q}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no 1/0 cusp")]
+    fn cusp_rejects_zero_denominator() {
+        cusp(1, 0);
+    }
+
+    #[test]
+    fn double_cusp_farey_words_are_parabolic() {
+        for q in 1..=6 {
+            for p in (0..=q).filter(|&p| gcd(p, q) == 1) {
+                let gens = generators(cusp(p, q));
+                assert!(gens.iter().all(|g| g.matrix.iter().all(|z| z.is_finite())));
+                let t = farey_matrix(p, q, &gens).trace();
+                assert!((t - 2.0).norm() < 1e-9, "tr W_{p}/{q} = {t}");
+            }
+        }
+    }
+}
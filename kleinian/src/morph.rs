@@ -0,0 +1,154 @@
+//! Paths between groups, for animations.
+//!
+//! A group with parabolic commutator is determined up to conjugacy by the
+//! traces of a, b, and ab, which satisfy
+//! tr a^2 + tr b^2 + tr ab^2 = tr a tr b tr ab.
+//! Interpolating the matrices themselves leaves SL(2, C) and passes through
+//! groups with the wrong commutator, so instead the traces of a and b are
+//! interpolated, and tr ab is chosen as the root of the equation that
+//! varies continuously along the path.  The matrices with those traces are
+//! then conjugated by a transformation that moves steadily from the frame
+//! of the first group to the frame of the second, so that the limit set
+//! does not jump around.
+
+use crate::algebra::{inv, power};
+use crate::group::traces;
+use crate::{generators_from_matrices, matrices_for_traces, tab_roots, Cpx, Generator};
+use nalgebra::{Matrix2, SMatrix};
+
+/// The number of steps over which tr ab is tracked along the path.
+const STEPS: usize = 1024;
+
+/// The traces (tr a, tr b, tr ab) of a group.
+type Traces = [Cpx; 3];
+
+/// Returns the root nearest to `guess`.
+fn nearest_root(ta: Cpx, tb: Cpx, guess: Cpx) -> Cpx {
+    let [r0, r1] = tab_roots(ta, tb);
+    if (r0 - guess).norm() <= (r1 - guess).norm() {
+        r0
+    } else {
+        r1
+    }
+}
+
+/// Returns a matrix C of determinant 1 with C x C^{-1} = y for both pairs
+/// `(x, y)`, or `None` if there is no such matrix.
+fn conjugator(pairs: [(&Matrix2<Cpx>, &Matrix2<Cpx>); 2]) -> Option<Matrix2<Cpx>> {
+    // Each pair gives four linear equations C x - y C = 0 in the entries
+    // of C, and the solution is the null vector of the system.
+    let mut sys = SMatrix::<Cpx, 8, 4>::zeros();
+    for (k, (x, y)) in pairs.iter().enumerate() {
+        for i in 0..2 {
+            for j in 0..2 {
+                for p in 0..2 {
+                    sys[(4 * k + 2 * i + j, 2 * i + p)] += x[(p, j)];
+                    sys[(4 * k + 2 * i + j, 2 * p + j)] -= y[(i, p)];
+                }
+            }
+        }
+    }
+    let svd = sys.svd(false, true);
+    let v_t = svd.v_t?;
+    let k = svd.singular_values.imin();
+    let scale = svd.singular_values.max();
+    if svd.singular_values[k] > 1e-6 * scale || !scale.is_finite() {
+        return None;
+    }
+    let v = v_t.row(k).adjoint();
+    let c = Matrix2::new(v[0], v[1], v[2], v[3]);
+    let det = c.determinant();
+    (det.norm() > 1e-12 * v.norm_squared()).then(|| c / det.sqrt())
+}
+
+/// A path from one group to another, parameterized by `t` from 0 to 1.
+/// Both groups must have parabolic commutators, as the groups returned by
+/// `generators` and `deform::quakebend` do.
+#[derive(Clone, Debug)]
+pub struct GroupPath {
+    from: Traces,
+    to: Traces,
+    /// A detour added to tr a, which goes around a branch point of tr ab if
+    /// the straight path would arrive at the wrong root.
+    bump: Cpx,
+    /// tr ab at `STEPS + 1` evenly spaced times.
+    tab: Vec<Cpx>,
+    /// The conjugation taking the normalized matrices at time 0 to the
+    /// first group, and the ratio between it and the one for time 1.
+    frame: Matrix2<Cpx>,
+    motion: Matrix2<Cpx>,
+}
+
+impl GroupPath {
+    /// Returns the path between the groups, or `None` if their generators
+    /// are degenerate.  If the straight path in tr a and tr b would arrive
+    /// at the other value of tr ab, the path makes a detour in tr a around
+    /// a branch point.
+    pub fn new(from: &[Generator; 4], to: &[Generator; 4]) -> Option<Self> {
+        let (start, end) = (traces(from), traces(to));
+        if !start.iter().chain(&end).all(|z| z.is_finite()) {
+            return None;
+        }
+        // The branch points lie where tr a^2 (tr b^2 - 4) = 4 tr b^2, so
+        // detours on the scale of the traces can go around them.
+        let size = (end[0] - start[0]).norm() + start[0].norm() + start[1].norm() + 1.0;
+        let bumps = [0.0, 1.0, -1.0, 2.0, -2.0, 4.0, -4.0]
+            .into_iter()
+            .flat_map(|k| [Cpx::new(0.0, k * size), Cpx::new(k * size, 0.0)]);
+        let mut path = None;
+        for bump in bumps {
+            let p = GroupPath {
+                from: start,
+                to: end,
+                bump,
+                tab: Vec::new(),
+                frame: Matrix2::identity(),
+                motion: Matrix2::identity(),
+            };
+            let mut tab = Vec::with_capacity(STEPS + 1);
+            tab.push(start[2]);
+            for i in 1..=STEPS {
+                let (ta, tb) = p.traces_ab(i as f64 / STEPS as f64);
+                tab.push(nearest_root(ta, tb, tab[i - 1]));
+            }
+            let last = tab[STEPS];
+            if (last - end[2]).norm() <= 1e-6 * (1.0 + end[2].norm()) {
+                path = Some(GroupPath { tab, ..p });
+                break;
+            }
+        }
+        let mut path = path?;
+        let frame_at = |gens: &[Generator; 4], t: Traces| {
+            let (a, b) = matrices_for_traces(t[0], t[1], t[2]);
+            conjugator([(&a, &gens[0].matrix), (&b, &gens[1].matrix)])
+        };
+        let f0 = frame_at(from, start)?;
+        let f1 = frame_at(to, end)?;
+        path.frame = f0;
+        path.motion = inv(&f0) * f1;
+        Some(path)
+    }
+    /// Returns tr a and tr b at time `t`.
+    fn traces_ab(&self, t: f64) -> (Cpx, Cpx) {
+        let lerp = |i: usize| self.from[i] + t * (self.to[i] - self.from[i]);
+        (lerp(0) + 4.0 * t * (1.0 - t) * self.bump, lerp(1))
+    }
+    /// Returns the traces of a, b, and ab at time `t`.
+    pub fn traces_at(&self, t: f64) -> [Cpx; 3] {
+        let t = t.clamp(0.0, 1.0);
+        let (ta, tb) = self.traces_ab(t);
+        let x = t * STEPS as f64;
+        let i = (x.floor() as usize).min(STEPS - 1);
+        let guess = self.tab[i] + (x - i as f64) * (self.tab[i + 1] - self.tab[i]);
+        [ta, tb, nearest_root(ta, tb, guess)]
+    }
+    /// Returns the generators of the group at time `t`, which are the
+    /// generators of the first group at time 0 and of the second at time 1.
+    pub fn generators_at(&self, t: f64) -> [Generator; 4] {
+        let [ta, tb, tab] = self.traces_at(t);
+        let (a, b) = matrices_for_traces(ta, tb, tab);
+        let c = self.frame * power(&self.motion, t.clamp(0.0, 1.0));
+        let ci = inv(&c);
+        generators_from_matrices(c * a * ci, c * b * ci)
+    }
+}
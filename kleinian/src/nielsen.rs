@@ -0,0 +1,80 @@
+//! Changes of the pair of generators of a group.  The same group is
+//! generated by many pairs, and every pair can be reached from any other by
+//! a sequence of Nielsen moves.  Each choice of pair gives different
+//! circles for the generators, and so a different picture while exploring,
+//! even though the limit set is the same.
+//!
+//! The trace of the commutator is the same for every pair, so a group with
+//! parabolic commutator stays that way, and the traces of the new pair can
+//! be computed from the traces of the old one with the identity
+//! tr xy + tr x^{-1}y = tr x tr y.
+
+use crate::algebra::inv;
+use crate::render::UnknownName;
+use crate::{generators_from_matrices, Cpx, Generator};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NielsenMove {
+    /// Exchanges a and b.
+    Swap,
+    /// Replaces a by a^{-1}.
+    InvertA,
+    /// Replaces b by b^{-1}.
+    InvertB,
+    /// Replaces a by ab.
+    MultiplyA,
+    /// Replaces b by ba.
+    MultiplyB,
+}
+
+impl NielsenMove {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NielsenMove::Swap => "swap",
+            NielsenMove::InvertA => "invert-a",
+            NielsenMove::InvertB => "invert-b",
+            NielsenMove::MultiplyA => "multiply-a",
+            NielsenMove::MultiplyB => "multiply-b",
+        }
+    }
+    /// Returns the traces (tr a, tr b, tr ab) of the new generators, given
+    /// those of the old ones.
+    pub fn apply_traces(&self, [ta, tb, tab]: [Cpx; 3]) -> [Cpx; 3] {
+        match self {
+            NielsenMove::Swap => [tb, ta, tab],
+            NielsenMove::InvertA | NielsenMove::InvertB => [ta, tb, ta * tb - tab],
+            // tr ab^2 = tr b tr ab - tr a
+            NielsenMove::MultiplyA => [tab, tb, tb * tab - ta],
+            // tr aba = tr a tr ab - tr b
+            NielsenMove::MultiplyB => [ta, tab, ta * tab - tb],
+        }
+    }
+    /// Returns the new generators, with circles chosen as in
+    /// `generators_from_matrices`, so the commutator must be parabolic.
+    pub fn apply(&self, gens: &[Generator; 4]) -> [Generator; 4] {
+        let (a, b) = (gens[0].matrix, gens[1].matrix);
+        let (a, b) = match self {
+            NielsenMove::Swap => (b, a),
+            NielsenMove::InvertA => (inv(&a), b),
+            NielsenMove::InvertB => (a, inv(&b)),
+            NielsenMove::MultiplyA => (a * b, b),
+            NielsenMove::MultiplyB => (a, b * a),
+        };
+        generators_from_matrices(a, b)
+    }
+}
+
+impl FromStr for NielsenMove {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "swap" => Ok(NielsenMove::Swap),
+            "invert-a" => Ok(NielsenMove::InvertA),
+            "invert-b" => Ok(NielsenMove::InvertB),
+            "multiply-a" => Ok(NielsenMove::MultiplyA),
+            "multiply-b" => Ok(NielsenMove::MultiplyB),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Splitting the exploration of a group into chunks, so that renders too
+//! large for one machine can be shared between several.  The words are
+//! grouped by their first few letters, and the groups are listed in the
+//! order in which their points occur along the limit set and divided into
+//! chunks of consecutive groups.  Processes that are given the same
+//! generators and `Partition` list the same groups, so each can generate
+//! its own chunk without talking to the others, into a file of points as
+//! in `stream` or into a `Density`.  The results are merged afterwards
+//! with `merge_points` or `Density::add`.
+
+use crate::depth_first::{Branch, DepthFirst};
+use crate::stream::{write_header, write_point, write_points, PointReader};
+use crate::window::CoordTransform;
+use crate::{Cpx, Error, Generator};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+/// Identifies density files, and changes whenever their format does.
+const DENSITY_MAGIC: &[u8; 8] = b"KLDEN001";
+
+/// A division of the words into chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Partition {
+    /// The number of letters by which the words are grouped.  There are
+    /// 4·3^(depth - 1) groups, fewer for elliptic generators or if some
+    /// circles are already small enough, and they are divided evenly by
+    /// number, so the depth should be large enough that there are many
+    /// groups in each chunk.
+    pub depth: usize,
+    pub num_chunks: usize,
+}
+
+impl Partition {
+    /// Returns the groups of words, as the branches for their prefixes, in
+    /// reverse order.  Words that become small enough before they have
+    /// `depth` letters are groups by themselves.
+    fn groups(&self, explorer: &DepthFirst) -> Result<Vec<Branch>, Error> {
+        let mut stack = explorer.roots();
+        let mut groups = Vec::new();
+        while let Some(branch) = stack.pop() {
+            // A branch that is visited either has a point, or pushes its
+            // children and is not a group itself.
+            if branch.length >= self.depth || explorer.visit(&branch, &mut stack)?.is_some() {
+                groups.push(branch);
+            }
+        }
+        groups.reverse();
+        Ok(groups)
+    }
+    /// Returns the branches of the `chunk`th chunk, in reverse order, so
+    /// that they are popped from a stack in order.
+    fn chunk(&self, explorer: &DepthFirst, chunk: usize) -> Result<Vec<Branch>, Error> {
+        assert!(chunk < self.num_chunks, "chunk out of range");
+        let mut groups = self.groups(explorer)?;
+        let n = groups.len();
+        let (start, end) = (
+            n * chunk / self.num_chunks,
+            n * (chunk + 1) / self.num_chunks,
+        );
+        // The groups are in reverse order, as are the branches returned.
+        groups.truncate(n - start);
+        Ok(groups.split_off(n - end))
+    }
+    /// Writes the points of the `chunk`th chunk, numbered from 0, in the
+    /// format of `stream::generate_points_to`, with the same `min_radius`.
+    /// Merging the files for all of the chunks, in order, gives the same
+    /// points in the same order as `generate_points_to`.  Returns the
+    /// number of points written.  Errors in exploring the group are
+    /// returned as `io::Error`s wrapping an `Error`.
+    pub fn generate_chunk_to<W: Write>(
+        &self,
+        gens: &[Generator; 4],
+        min_radius: f64,
+        chunk: usize,
+        out: W,
+    ) -> io::Result<usize> {
+        let explorer = DepthFirst::new(gens, min_radius).map_err(io::Error::other)?;
+        let stack = self.chunk(&explorer, chunk).map_err(io::Error::other)?;
+        let mut out = BufWriter::new(out);
+        write_header(&mut out)?;
+        let count = write_points(&explorer, stack, &mut out)?;
+        out.flush()?;
+        Ok(count)
+    }
+    /// Counts the points of the `chunk`th chunk that land in each pixel of
+    /// a `width` by `height` image.  The densities of all of the chunks add
+    /// up to that of the points of `stream::generate_points_to`.
+    pub fn chunk_density(
+        &self,
+        gens: &[Generator; 4],
+        min_radius: f64,
+        chunk: usize,
+        trans: &CoordTransform,
+        width: usize,
+        height: usize,
+    ) -> Result<Density, Error> {
+        let explorer = DepthFirst::new(gens, min_radius)?;
+        let mut stack = self.chunk(&explorer, chunk)?;
+        let mut density = Density::new(width, height);
+        while let Some(branch) = stack.pop() {
+            if let Some(z) = explorer.visit(&branch, &mut stack)? {
+                density.add_point(z, trans);
+            }
+        }
+        Ok(density)
+    }
+}
+
+/// Concatenates the files of points for the chunks, which must be given in
+/// order, into a single file of points.  Returns the number of points
+/// written.
+pub fn merge_points<R: Read, W: Write, I: IntoIterator<Item = R>>(
+    inputs: I,
+    out: W,
+) -> io::Result<usize> {
+    let mut out = BufWriter::new(out);
+    write_header(&mut out)?;
+    let mut count = 0;
+    for input in inputs {
+        for z in PointReader::new(input)? {
+            write_point(&mut out, z?)?;
+            count += 1;
+        }
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+/// The number of points that land in each pixel of an image, in the same
+/// layout as `render::density`, so that it can be converted with
+/// `render::tone_map`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Density {
+    pub width: usize,
+    pub height: usize,
+    pub counts: Vec<f32>,
+}
+
+impl Density {
+    /// Returns an empty `width` by `height` image.
+    pub fn new(width: usize, height: usize) -> Self {
+        Density {
+            width,
+            height,
+            counts: vec![0.0; width * height],
+        }
+    }
+    /// Counts the point, if it lands in the image.
+    pub fn add_point(&mut self, z: Cpx, trans: &CoordTransform) {
+        if let Some((x, y)) = trans.apply_within(&z, self.width, self.height) {
+            self.counts[(self.height - 1 - y) * self.width + x] += 1.0;
+        }
+    }
+    /// Adds the counts of another image of the same size.
+    pub fn add(&mut self, other: &Density) {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "densities have different sizes"
+        );
+        for (c, d) in self.counts.iter_mut().zip(&other.counts) {
+            *c += d;
+        }
+    }
+    /// Writes the image as an 8 byte header, the width and height as
+    /// little endian 32-bit integers, and the counts as little endian
+    /// 32-bit floats.
+    pub fn write_to<W: Write>(&self, out: W) -> io::Result<()> {
+        let mut out = BufWriter::new(out);
+        out.write_all(DENSITY_MAGIC)?;
+        for n in [self.width, self.height] {
+            let n = u32::try_from(n).map_err(io::Error::other)?;
+            out.write_all(&n.to_le_bytes())?;
+        }
+        for c in &self.counts {
+            out.write_all(&c.to_le_bytes())?;
+        }
+        out.flush()
+    }
+    /// Reads an image written by `write_to`.
+    pub fn read_from<R: Read>(input: R) -> io::Result<Self> {
+        let mut input = BufReader::new(input);
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != DENSITY_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a density file",
+            ));
+        }
+        let mut word = [0; 4];
+        input.read_exact(&mut word)?;
+        let width = u32::from_le_bytes(word) as usize;
+        input.read_exact(&mut word)?;
+        let height = u32::from_le_bytes(word) as usize;
+        let mut density = Density::new(width, height);
+        for c in &mut density.counts {
+            input.read_exact(&mut word)?;
+            *c = f32::from_le_bytes(word);
+        }
+        Ok(density)
+    }
+}
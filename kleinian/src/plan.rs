@@ -0,0 +1,323 @@
+//! Choosing how to draw a limit set.  The ways of drawing in this crate
+//! trade time, memory, and quality differently: a chaos game gives a rough
+//! preview almost at once, the priority queue of `CircleQueue` spreads a
+//! fixed number of points evenly over the limit set but stores all of
+//! them, `raster::rasterize` marks every pixel that the limit set passes
+//! through but has to visit many circles to do so, and drawing the image
+//! in bands does the same without holding the whole image in memory.
+//! `LimitSet::auto` picks one of them, and its limits, from the size of
+//! the image and the time and memory that may be spent on it.
+//!
+//! The costs are rough figures for a single core of an ordinary machine.
+//! They decide which strategy is used, but they are not a promise, since
+//! the number of circles that a group needs depends on the group.
+
+use crate::conjugate::SplitMix64;
+use crate::cyclic::mobius;
+use crate::stream::rasterize_points;
+use crate::window::{window_transform, CoordTransform};
+use crate::{check_generators, raster, Cpx, Error, Generator};
+use std::fmt;
+
+/// The number of points used to fit the window when the target has none.
+const FIT_SAMPLE: usize = 10000;
+
+/// The time taken by the chaos game for each point, in seconds.
+const CHAOS_SECONDS_PER_POINT: f64 = 3e-8;
+
+/// The time taken by the priority queue for each circle, in seconds.
+const QUEUE_SECONDS_PER_CIRCLE: f64 = 2e-6;
+
+/// The memory used by the priority queue for each circle, in bytes,
+/// counting the words of the circles and the points returned.
+const QUEUE_BYTES_PER_CIRCLE: usize = 160;
+
+/// The time taken by `raster::rasterize` for each circle, in seconds.
+const RASTER_SECONDS_PER_CIRCLE: f64 = 5e-8;
+
+/// The number of circles that `raster::rasterize` is expected to visit for
+/// each pixel of the image.  Groups near the boundary of the deformation
+/// space can need many more.
+const RASTER_CIRCLES_PER_PIXEL: usize = 8;
+
+/// Rasterizing stops after this many circles per pixel, which only matters
+/// if the group is far from discrete.
+const RASTER_MAX_CIRCLES_PER_PIXEL: usize = 64;
+
+/// The priority queue is only used if it can afford at least this many
+/// circles per pixel; otherwise the image would be sparser than that of a
+/// chaos game with the same budget.
+const QUEUE_MIN_CIRCLES_PER_PIXEL: f64 = 0.25;
+
+/// The number of steps of the chaos game that are not drawn, while the
+/// point approaches the limit set.
+const CHAOS_BURN_IN: usize = 64;
+
+/// Transformations whose traces are within this of a real number between
+/// -2 and 2 are treated as elliptic, whose fixed points are not in the
+/// limit set.
+const ELLIPTIC_TOLERANCE: f64 = 1e-9;
+
+/// What is to be drawn, and the resources that may be used to draw it.
+#[derive(Clone, Copy)]
+pub struct Target {
+    pub width: usize,
+    pub height: usize,
+    /// The window.  If this is `None`, the window is fitted to a sample of
+    /// the points, as in `window::window_transform`.
+    pub view: Option<CoordTransform>,
+    /// The time that may be spent, in seconds.
+    pub time_budget: f64,
+    /// The memory that may be used, in bytes.
+    pub memory_budget: usize,
+}
+
+impl Target {
+    /// Returns a target with a time budget of a second, and enough memory
+    /// for anything that could be done in that time.
+    pub fn new(width: usize, height: usize) -> Self {
+        Target {
+            width,
+            height,
+            view: None,
+            time_budget: 1.0,
+            memory_budget: usize::MAX,
+        }
+    }
+}
+
+/// A way of drawing the limit set, with the limits that keep it within a
+/// budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// A random walk through the group, which marks the images of a point
+    /// under `num_points` random words.
+    ChaosGame { num_points: usize },
+    /// Explores the group with a `CircleQueue` until there are
+    /// `num_circles` circles, and marks their centers.
+    Queue { num_circles: usize },
+    /// `raster::rasterize`, stopping after `max_circles` circles.
+    DepthFirst { max_circles: usize },
+    /// `raster::rasterize_rows` on bands of `band_rows` rows of the image,
+    /// one at a time, stopping after `max_circles` circles in each band.
+    Tiled {
+        band_rows: usize,
+        max_circles: usize,
+    },
+}
+
+impl Strategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::ChaosGame { .. } => "chaos-game",
+            Strategy::Queue { .. } => "queue",
+            Strategy::DepthFirst { .. } => "depth-first",
+            Strategy::Tiled { .. } => "tiled",
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::ChaosGame { num_points } => {
+                write!(f, "{} with {} points", self.as_str(), num_points)
+            }
+            Strategy::Queue { num_circles } => {
+                write!(f, "{} with {} circles", self.as_str(), num_circles)
+            }
+            Strategy::DepthFirst { max_circles } => {
+                write!(f, "{} with at most {} circles", self.as_str(), max_circles)
+            }
+            Strategy::Tiled {
+                band_rows,
+                max_circles,
+            } => write!(
+                f,
+                "{} in bands of {} rows with at most {} circles each",
+                self.as_str(),
+                band_rows,
+                max_circles
+            ),
+        }
+    }
+}
+
+/// The choice made by `LimitSet::auto`, which `LimitSet::render` carries
+/// out.
+#[derive(Clone, Copy)]
+pub struct Plan {
+    pub strategy: Strategy,
+    pub trans: CoordTransform,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// The limit set of a group, to be drawn in whichever way suits the
+/// target.
+pub struct LimitSet {
+    gens: [Generator; 4],
+}
+
+impl LimitSet {
+    pub fn new(gens: [Generator; 4]) -> Self {
+        LimitSet { gens }
+    }
+    pub fn generators(&self) -> &[Generator; 4] {
+        &self.gens
+    }
+    fn copy_generators(&self) -> [Generator; 4] {
+        std::array::from_fn(|i| Generator {
+            matrix: self.gens[i].matrix,
+            circle: self.gens[i].circle,
+        })
+    }
+    /// Chooses how to draw the limit set for the target.  The image is
+    /// drawn in bands if it does not fit in memory as a whole, and
+    /// otherwise by `raster::rasterize` if there is time to, by the
+    /// priority queue if there is time and memory for enough circles, and
+    /// by a chaos game if not.  If the target has no window, one is fitted
+    /// to a sample of the points, which takes some time of its own.
+    pub fn auto(&self, target: &Target) -> Result<Plan, Error> {
+        check_generators(&self.gens)?;
+        let (width, height) = (target.width, target.height);
+        let trans = match target.view {
+            Some(trans) => trans,
+            None => {
+                let pts = crate::generate_points(self.copy_generators(), FIT_SAMPLE)?;
+                window_transform(&pts, width, height)?
+            }
+        };
+        let pixels = width * height;
+        let budget = target.time_budget.max(0.0);
+        let affordable = |seconds: f64| (budget / seconds).min(usize::MAX as f64) as usize;
+        let max_circles = RASTER_MAX_CIRCLES_PER_PIXEL * pixels;
+        let strategy = if pixels > target.memory_budget {
+            // Each band is explored from the top of the tree, so the limit
+            // on circles applies to each band, as described for
+            // `Strategy::Tiled`.  Each band gets the limit for the whole
+            // image, since a band through the densest part of the limit set
+            // can need nearly as many circles as the whole image, and the
+            // limit is only there in case the group is far from discrete.
+            Strategy::Tiled {
+                band_rows: (target.memory_budget / width.max(1)).clamp(1, height.max(1)),
+                max_circles,
+            }
+        } else if affordable(RASTER_SECONDS_PER_CIRCLE) >= RASTER_CIRCLES_PER_PIXEL * pixels {
+            Strategy::DepthFirst {
+                max_circles: affordable(RASTER_SECONDS_PER_CIRCLE).min(max_circles),
+            }
+        } else {
+            let num_circles = affordable(QUEUE_SECONDS_PER_CIRCLE)
+                .min((target.memory_budget - pixels) / QUEUE_BYTES_PER_CIRCLE);
+            if num_circles as f64 >= QUEUE_MIN_CIRCLES_PER_PIXEL * pixels as f64 {
+                Strategy::Queue { num_circles }
+            } else {
+                Strategy::ChaosGame {
+                    num_points: affordable(CHAOS_SECONDS_PER_POINT).max(1),
+                }
+            }
+        };
+        Ok(Plan {
+            strategy,
+            trans,
+            width,
+            height,
+        })
+    }
+    /// Draws the limit set as planned, into a mask in the layout of
+    /// `raster::rasterize`.  A tiled plan is drawn band by band, but the
+    /// bands are put together, so use `render_bands` to keep to the memory
+    /// budget.
+    pub fn render(&self, plan: &Plan) -> Result<Vec<bool>, Error> {
+        let mut mask = Vec::with_capacity(plan.width * plan.height);
+        self.render_bands(plan, |_, band| mask.extend_from_slice(band))?;
+        Ok(mask)
+    }
+    /// Draws the limit set as planned, calling `f` with the index of the
+    /// first row and the mask of each band of rows, from the top of the
+    /// image down.  Plans that are not tiled are drawn as a single band.
+    pub fn render_bands<F: FnMut(usize, &[bool])>(
+        &self,
+        plan: &Plan,
+        mut f: F,
+    ) -> Result<(), Error> {
+        let (width, height, trans) = (plan.width, plan.height, &plan.trans);
+        match plan.strategy {
+            Strategy::ChaosGame { num_points } => {
+                let pts = chaos_game(&self.gens, num_points)?;
+                f(0, &rasterize_points(pts, trans, width, height));
+            }
+            Strategy::Queue { num_circles } => {
+                let pts = crate::generate_points(self.copy_generators(), num_circles)?;
+                f(0, &rasterize_points(pts, trans, width, height));
+            }
+            Strategy::DepthFirst { max_circles } => {
+                f(
+                    0,
+                    &raster::rasterize(&self.gens, trans, width, height, max_circles)?,
+                );
+            }
+            Strategy::Tiled {
+                band_rows,
+                max_circles,
+            } => {
+                for first in (0..height).step_by(band_rows.max(1)) {
+                    let rows = first..(first + band_rows).min(height);
+                    f(
+                        first,
+                        &raster::rasterize_rows(
+                            &self.gens,
+                            trans,
+                            width,
+                            height,
+                            rows,
+                            max_circles,
+                        )?,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the images of a point of the limit set under `num_points`
+/// random reduced words, each one letter longer than the one before.  The
+/// point is a fixed point of one of the generators, or of ab, which is in
+/// the limit set unless the transformation is elliptic.
+fn chaos_game(gens: &[Generator; 4], num_points: usize) -> Result<Vec<Cpx>, Error> {
+    check_generators(gens)?;
+    let ab = gens[0].matrix * gens[1].matrix;
+    let start = [gens[0].matrix, gens[1].matrix, ab]
+        .iter()
+        .filter(|m| {
+            let t = m.trace();
+            t.im.abs() > ELLIPTIC_TOLERANCE || t.re.abs() > 2.0 - ELLIPTIC_TOLERANCE
+        })
+        .flat_map(crate::algebra::fixed_points)
+        .find(|z| z.is_finite())
+        .unwrap_or_else(|| gens[0].circle.center_point().affine());
+    let mut rng = SplitMix64(0);
+    let mut z = start;
+    let mut last = 0u8;
+    let mut pts = Vec::with_capacity(num_points);
+    for step in 0..num_points + CHAOS_BURN_IN {
+        // Any letter but the inverse of the last one.
+        let letter = if step == 0 {
+            (rng.next_u64() % 4) as u8
+        } else {
+            (last + 3 + (rng.next_u64() % 3) as u8) % 4
+        };
+        z = mobius(&gens[letter as usize].matrix, z);
+        last = letter;
+        if !z.is_finite() {
+            z = start;
+            continue;
+        }
+        if step >= CHAOS_BURN_IN {
+            pts.push(z);
+        }
+    }
+    Ok(pts)
+}
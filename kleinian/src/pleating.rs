@@ -0,0 +1,111 @@
+//! Rational pleating rays in a plane of parameters.
+//!
+//! Each fraction p/q corresponds to a Farey word W_{p/q} in a and b, with
+//! W_{0/1} = a, W_{1/0} = b, and the word of the mediant of two Farey
+//! neighbors being the product of their words.  The p/q pleating ray is the
+//! curve in the parameter plane along which tr W_{p/q} is real and at least
+//! 2, with the matrices of `generators`.  It ends at the p/q cusp on the
+//! boundary of the discrete groups, where W_{p/q} becomes parabolic, and
+//! the rays divide up the space of discrete groups.
+//!
+//! The traces of Farey words are computed from tr a, tr b, and tr ab using
+//! tr xy = tr x tr y - tr xy^{-1}, so this works for any recipe.
+
+use crate::Cpx;
+
+/// Returns the trace of the Farey word W_{p/q}, given the traces
+/// `[tr a, tr b, tr ab]`.  `p` and `q` must be coprime, and not both 0.
+pub fn farey_trace(p: u32, q: u32, [ta, tb, tab]: [Cpx; 3]) -> Cpx {
+    // The fractions l/m and r/s are Farey neighbors with l/m < p/q < r/s,
+    // with traces tl and tr, and td is the trace of W_{l/m} W_{r/s}^{-1}.
+    let (mut l, mut m, mut r, mut s) = (0, 1, 1, 0);
+    let (mut tl, mut tr, mut td) = (ta, tb, ta * tb - tab);
+    loop {
+        if (p, q) == (l, m) {
+            return tl;
+        }
+        if (p, q) == (r, s) {
+            return tr;
+        }
+        let tm = tl * tr - td;
+        let (a, b) = (l + r, m + s);
+        // Compare p/q with the mediant a/b.
+        match (p as u64 * b as u64).cmp(&(a as u64 * q as u64)) {
+            std::cmp::Ordering::Equal => return tm,
+            // W_{l/m} (W_{l/m} W_{r/s})^{-1} is conjugate to W_{r/s}^{-1}.
+            std::cmp::Ordering::Less => {
+                (r, s) = (a, b);
+                (tr, td) = (tm, tr);
+            }
+            // (W_{l/m} W_{r/s}) W_{r/s}^{-1} = W_{l/m}.
+            std::cmp::Ordering::Greater => {
+                (l, m) = (a, b);
+                (tl, td) = (tm, tl);
+            }
+        }
+    }
+}
+
+/// Returns line segments approximating the p/q pleating ray within the
+/// rectangle with corners `min` and `max` of a parameter plane, such as
+/// the tr a plane with tr b fixed.  `traces` gives `[tr a, tr b, tr ab]`
+/// at each point of the plane, as `group::traces` does for generators.  The rectangle is divided into `n` by `n`
+/// cells, and the ray is found in each cell by linear interpolation.
+///
+/// Other curves along which the trace is real and at least 2 are found as
+/// well, which can happen away from the discrete groups.  Jumps in the traces, such as where a recipe changes the branch
+/// of a square root, are not mistaken for crossings.
+pub fn pleating_ray<F: Fn(Cpx) -> [Cpx; 3]>(
+    p: u32,
+    q: u32,
+    traces: F,
+    min: Cpx,
+    max: Cpx,
+    n: usize,
+) -> Vec<[Cpx; 2]> {
+    let n = n.max(1);
+    let point = |i: usize, j: usize| {
+        let d = max - min;
+        min + Cpx::new(d.re * i as f64 / n as f64, d.im * j as f64 / n as f64)
+    };
+    // values[j][i] is the trace at point(i, j).
+    let values: Vec<Vec<Cpx>> = (0..=n)
+        .map(|j| {
+            (0..=n)
+                .map(|i| farey_trace(p, q, traces(point(i, j))))
+                .collect()
+        })
+        .collect();
+    // Returns where the trace is real along the edge between two corners,
+    // if it is, and the trace is at least 2 there.
+    let crossing = |(z0, w0): (Cpx, Cpx), (z1, w1): (Cpx, Cpx)| {
+        if !(w0.is_finite() && w1.is_finite()) || (w0.im > 0.0) == (w1.im > 0.0) {
+            return None;
+        }
+        if (w1 - w0).norm() > 0.5 * w0.norm().max(w1.norm()).max(2.0) {
+            return None;
+        }
+        let t = w0.im / (w0.im - w1.im);
+        let re = w0.re + t * (w1.re - w0.re);
+        (re >= 2.0).then(|| z0 + t * (z1 - z0))
+    };
+    let mut segments = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            let corner = |di: usize, dj: usize| (point(i + di, j + dj), values[j + dj][i + di]);
+            let corners = [corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1)];
+            let found: Vec<Cpx> = (0..4)
+                .filter_map(|k| crossing(corners[k], corners[(k + 1) % 4]))
+                .collect();
+            // With four crossings, the pairing is ambiguous, and either
+            // choice is fine at the resolution of the grid.
+            // When the trace is real at a corner, both of its edges find it.
+            for pair in found.chunks_exact(2) {
+                if pair[0] != pair[1] {
+                    segments.push([pair[0], pair[1]]);
+                }
+            }
+        }
+    }
+    segments
+}
@@ -0,0 +1,90 @@
+//! Export of limit points as colored point clouds, for viewers such as
+//! MeshLab, Blender, and CloudCompare.
+
+use crate::projective::ProjectivePoint;
+use crate::render::{Rgb, Style, UnknownName};
+use crate::{Cpx, LimitPoint};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// How the complex plane is placed in space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lift {
+    /// The plane z = 0, which is the boundary of upper half-space.
+    Plane,
+    /// The unit sphere, by inverse stereographic projection from the north
+    /// pole, which is the image of infinity.
+    Sphere,
+}
+
+impl FromStr for Lift {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "plane" => Ok(Lift::Plane),
+            "sphere" => Ok(Lift::Sphere),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
+
+impl Lift {
+    /// Returns the position of `z` in space, or `None` if it has none.
+    pub fn apply(&self, z: Cpx) -> Option<[f64; 3]> {
+        match self {
+            Lift::Plane if z.is_finite() => Some([z.re, z.im, 0.0]),
+            Lift::Sphere => ProjectivePoint::from_affine(z).sphere(),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the positions and colors of the points that have a position.
+fn lifted(pts: &[LimitPoint], lift: Lift, style: &Style) -> Vec<([f64; 3], Rgb)> {
+    let color = style.colorer(pts);
+    pts.iter()
+        .filter_map(|p| Some((lift.apply(p.z)?, color(p))))
+        .collect()
+}
+
+/// Writes the points as lines of the form `x y z r g b`.  The points are
+/// colored as in `render::render_rgba`.
+pub fn write_xyz<W: Write>(
+    out: &mut W,
+    pts: &[LimitPoint],
+    lift: Lift,
+    style: &Style,
+) -> io::Result<()> {
+    for ([x, y, z], [r, g, b]) in lifted(pts, lift, style) {
+        writeln!(out, "{} {} {} {} {} {}", x, y, z, r, g, b)?;
+    }
+    Ok(())
+}
+
+/// Writes the points as a binary PLY file with a vertex color for each
+/// point.  The points are colored as in `render::render_rgba`.
+pub fn write_ply<W: Write>(
+    out: &mut W,
+    pts: &[LimitPoint],
+    lift: Lift,
+    style: &Style,
+) -> io::Result<()> {
+    let vertices = lifted(pts, lift, style);
+    writeln!(out, "ply")?;
+    writeln!(out, "format binary_little_endian 1.0")?;
+    writeln!(out, "element vertex {}", vertices.len())?;
+    for c in ["x", "y", "z"] {
+        writeln!(out, "property double {}", c)?;
+    }
+    for c in ["red", "green", "blue"] {
+        writeln!(out, "property uchar {}", c)?;
+    }
+    writeln!(out, "end_header")?;
+    for (pos, rgb) in vertices {
+        for c in pos {
+            out.write_all(&c.to_le_bytes())?;
+        }
+        out.write_all(&rgb)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Filters applied to density buffers, as returned by `render::density`,
+//! before they are tone-mapped.  Bloom spreads some of the density of each
+//! pixel over its neighbors, which gives the filigree of a limit set a
+//! glow, and the unsharp mask does the opposite, which brings out fine
+//! detail.  Both work on the counts rather than on the colors, so the glow
+//! is brightest where the points are densest, as it would be for light.
+
+/// Returns the weights of a Gaussian kernel with standard deviation
+/// `radius`, from the center outwards, cut off at three standard
+/// deviations.
+fn kernel(radius: f64) -> Vec<f32> {
+    let n = (3.0 * radius).ceil() as usize;
+    (0..=n)
+        .map(|i| (-0.5 * (i as f64 / radius).powi(2)).exp() as f32)
+        .collect()
+}
+
+/// Blurs the row into `out`.  The weights are divided by their sum over
+/// the pixels within the row, so that the edges are not darkened.
+fn blur_row(row: &[f32], kernel: &[f32], out: &mut [f32]) {
+    let n = row.len();
+    for (x, o) in out.iter_mut().enumerate() {
+        let (mut sum, mut weight) = (row[x] * kernel[0], kernel[0]);
+        for (k, &w) in kernel.iter().enumerate().skip(1) {
+            if x >= k {
+                sum += row[x - k] * w;
+                weight += w;
+            }
+            if x + k < n {
+                sum += row[x + k] * w;
+                weight += w;
+            }
+        }
+        *o = sum / weight;
+    }
+}
+
+/// Blurs the `y`th row of the image into `out` in the vertical direction,
+/// with the weights normalized as in `blur_row`.
+fn blur_column(image: &[f32], width: usize, y: usize, kernel: &[f32], out: &mut [f32]) {
+    let height = image.len() / width;
+    let row = |y: usize| &image[y * width..(y + 1) * width];
+    let mut weight = kernel[0];
+    for (o, v) in out.iter_mut().zip(row(y)) {
+        *o = v * kernel[0];
+    }
+    for (k, &w) in kernel.iter().enumerate().skip(1) {
+        for yy in [y.checked_sub(k), Some(y + k).filter(|&yy| yy < height)]
+            .into_iter()
+            .flatten()
+        {
+            weight += w;
+            for (o, v) in out.iter_mut().zip(row(yy)) {
+                *o += v * w;
+            }
+        }
+    }
+    out.iter_mut().for_each(|o| *o /= weight);
+}
+
+/// Returns the image blurred by a Gaussian with standard deviation `radius`
+/// pixels.  The image is `width` pixels wide, in row-major order.  With the
+/// `rayon` feature, the rows are blurred in parallel.
+pub fn gaussian_blur(image: &[f32], width: usize, radius: f64) -> Vec<f32> {
+    if radius <= 0.0 || width == 0 || radius.is_nan() {
+        return image.to_vec();
+    }
+    let kernel = kernel(radius);
+    let mut rows = vec![0.0; image.len()];
+    let mut out = vec![0.0; image.len()];
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        rows.par_chunks_mut(width)
+            .zip(image.par_chunks(width))
+            .for_each(|(o, row)| blur_row(row, &kernel, o));
+        out.par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, o)| blur_column(&rows, width, y, &kernel, o));
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        for (o, row) in rows.chunks_mut(width).zip(image.chunks(width)) {
+            blur_row(row, &kernel, o);
+        }
+        for (y, o) in out.chunks_mut(width).enumerate() {
+            blur_column(&rows, width, y, &kernel, o);
+        }
+    }
+    out
+}
+
+/// Adds `strength` times the image blurred with standard deviation
+/// `radius` to the image, so that each pixel glows onto its neighbors.
+pub fn bloom(image: &mut [f32], width: usize, radius: f64, strength: f32) {
+    let glow = gaussian_blur(image, width, radius);
+    for (c, g) in image.iter_mut().zip(glow) {
+        *c += strength * g;
+    }
+}
+
+/// Adds `strength` times the difference between the image and the image
+/// blurred with standard deviation `radius` to the image, which sharpens
+/// the detail at about that scale.  Densities that would become negative
+/// are set to 0.
+pub fn unsharp_mask(image: &mut [f32], width: usize, radius: f64, strength: f32) {
+    let blurred = gaussian_blur(image, width, radius);
+    for (c, b) in image.iter_mut().zip(blurred) {
+        *c = (*c + strength * (*c - b)).max(0.0);
+    }
+}
@@ -0,0 +1,125 @@
+//! Parameters that are known to give good pictures.
+
+use crate::group::GeneratorSet;
+use crate::render::UnknownName;
+use crate::{Cpx, Generator};
+use std::str::FromStr;
+
+/// The ways of building generators from traces, named by the orbifold
+/// notation for the quotient surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// ○∞, given by `generators`.
+    Oi,
+    /// ××∞, given by `generators_xx`.
+    Xxi,
+    /// ×∞∞, given by `generators_x`.  Only the first trace is used.
+    Xii,
+}
+
+impl Symmetry {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Symmetry::Oi => "oi",
+            Symmetry::Xxi => "xxi",
+            Symmetry::Xii => "xii",
+        }
+    }
+    /// Returns the generators with traces `ta` and `tb`.
+    pub fn generators(&self, ta: Cpx, tb: Cpx) -> [Generator; 4] {
+        match self {
+            Symmetry::Oi => crate::generators(ta, tb),
+            Symmetry::Xxi => crate::generators_xx(ta, tb),
+            Symmetry::Xii => crate::generators_x(ta),
+        }
+    }
+    /// Like `generators`, but with derived data.
+    pub fn generator_set(&self, ta: Cpx, tb: Cpx) -> GeneratorSet {
+        GeneratorSet::new(self.generators(ta, tb))
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "oi" => Ok(Symmetry::Oi),
+            "xxi" => Ok(Symmetry::Xxi),
+            "xii" => Ok(Symmetry::Xii),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub symmetry: Symmetry,
+    pub ta: Cpx,
+    pub tb: Cpx,
+}
+
+impl Preset {
+    pub fn generators(&self) -> [Generator; 4] {
+        self.symmetry.generators(self.ta, self.tb)
+    }
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "circle",
+        description: "A Fuchsian group, whose limit set is a circle.",
+        symmetry: Symmetry::Oi,
+        ta: Cpx::new(2.2, 0.0),
+        tb: Cpx::new(2.2, 0.0),
+    },
+    Preset {
+        name: "quasicircle",
+        description: "A quasi-Fuchsian group, whose limit set is a fractal curve.",
+        symmetry: Symmetry::Oi,
+        ta: Cpx::new(1.87, 0.1),
+        tb: Cpx::new(1.87, -0.1),
+    },
+    Preset {
+        name: "spirals",
+        description: "A group in the Maskit slice, with spirals between large circles.",
+        symmetry: Symmetry::Oi,
+        ta: Cpx::new(1.91, 0.05),
+        tb: Cpx::new(2.0, 0.0),
+    },
+    Preset {
+        name: "maskit-boundary",
+        description: "A group near the boundary of the Maskit slice, where the spirals \
+                      nearly close up into chains of tangent circles.",
+        symmetry: Symmetry::Oi,
+        ta: Cpx::new(1.9247306, -0.0449408),
+        tb: Cpx::new(2.0, 0.0),
+    },
+    Preset {
+        name: "apollonian",
+        description: "The double cusp group whose limit set is the Apollonian gasket.",
+        symmetry: Symmetry::Oi,
+        ta: Cpx::new(2.0, 0.0),
+        tb: Cpx::new(2.0, 0.0),
+    },
+    Preset {
+        name: "necklace",
+        description: "A group with ××∞ symmetry.",
+        symmetry: Symmetry::Xxi,
+        ta: Cpx::new(2.0, 1.0),
+        tb: Cpx::new(2.0, 1.0),
+    },
+    Preset {
+        name: "dragon",
+        description: "A group with ×∞∞ symmetry.",
+        symmetry: Symmetry::Xii,
+        ta: Cpx::new(2.0, 1.0),
+        tb: Cpx::new(0.0, 0.0),
+    },
+];
+
+/// Returns the preset with the given name.
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name == name)
+}
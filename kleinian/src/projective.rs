@@ -0,0 +1,63 @@
+//! Points of the Riemann sphere in homogeneous coordinates [z : w], which
+//! stand for z / w.  The centers of circles are computed this way, so that
+//! a center at or near infinity is recognized as such, instead of becoming
+//! a huge complex number made mostly of rounding error that throws off the
+//! fitting of a window.
+
+use crate::Cpx;
+
+/// Points farther than this from the origin are treated as infinity.  The
+/// circles with centers this far out are lines, up to rounding error.
+pub const MAX_MODULUS: f64 = 1e10;
+
+/// The point z / w of the Riemann sphere.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectivePoint(pub Cpx, pub Cpx);
+
+impl ProjectivePoint {
+    pub fn infinity() -> Self {
+        ProjectivePoint(Cpx::from(1.0), Cpx::from(0.0))
+    }
+    /// Returns the point z, which is infinity if `z` is infinite.
+    pub fn from_affine(z: Cpx) -> Self {
+        if z.is_infinite() {
+            Self::infinity()
+        } else {
+            ProjectivePoint(z, Cpx::from(1.0))
+        }
+    }
+    /// Returns whether the point is infinity, or farther from the origin
+    /// than `MAX_MODULUS`.
+    pub fn is_infinite(&self) -> bool {
+        self.0.norm() > MAX_MODULUS * self.1.norm()
+    }
+    /// Returns the point as a complex number.  Points that are infinite, as
+    /// in `is_infinite`, are all returned as the same complex number with
+    /// infinite real part, as in `algebra::fixed_points`, so that they are
+    /// skipped when fitting a window and drawing.
+    pub fn affine(&self) -> Cpx {
+        if self.is_infinite() {
+            Cpx::new(f64::INFINITY, 0.0)
+        } else {
+            self.0 / self.1
+        }
+    }
+    /// Returns the point of the unit sphere given by inverse stereographic
+    /// projection from the north pole, which is the image of infinity, or
+    /// `None` if the coordinates are not finite or are both 0.  Points near
+    /// infinity are projected accurately.
+    pub fn sphere(&self) -> Option<[f64; 3]> {
+        if !(self.0.is_finite() && self.1.is_finite()) {
+            return None;
+        }
+        let scale = self.0.norm().max(self.1.norm());
+        if !(scale.is_finite() && scale > 0.0) {
+            return None;
+        }
+        let (z, w) = (self.0 / scale, self.1 / scale);
+        let (nz, nw) = (z.norm_sqr(), w.norm_sqr());
+        let zw = z * w.conj();
+        let d = nz + nw;
+        Some([2.0 * zw.re / d, 2.0 * zw.im / d, (nz - nw) / d])
+    }
+}
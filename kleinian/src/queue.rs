@@ -1,56 +1,653 @@
-use crate::{Circle, Cpx, Generator};
+use crate::diagnostics::Diagnostics;
+use crate::reflection::MobiusOrAnti;
+use crate::{check_generators, elliptic_order, Circle, Cpx, Error, Generator, LimitPoint};
 use derive_where::derive_where;
 use nalgebra::Matrix2;
 use ordered_float::NotNan;
+use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 
+/// A priority queue of circles, ordered by size.  Each call to `advance`
+/// replaces the largest circle with its images under the generators, so
+/// generation can be stopped and resumed at any point.
 pub struct CircleQueue {
     queue: BinaryHeap<QueueItem>,
-    gens: [Generator; 4],
+    /// The generators followed by their inverses, in the same order.
+    gens: Vec<Generator>,
+    /// Whether each generator reverses orientation, so that its matrix acts
+    /// on z̄.
+    anti: Vec<bool>,
+    /// The largest number of times that each generator may be repeated in a
+    /// row.  This is limited for elliptic generators of finite order, so
+    /// that each power of the generator is only used once.
+    max_run: Vec<usize>,
+    /// The relation between the generators, if there is one.
+    relation: Option<Relation>,
+    /// Whether the words are recorded, as described in `enable_words`.
+    record_words: bool,
+    /// The words that have been recorded, which the items point into.  The
+    /// words of one letter are always recorded.
+    tree: Vec<Node>,
+    diagnostics: Option<Diagnostics>,
+    /// Whether to correct the rounding error that builds up in long words,
+    /// as described in `enable_repair`.
+    repair: bool,
+    /// Whether the order of exploration is made independent of rounding
+    /// error, as described in `enable_deterministic_order`.
+    deterministic: bool,
+    /// The number of items created since `enable_deterministic_order` was
+    /// called.
+    created: u64,
 }
 
 impl CircleQueue {
-    fn item(&self, matrix: Matrix2<Cpx>, last: u8) -> QueueItem {
-        let ri = (matrix * self.gens[last as usize].circle).radius_inv();
-        QueueItem {
+    fn item(&mut self, matrix: MobiusOrAnti, last: u8, end: WordEnd) -> Result<QueueItem, Error> {
+        Ok(self.item_and_circle(matrix, last, end)?.0)
+    }
+    fn item_and_circle(
+        &mut self,
+        matrix: MobiusOrAnti,
+        last: u8,
+        end: WordEnd,
+    ) -> Result<(QueueItem, Circle), Error> {
+        let circle = matrix * self.gens[last as usize].circle;
+        let rank = self.rank();
+        if let Some(d) = &mut self.diagnostics {
+            d.check_circle(&circle, || word(&self.tree, end.node, rank));
+        }
+        let circle = repaired(circle, self.repair);
+        let mut priority = NotNan::new(-circle.radius_inv()).map_err(|_| {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(depth = end.len, "circle of undefined size");
+            Error::NumericalBreakdown
+        })?;
+        let mut order = Reverse(0);
+        if self.deterministic {
+            priority = quantize(priority);
+            self.created += 1;
+            order = Reverse(self.created);
+        }
+        let item = QueueItem {
             matrix,
             last,
-            priority: NotNan::new(-ri).unwrap(),
+            end,
+            priority,
+            order,
+        };
+        Ok((item, circle))
+    }
+    fn label(&self, item: &QueueItem) -> LimitPoint {
+        let c = self.circle(item);
+        LimitPoint {
+            z: c.center_point().affine(),
+            radius: 1.0 / c.radius_inv(),
+            generator: item.last,
+            depth: item.end.len as usize,
         }
     }
-    pub fn new(gens: [Generator; 4]) -> Self {
+    pub fn new(gens: [Generator; 4]) -> Result<Self, Error> {
+        Self::with_generators(gens.into())
+    }
+    /// Like `new`, but for any number of generators.  `gens` holds the
+    /// generators followed by their inverses in the same order, so that the
+    /// inverse of letter `l` of `n` is letter `(l + n / 2) % n`.  For two
+    /// generators, this is the order a, b, a^{-1}, b^{-1}.  If the product
+    /// of the commutators of the pairs of generators is the identity, or
+    /// elliptic of finite order, as for `fuchsian::generators_genus2` and
+    /// `generators_orbifold`, the words that the relation shortens are
+    /// skipped.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", name = "setup", skip_all, fields(rank = gens.len() / 2))
+    )]
+    pub fn with_generators(gens: Vec<Generator>) -> Result<Self, Error> {
+        check_generators(&gens)?;
+        let max_run = max_runs(&gens);
+        let relation = Relation::find(&gens);
+        let anti = vec![false; gens.len()];
+        Self::with_letters(gens, anti, max_run, relation)
+    }
+    /// Returns a queue for the group generated by the reflections in the
+    /// circles, as described in `reflection`.  Each reflection is its own
+    /// inverse, so the words are those in which no letter is repeated in a
+    /// row, and the circle of a word is the circle of its last letter,
+    /// mapped by the other letters.  Letter `l` is the reflection in
+    /// `circles[l]`, and `rank` is the number of circles.  Returns
+    /// `Error::DegenerateGenerators` for fewer than 3 circles: one circle
+    /// gives a finite group, and with two, each word has only one child, so
+    /// the queue never grows.
+    pub fn with_reflections(circles: Vec<Circle>) -> Result<Self, Error> {
+        let rank = circles.len();
+        if rank < 3 {
+            return Err(Error::DegenerateGenerators);
+        }
+        let reflection = |c: &Circle| Generator {
+            matrix: MobiusOrAnti::reflection(c).matrix,
+            circle: *c,
+        };
+        // Each reflection is also listed as its own inverse, but those
+        // letters are never used, so that each word is only found once.
+        let gens: Vec<Generator> = circles.iter().chain(&circles).map(reflection).collect();
+        check_generators(&gens)?;
+        let mut max_run = vec![1; rank];
+        max_run.resize(2 * rank, 0);
+        Self::with_letters(gens, vec![true; 2 * rank], max_run, None)
+    }
+    fn with_letters(
+        gens: Vec<Generator>,
+        anti: Vec<bool>,
+        max_run: Vec<usize>,
+        relation: Option<Relation>,
+    ) -> Result<Self, Error> {
+        let rank = gens.len() / 2;
         let mut q = CircleQueue {
             queue: BinaryHeap::new(),
             gens,
+            anti,
+            max_run,
+            relation,
+            record_words: false,
+            tree: Vec::new(),
+            diagnostics: None,
+            repair: false,
+            deterministic: false,
+            created: 0,
         };
-        for i in 0..4 {
-            q.queue.push(q.item(Matrix2::identity(), i));
+        for i in 0..2 * rank as u8 {
+            if q.max_run[i as usize] == 0 {
+                continue;
+            }
+            let end = WordEnd {
+                node: q.add_node(ROOT, i),
+                len: 1,
+                run: 1,
+                runs: [1, 1],
+            };
+            let item = q.item(MobiusOrAnti::identity(), i, end)?;
+            q.queue.push(item);
+        }
+        Ok(q)
+    }
+    /// Returns the number of generators, not counting their inverses.
+    pub fn rank(&self) -> usize {
+        self.gens.len() / 2
+    }
+    /// Replaces the largest circle with its images.  On error, the queue is
+    /// left unchanged.  Returns `Error::EmptyQueue` if there are no circles
+    /// left, which can only happen if some words are skipped, as for
+    /// elliptic generators or reflections.
+    pub fn advance(&mut self) -> Result<(), Error> {
+        self.advance_with(|_| ())
+    }
+    /// Like `advance`, but also calls `f` with each of the new circles, so
+    /// that the caller can show the exploration as it happens.
+    pub fn advance_with<F: FnMut(&Circle)>(&mut self, f: F) -> Result<(), Error> {
+        self.expand(f).map(|_| ())
+    }
+    /// Like `advance_with`, but returns the group element of the word whose
+    /// circle was replaced.
+    pub(crate) fn expand<F: FnMut(&Circle)>(&mut self, mut f: F) -> Result<MobiusOrAnti, Error> {
+        let item = self.queue.pop().ok_or(Error::EmptyQueue)?;
+        let mut matrix = item.matrix * self.letter(item.last);
+        let rank = self.rank();
+        if let Some(d) = &mut self.diagnostics {
+            d.check_matrix(&matrix.matrix, || word(&self.tree, item.end.node, rank));
+        }
+        let det = matrix.matrix.determinant();
+        if self.repair && det.is_finite() && det != Cpx::from(0.0) {
+            matrix.matrix /= det.sqrt();
+        }
+        let nodes = self.tree.len();
+        let n = self.gens.len() as u8;
+        let mut children = Vec::with_capacity(n as usize - 1);
+        for turn in 0..n - 1 {
+            let letter = next_letter(item.last, turn, rank);
+            let run = if letter == item.last {
+                item.end.run + 1
+            } else {
+                1
+            };
+            if run as usize > self.max_run[letter as usize] {
+                continue;
+            }
+            let mut runs = [1, 1];
+            if let Some(r) = &self.relation {
+                let before = item.end.runs.map(|k| k as usize);
+                if !r.allows(before, item.last, letter) {
+                    continue;
+                }
+                runs = r.step(before, item.last, letter).map(|k| k as u32);
+            }
+            let end = WordEnd {
+                node: self.add_node(item.end.node, turn),
+                len: item.end.len + 1,
+                run,
+                runs,
+            };
+            match self.item_and_circle(matrix, letter, end) {
+                Ok(child) => children.push(child),
+                Err(e) => {
+                    self.tree.truncate(nodes);
+                    self.queue.push(item);
+                    return Err(e);
+                }
+            }
+        }
+        for (child, circle) in children {
+            f(&circle);
+            self.queue.push(child);
+        }
+        Ok(matrix)
+    }
+    /// Starts recording the words of the circles, which `words`,
+    /// `ordered_circles`, and `ordered_labelled_points` need.  This takes a
+    /// few bytes for each circle found, so it is off by default.  It must be
+    /// called before `advance`.
+    pub fn enable_words(&mut self) {
+        assert!(
+            self.record_words || self.queue.iter().all(|i| i.end.len == 1),
+            "enable_words must be called before advance"
+        );
+        self.record_words = true;
+    }
+    /// Adds the word that takes the turn from the word of `parent`, or the
+    /// word of one letter `turn` if `parent` is `ROOT`, to the tree, and
+    /// returns its node, or returns `ROOT` if the word is not recorded.
+    fn add_node(&mut self, parent: u32, turn: u8) -> u32 {
+        if !self.record_words && parent != ROOT {
+            return ROOT;
         }
-        q
+        self.tree.push(Node { parent, turn });
+        (self.tree.len() - 1) as u32
     }
-    pub fn advance(&mut self) {
-        let item = self.queue.pop().unwrap();
-        let matrix = item.matrix * self.gens[item.last as usize].matrix;
-        for i in 3..6 {
-            self.queue.push(self.item(matrix, (item.last + i) % 4));
+    fn check_words_recorded(&self) {
+        assert!(
+            self.record_words,
+            "the words are only recorded after enable_words"
+        );
+    }
+    /// Starts checking the matrices and circles computed by `advance` for
+    /// NaN and infinite entries.  This slows exploration down a little.
+    /// The problems are recorded with their words, so this also calls
+    /// `enable_words`, and must be called before `advance`.
+    pub fn enable_diagnostics(&mut self) {
+        self.enable_words();
+        let rank = self.rank();
+        self.diagnostics.get_or_insert_with(|| Diagnostics {
+            rank,
+            ..Default::default()
+        });
+    }
+    /// Starts correcting the rounding error that builds up in the products
+    /// of long words: each product is rescaled to determinant 1, and each
+    /// circle that is not valid, as in `Circle::is_valid`, is replaced with
+    /// its repair.  This costs a little time, and only matters for groups
+    /// whose words get very long before their circles get small.
+    pub fn enable_repair(&mut self) {
+        self.repair = true;
+    }
+    /// Makes the order of exploration, and so the circles found by a given
+    /// number of calls to `advance`, the same on every run and platform.
+    /// The sizes of the circles are compared after rounding off their last
+    /// 24 bits, so that differences in rounding between platforms are very
+    /// unlikely to matter, and circles of the same rounded size are explored
+    /// in the order in which they were found.  Without this, circles of the
+    /// same size, which are common in groups with symmetry, are explored in
+    /// an order that depends on the internals of the heap.  This should be
+    /// called before `advance`.
+    pub fn enable_deterministic_order(&mut self) {
+        if self.deterministic {
+            return;
+        }
+        self.deterministic = true;
+        let mut items = std::mem::take(&mut self.queue).into_vec();
+        items.sort_by_cached_key(|i| path(&self.tree, i.end.node));
+        for item in &mut items {
+            item.priority = quantize(item.priority);
+            self.created += 1;
+            item.order = Reverse(self.created);
+        }
+        self.queue = items.into();
+    }
+    /// Returns the generator of the letter.
+    fn letter(&self, l: u8) -> MobiusOrAnti {
+        MobiusOrAnti {
+            matrix: self.gens[l as usize].matrix,
+            anti: self.anti[l as usize],
         }
     }
+    /// Returns the circle of the item's word.
+    fn circle(&self, item: &QueueItem) -> Circle {
+        repaired(
+            item.matrix * self.gens[item.last as usize].circle,
+            self.repair,
+        )
+    }
+    /// Returns what was found since `enable_diagnostics` was called, or
+    /// `None` if it was not called.
+    pub fn diagnostics(&self) -> Option<&Diagnostics> {
+        self.diagnostics.as_ref()
+    }
     pub fn len(&self) -> usize {
         self.queue.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+    /// Returns the radius of the largest circle in the queue.
+    pub fn max_radius(&self) -> f64 {
+        self.queue.peek().map(|i| -1.0 / *i.priority).unwrap_or(0.0)
+    }
+    /// Returns the centers of the circles currently in the queue.  Centers
+    /// at or near infinity are all returned as infinity, as described in
+    /// `ProjectivePoint::affine`.
+    pub fn points(&self) -> impl Iterator<Item = Cpx> + '_ {
+        self.queue
+            .iter()
+            .map(|i| self.circle(i).center_point().affine())
+    }
+    /// Returns the centers of the circles currently in the queue, along with
+    /// information about each circle.
+    pub fn labelled_points(&self) -> impl Iterator<Item = LimitPoint> + '_ {
+        self.queue.iter().map(|i| self.label(i))
+    }
+    /// Returns the group elements whose words are currently in the queue,
+    /// in no particular order.  The element of a word is the product of all
+    /// of its letters.  For a group generated by reflections, use
+    /// `elements_with_orientation`, since the matrices of the elements that
+    /// reverse orientation act on z̄.
+    pub fn elements(&self) -> impl Iterator<Item = Matrix2<Cpx>> + '_ {
+        self.elements_with_orientation().map(|m| m.matrix)
+    }
+    /// Like `elements`, but with whether each element reverses orientation.
+    pub fn elements_with_orientation(&self) -> impl Iterator<Item = MobiusOrAnti> + '_ {
+        self.queue.iter().map(|i| i.matrix * self.letter(i.last))
+    }
+    /// Returns the queue items in the order in which their circles occur
+    /// along the limit set.
+    fn sorted_items(&self) -> Vec<&QueueItem> {
+        self.check_words_recorded();
+        let mut items: Vec<&QueueItem> = self.queue.iter().collect();
+        items.sort_by_cached_key(|i| path(&self.tree, i.end.node));
+        items
+    }
+    /// Returns the words whose circles are currently in the queue, in the
+    /// order in which they occur along the limit set.  A word is a sequence
+    /// of generator indices, and its circle is the circle of its last letter
+    /// mapped by the product of the other letters.  The words must have been
+    /// recorded, as in `enable_words`.
+    pub fn words(&self) -> Vec<Vec<u8>> {
+        self.sorted_items()
+            .into_iter()
+            .map(|i| word(&self.tree, i.end.node, self.rank()))
+            .collect()
+    }
+    /// Like `labelled_points`, but in the order in which the points occur
+    /// along the limit set, and with the word of each point, as in `words`.
+    /// The words must have been recorded, as in `enable_words`.
+    pub fn ordered_labelled_points(&self) -> Vec<(LimitPoint, Vec<u8>)> {
+        self.sorted_items()
+            .into_iter()
+            .map(|i| (self.label(i), word(&self.tree, i.end.node, self.rank())))
+            .collect()
+    }
     pub fn circles(self) -> impl Iterator<Item = Circle> {
-        let (queue, gens) = (self.queue, self.gens);
+        let (queue, gens, repair) = (self.queue, self.gens, self.repair);
         queue
             .into_iter()
-            .map(move |i| i.matrix * gens[i.last as usize].circle)
+            .map(move |i| repaired(i.matrix * gens[i.last as usize].circle, repair))
+    }
+    /// Returns the circles in the order in which they occur along the limit
+    /// set.  The children of each circle are visited in the cyclic order
+    /// of the generators, so sorting by the sequence of turns taken gives
+    /// the same order as a depth-first traversal.  The words must have been
+    /// recorded, as in `enable_words`.
+    pub fn ordered_circles(self) -> impl Iterator<Item = Circle> {
+        self.check_words_recorded();
+        let (queue, gens, tree, repair) = (self.queue, self.gens, self.tree, self.repair);
+        let mut items = queue.into_vec();
+        items.sort_by_cached_key(|i| path(&tree, i.end.node));
+        items
+            .into_iter()
+            .map(move |i| repaired(i.matrix * gens[i.last as usize].circle, repair))
     }
 }
 
+/// Returns the largest number of times in a row that each letter may occur
+/// in a word, for the generators followed by their inverses, as in
+/// `CircleQueue::with_generators`.  This is limited for elliptic
+/// generators of finite order, so that each power of the generator is only
+/// used once.
+pub(crate) fn max_runs(gens: &[Generator]) -> Vec<usize> {
+    let rank = gens.len() / 2;
+    let mut max_run = vec![usize::MAX; gens.len()];
+    for i in 0..rank {
+        if let Some(k) = elliptic_order(&gens[i].matrix) {
+            // Each power other than the identity is either g^j with
+            // j <= k / 2 or g^{-j} with j < k / 2.
+            max_run[i] = k / 2;
+            max_run[i + rank] = (k - 1) / 2;
+        }
+    }
+    max_run
+}
+
+/// Rounds off the last 24 bits of the mantissa, keeping 28 bits, which
+/// is about 8 decimal digits.
+fn quantize(x: NotNan<f64>) -> NotNan<f64> {
+    // Rounding towards 0 only changes finite numbers, and keeps them finite.
+    NotNan::new(f64::from_bits(x.to_bits() & !0xff_ffff)).unwrap_or(x)
+}
+
+/// Returns the repair of the circle if `repair` is set and the circle is
+/// not valid, and otherwise the circle itself.
+fn repaired(circle: Circle, repair: bool) -> Circle {
+    if repair && !circle.is_valid() {
+        circle.repair().unwrap_or(circle)
+    } else {
+        circle
+    }
+}
+
+/// Returns the letter that follows `last` after taking the given turn, for
+/// a group of the given rank.  The turns run through the letters other than
+/// the inverse of `last` in cyclic order, starting after the inverse, so
+/// for two generators a turn of 1 repeats the last letter.
+fn next_letter(last: u8, turn: u8, rank: usize) -> u8 {
+    let n = 2 * rank;
+    ((last as usize + rank + 1 + turn as usize) % n) as u8
+}
+
+/// The parent of the nodes of the words of one letter.
+const ROOT: u32 = u32::MAX;
+
+/// A word in the tree of explored words, which is the word of `parent`
+/// followed by the letter reached by taking the turn `turn` from its last
+/// letter, or the word of the single letter `turn` if `parent` is `ROOT`.
+struct Node {
+    parent: u32,
+    turn: u8,
+}
+
+/// Returns the first letter of the word of the node, followed by the turns
+/// taken to spell out the rest of it.  Sorting by these puts the words in
+/// the order of a depth-first traversal.
+fn path(tree: &[Node], mut node: u32) -> Vec<u8> {
+    let mut path = Vec::new();
+    while node != ROOT {
+        let n = &tree[node as usize];
+        path.push(n.turn);
+        node = n.parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Returns the word of the node.
+fn word(tree: &[Node], node: u32, rank: usize) -> Vec<u8> {
+    let path = path(tree, node);
+    let mut word = Vec::with_capacity(path.len());
+    let mut letter = path[0];
+    word.push(letter);
+    for &turn in &path[1..] {
+        letter = next_letter(letter, turn, rank);
+        word.push(letter);
+    }
+    word
+}
+
 #[derive_where(PartialEq, Eq, PartialOrd, Ord)]
 struct QueueItem {
+    /// The product of the letters of the word other than the last.
     #[derive_where(skip(EqHashOrd))]
-    matrix: Matrix2<Cpx>,
+    matrix: MobiusOrAnti,
     #[derive_where(skip(EqHashOrd))]
     last: u8,
+    #[derive_where(skip(EqHashOrd))]
+    end: WordEnd,
     priority: NotNan<f64>,
+    /// Breaks ties between items of the same priority, in favor of the one
+    /// created first.  This is 0 unless the order is deterministic.
+    order: Reverse<u64>,
+}
+
+/// The node of the word of a queue item, along with what `expand` needs to
+/// know about its end, so that it does not have to spell out the word.
+#[derive(Clone, Copy)]
+struct WordEnd {
+    node: u32,
+    /// The number of letters.
+    len: u32,
+    /// The number of times that the last letter is repeated at the end.
+    run: u32,
+    /// The runs of the word, as in `Relation::step`, if there is a
+    /// relation.
+    runs: [u32; 2],
+}
+
+/// Elements are treated as the identity when their entries are within this
+/// of those of the identity, relative to the size of the entries.
+const RELATION_TOLERANCE: f64 = 1e-9;
+
+/// A relation between the generators that the explorers allow for.  If the
+/// product of the commutators of the pairs of generators, g_1 g_2 g_1^{-1}
+/// g_2^{-1} g_3 g_4 g_3^{-1} g_4^{-1} and so on, is the identity, as for the
+/// surface groups of `fuchsian::generators_genus2`, or is elliptic of
+/// finite order n, as for `generators_orbifold`, then the relator is that
+/// product or its nth power.  A word with more than half of the relator in
+/// a row is equal to a shorter word, and one with exactly half of it is
+/// equal to one with half of its inverse, so such words are skipped, except
+/// for the halves of the relator itself.
+pub(crate) struct Relation {
+    /// The letter after each letter in the product of the commutators, and
+    /// in its inverse, read cyclically.
+    pub(crate) next: [Vec<u8>; 2],
+    /// The largest number of letters in a row that a word may have in
+    /// common with the relator, and with its inverse, repeated forever.
+    pub(crate) max_run: [usize; 2],
+}
+
+impl Relation {
+    /// Returns the relation satisfied by the generators, followed by their
+    /// inverses, if there is one.
+    pub(crate) fn find(gens: &[Generator]) -> Option<Self> {
+        let n = gens.len();
+        let rank = n / 2;
+        if !rank.is_multiple_of(2) {
+            return None;
+        }
+        let cycle: Vec<usize> = (0..rank)
+            .step_by(2)
+            .flat_map(|i| [i, i + 1, i + rank, i + 1 + rank])
+            .collect();
+        let product: Matrix2<Cpx> = cycle
+            .iter()
+            .fold(Matrix2::identity(), |p, &l| p * gens[l].matrix);
+        let size = product.iter().map(|z| z.norm()).fold(1.0, f64::max);
+        let is_identity = [1.0, -1.0].iter().any(|&s| {
+            let diff = product - Matrix2::identity() * Cpx::from(s);
+            diff.iter().all(|z| z.norm() < RELATION_TOLERANCE * size)
+        });
+        let power = if is_identity {
+            1
+        } else {
+            elliptic_order(&product)?
+        };
+        let inverse = |l: usize| (l + rank) % n;
+        let mut next = [vec![0; n], vec![0; n]];
+        for (j, &l) in cycle.iter().enumerate() {
+            next[0][l] = cycle[(j + 1) % n] as u8;
+            // In the inverse of the relator, the inverse of each letter is
+            // followed by the inverse of the letter before it.
+            next[1][inverse(l)] = inverse(cycle[(j + n - 1) % n]) as u8;
+        }
+        let half = n * power / 2;
+        Some(Relation {
+            next,
+            max_run: [half, half - 1],
+        })
+    }
+    /// The runs of a word are the numbers of letters at its end that it has
+    /// in common with the relator, and with its inverse, repeated forever,
+    /// which are [1, 1] for a word of one letter.  Returns the runs of the
+    /// word that ends in `last`, with the given runs, followed by `letter`.
+    pub(crate) fn step(&self, runs: [usize; 2], last: u8, letter: u8) -> [usize; 2] {
+        [0, 1].map(|k| {
+            if self.next[k][last as usize] == letter {
+                runs[k] + 1
+            } else {
+                1
+            }
+        })
+    }
+    /// Returns whether a word ending in `last`, with the given runs, may be
+    /// followed by `letter`.
+    pub(crate) fn allows(&self, runs: [usize; 2], last: u8, letter: u8) -> bool {
+        (0..2).all(|k| self.next[k][last as usize] != letter || runs[k] < self.max_run[k])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_on_an_empty_queue_is_an_error() {
+        // A rotation of order 3 generates a finite group, so the words a
+        // and a^{-1} have no children, and the queue runs out.
+        let (c, s) = (0.5, 0.75f64.sqrt());
+        let r = Matrix2::new(Cpx::from(c), Cpx::from(-s), Cpx::from(s), Cpx::from(c));
+        let gens = vec![
+            Generator {
+                matrix: r,
+                circle: Circle::new(Cpx::new(2.0, 0.0), 0.5),
+            },
+            Generator {
+                matrix: crate::algebra::inv(&r),
+                circle: Circle::new(Cpx::new(-2.0, 0.0), 0.5),
+            },
+        ];
+        let mut queue = CircleQueue::with_generators(gens).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.advance(), Ok(()));
+        assert_eq!(queue.advance(), Ok(()));
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.advance(), Err(Error::EmptyQueue));
+    }
+
+    #[test]
+    fn with_reflections_needs_three_circles() {
+        let circles: Vec<Circle> = (0..3)
+            .map(|k| Circle::new(Cpx::from_polar(2.0, 2.0 * k as f64), 0.5))
+            .collect();
+        for n in 0..3 {
+            let result = CircleQueue::with_reflections(circles[..n].to_vec());
+            assert_eq!(result.err(), Some(Error::DegenerateGenerators));
+        }
+        let mut queue = CircleQueue::with_reflections(circles).unwrap();
+        for _ in 0..10 {
+            queue.advance().unwrap();
+        }
+        assert_eq!(queue.len(), 13);
+    }
 }
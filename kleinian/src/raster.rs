@@ -0,0 +1,218 @@
+//! Rasterization of the limit set directly into a bitmap, without storing
+//! a list of points.  The group is explored depth first, and a branch is
+//! abandoned once its circle is much smaller than a pixel and lands in a
+//! pixel that is already marked, since everything below it would land there
+//! too.
+//! This is much faster and uses much less memory than `render::render_rgba`
+//! when only an image is wanted.
+
+use crate::depth_first::DepthFirst;
+use crate::render::{Rgb, Style};
+use crate::window::CoordTransform;
+use crate::{Error, Generator};
+use std::ops::Range;
+
+/// Circles with a smaller radius than this, in pixels, are marked.  The
+/// circles only approximately contain the circles below them, and with a
+/// radius of half a pixel, about a tenth of the pixels are missed.
+const LEAF_RADIUS: f64 = 0.1;
+
+/// The number of rows around a band drawn by `rasterize_rows` that are
+/// drawn along with it and then dropped.  Small circles just outside of a
+/// band are marked in these rows and expanded as they would be in the whole
+/// image, rather than abandoned, since their children can land in the band.
+const BAND_PADDING: usize = 4;
+
+/// Branches are kept if they are within this many radii of a band drawn by
+/// `rasterize_rows`, at the edges of the band that are inside the image.
+/// Since the circles only roughly contain the circles below them, the
+/// slack of a pixel that is enough at the edges of the image would lose
+/// parts of the limit set that cross from one band into the next.
+const BAND_MARGIN: f64 = 3.0;
+
+/// Marks the pixels of a `width` by `height` image, in row-major order with
+/// the imaginary axis pointing up, that the limit set passes through.
+/// Circles much smaller than a pixel are drawn as their centers, as in
+/// `render::render_rgba`.  Branches whose circles miss the image entirely
+/// are skipped.  At most `max_circles` circles are visited, which only
+/// matters if the group is far from discrete.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", name = "render", skip_all, fields(width = width, height = height))
+)]
+pub fn rasterize(
+    gens: &[Generator; 4],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    max_circles: usize,
+) -> Result<Vec<bool>, Error> {
+    rasterize_rows(gens, trans, width, height, 0..height, max_circles)
+}
+
+/// Like `rasterize`, but only draws the given rows of the image, counted
+/// from the top, and returns the mask of those rows.  Drawing an image a
+/// band of rows at a time gives the same image as drawing it at once, or
+/// very nearly, with only a band in memory, though circles near the edges
+/// of the bands are visited once for each band.  At most `max_circles`
+/// circles are visited for the band.
+pub fn rasterize_rows(
+    gens: &[Generator; 4],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    rows: Range<usize>,
+    max_circles: usize,
+) -> Result<Vec<bool>, Error> {
+    assert!(rows.end <= height, "rows out of range");
+    let padded = rows.start.saturating_sub(BAND_PADDING)..(rows.end + BAND_PADDING).min(height);
+    let mut mask = vec![false; width * padded.len()];
+    explore(
+        gens,
+        trans,
+        width,
+        height,
+        padded.clone(),
+        max_circles,
+        |idx| std::mem::replace(&mut mask[idx], true),
+    )?;
+    mask.truncate(width * (rows.end - padded.start));
+    mask.drain(..width * (rows.start - padded.start));
+    Ok(mask)
+}
+
+/// Returns the color as a pixel of an RGBA image, so that a slice of pixels
+/// has the same bytes as the image, and can be passed to image encoders and
+/// to the `ImageData` of a canvas without copying.
+pub fn pack_rgba(color: Rgb) -> u32 {
+    u32::from_ne_bytes([color[0], color[1], color[2], 255])
+}
+
+/// Like `rasterize`, but draws into `bitmap`, an RGBA image of `width` by
+/// `height` pixels packed as in `pack_rgba`, that the caller has filled
+/// with the background.  The pixels that the limit set passes through are
+/// set to `foreground`, and pixels that already have that color are
+/// treated as drawn.  Nothing but the image and a stack of words is ever
+/// stored, so this uses the least memory of the ways to draw a limit set.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", name = "render", skip_all, fields(width = width, height = height))
+)]
+pub fn generate_into_bitmap(
+    gens: &[Generator; 4],
+    bitmap: &mut [u32],
+    width: usize,
+    height: usize,
+    trans: &CoordTransform,
+    foreground: u32,
+    max_circles: usize,
+) -> Result<(), Error> {
+    assert_eq!(bitmap.len(), width * height, "bitmap has the wrong size");
+    explore(gens, trans, width, height, 0..height, max_circles, |idx| {
+        std::mem::replace(&mut bitmap[idx], foreground) == foreground
+    })
+}
+
+/// Explores the group for `rasterize_rows`.  `mark` is called with the
+/// index, within the rows, of each pixel of the rows that a small circle
+/// lands in, marks it, and returns whether it was already marked.
+fn explore<F: FnMut(usize) -> bool>(
+    gens: &[Generator; 4],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    rows: Range<usize>,
+    max_circles: usize,
+    mut mark: F,
+) -> Result<(), Error> {
+    // The circles that are small enough to mark are found here, so the
+    // explorer's own test for them is not used.
+    let explorer = DepthFirst::new(gens, 0.0)?;
+    let w = width as f64;
+    // The rows, counted from the top, as a range of y coordinates.
+    let (bottom, top) = ((height - rows.end) as f64, (height - rows.start) as f64);
+    let (margin_below, margin_above) = (
+        if rows.end < height { BAND_MARGIN } else { 0.0 },
+        if rows.start > 0 { BAND_MARGIN } else { 0.0 },
+    );
+    let scale = trans.scale();
+    let mut stack = explorer.roots();
+    let mut visited = 0;
+    // The number of branches abandoned because they miss the image or land
+    // in a marked pixel.
+    #[cfg(feature = "tracing")]
+    let (mut outside, mut marked) = (0usize, 0usize);
+    while let Some(branch) = stack.pop() {
+        if visited >= max_circles {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(max_circles, "circle limit reached");
+            break;
+        }
+        visited += 1;
+        let circle = explorer.circle(&branch);
+        let ri = circle.radius_inv();
+        if ri.is_nan() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(visited, "circle of undefined size");
+            return Err(Error::NumericalBreakdown);
+        }
+        // Lines are infinitely large, so they are always expanded.
+        if ri > 0.0 {
+            let r = scale / ri;
+            let (x, y) = trans.map(&circle.center());
+            if !(x.is_finite() && y.is_finite()) {
+                return Err(Error::NumericalBreakdown);
+            }
+            // Allow a pixel of slack, since the circles only approximately
+            // contain the circles below them.
+            if x + r < -1.0
+                || y + r * (1.0 + margin_below) < bottom - 1.0
+                || x - r > w + 1.0
+                || y - r * (1.0 + margin_above) > top + 1.0
+            {
+                #[cfg(feature = "tracing")]
+                {
+                    outside += 1;
+                }
+                continue;
+            }
+            if r < LEAF_RADIUS {
+                if !(x >= 0.0 && y >= bottom && x < w && y < top) {
+                    #[cfg(feature = "tracing")]
+                    {
+                        outside += 1;
+                    }
+                    continue;
+                }
+                let idx = (height - 1 - y as usize - rows.start) * width + x as usize;
+                if mark(idx) {
+                    #[cfg(feature = "tracing")]
+                    {
+                        marked += 1;
+                    }
+                    continue;
+                }
+            }
+        }
+        explorer.push_children(&branch, &mut stack);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(visited, outside, marked, "pruned branches");
+    Ok(())
+}
+
+/// Converts a mask, as returned by `rasterize`, into an RGBA image in the
+/// foreground and background colors of the style.
+pub fn mask_to_rgba(mask: &[bool], style: &Style) -> Vec<u8> {
+    let mut pixel_data = Vec::with_capacity(4 * mask.len());
+    for &marked in mask {
+        let color = if marked {
+            style.foreground
+        } else {
+            style.background
+        };
+        pixel_data.extend_from_slice(&color);
+        pixel_data.push(255);
+    }
+    pixel_data
+}
@@ -0,0 +1,117 @@
+//! Groups generated by reflections in circles.  A reflection reverses
+//! orientation, so it is not a Möbius transformation but an anti-Möbius
+//! one, z ↦ (a z̄ + b) / (c z̄ + d).  The elements of such a group are
+//! represented by `MobiusOrAnti`, which is a matrix along with whether it
+//! acts on z or on z̄, and `CircleQueue::with_reflections` explores the
+//! group.  If the circles are disjoint or tangent, with disjoint insides,
+//! the circles of the words are nested, and they shrink towards the limit
+//! set, such as the Apollonian gasket for the circles of
+//! `apollonian_circles`.
+
+use crate::{Circle, Cpx};
+use core::ops::Mul;
+use nalgebra::Matrix2;
+
+/// A Möbius transformation z ↦ (a z + b) / (c z + d) if `anti` is false,
+/// or the anti-Möbius transformation z ↦ (a z̄ + b) / (c z̄ + d) if it is
+/// true, where `matrix` is (a b; c d), usually of determinant 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MobiusOrAnti {
+    pub matrix: Matrix2<Cpx>,
+    pub anti: bool,
+}
+
+impl MobiusOrAnti {
+    pub fn identity() -> Self {
+        MobiusOrAnti::mobius(Matrix2::identity())
+    }
+    /// Returns the Möbius transformation with the given matrix.
+    pub fn mobius(matrix: Matrix2<Cpx>) -> Self {
+        MobiusOrAnti {
+            matrix,
+            anti: false,
+        }
+    }
+    /// Returns the reflection in the circle, which fixes each of its points
+    /// and swaps its inside and outside.  For a line, this is the mirror
+    /// reflection.
+    pub fn reflection(circle: &Circle) -> Self {
+        // The circle is the set of points with A|z|^2 + B z̄ + B̄ z + D = 0,
+        // and its reflection is z ↦ -(B z̄ + D) / (A z̄ + B̄).  Multiplying
+        // by i makes the determinant -AD + |B|^2 = 1.
+        let m = &circle.0;
+        let i = Cpx::i();
+        MobiusOrAnti {
+            matrix: Matrix2::new(-m[(0, 1)], -m[(1, 1)], m[(0, 0)], m[(1, 0)]) * i,
+            anti: true,
+        }
+    }
+    /// Returns the image of `z`, which is infinite if `z` is mapped to
+    /// infinity.
+    pub fn apply(&self, z: Cpx) -> Cpx {
+        let z = if self.anti { z.conj() } else { z };
+        let m = &self.matrix;
+        (m[(0, 0)] * z + m[(0, 1)]) / (m[(1, 0)] * z + m[(1, 1)])
+    }
+    /// Returns the inverse, assuming that the determinant is 1.
+    pub fn inverse(&self) -> Self {
+        let inv = crate::algebra::inv(&self.matrix);
+        MobiusOrAnti {
+            // The inverse of z ↦ M z̄ is z ↦ M̄^{-1} z̄.
+            matrix: if self.anti { conj(&inv) } else { inv },
+            anti: self.anti,
+        }
+    }
+}
+
+/// Returns the matrix with each entry conjugated.
+pub(crate) fn conj(m: &Matrix2<Cpx>) -> Matrix2<Cpx> {
+    m.map(|z| z.conj())
+}
+
+/// The composition, which applies `other` first.
+impl Mul for MobiusOrAnti {
+    type Output = MobiusOrAnti;
+    fn mul(self, other: MobiusOrAnti) -> MobiusOrAnti {
+        // Conjugation commutes past a Möbius transformation by conjugating
+        // its matrix.
+        let m = if self.anti {
+            conj(&other.matrix)
+        } else {
+            other.matrix
+        };
+        MobiusOrAnti {
+            matrix: self.matrix * m,
+            anti: self.anti != other.anti,
+        }
+    }
+}
+
+/// Returns four mutually tangent circles, the reflections in which
+/// generate a group whose limit set is the Apollonian gasket in the unit
+/// disk, with three equal circles inside the unit circle, one of them
+/// centered on the positive real axis.  Each of the four circles passes
+/// through three of the six points where the circles of the gasket's first
+/// generation touch, and so is orthogonal to three of those circles.
+pub fn apollonian_circles() -> [Circle; 4] {
+    // Three circles of radius r whose centers are at distance 1 - r from the
+    // origin are tangent to each other when (1 - r) √3 = 2r.
+    let r = 3f64.sqrt() / (2.0 + 3f64.sqrt());
+    let center = |k: usize| Cpx::from_polar(1.0 - r, 2.0 * std::f64::consts::PI * k as f64 / 3.0);
+    // Where the inner circles touch each other, and the unit circle.
+    let inner = |j: usize, k: usize| 0.5 * (center(j) + center(k));
+    let outer = |k: usize| center(k) / center(k).norm();
+    let through = |p: [Cpx; 3]| {
+        // The center is equidistant from the three points.
+        let (b, c) = (p[1] - p[0], p[2] - p[0]);
+        let d = 2.0 * (b.conj() * c).im;
+        let z = -Cpx::i() * (c * b.norm_sqr() - b * c.norm_sqr()) / d;
+        Circle::new(p[0] + z, z.norm())
+    };
+    [
+        through([inner(0, 1), inner(1, 2), inner(2, 0)]),
+        through([outer(0), outer(1), inner(0, 1)]),
+        through([outer(1), outer(2), inner(1, 2)]),
+        through([outer(2), outer(0), inner(2, 0)]),
+    ]
+}
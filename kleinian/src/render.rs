@@ -0,0 +1,434 @@
+//! Rasterization of limit points into RGBA images.
+
+use crate::pointcloud::Lift;
+use crate::window::CoordTransform;
+use crate::{Cpx, LimitPoint};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+pub type Rgb = [u8; 3];
+
+/// The number of points handled by each task when rendering in parallel.
+#[cfg(feature = "rayon")]
+const PAR_CHUNK: usize = 1 << 14;
+
+/// The number of rows in each tile when rendering in parallel.
+#[cfg(feature = "rayon")]
+const TILE_ROWS: usize = 64;
+
+/// Parses a color of the form `#rrggbb`.
+pub fn parse_color(s: &str) -> Result<Rgb, UnknownName> {
+    let err = || UnknownName(s.to_string());
+    let hex = s.strip_prefix('#').ok_or_else(err)?;
+    if hex.len() != 6 {
+        return Err(err());
+    }
+    let mut rgb = [0; 3];
+    for (i, c) in rgb.iter_mut().enumerate() {
+        *c = u8::from_str_radix(hex.get(2 * i..2 * i + 2).ok_or_else(err)?, 16)
+            .map_err(|_| err())?;
+    }
+    Ok(rgb)
+}
+
+/// The error returned when parsing a style option fails.
+#[derive(Clone, Debug)]
+pub struct UnknownName(pub String);
+
+impl fmt::Display for UnknownName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unrecognized value: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownName {}
+
+/// A map from [0, 1] to colors, used when points are not all drawn in the
+/// foreground color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palette {
+    /// Interpolates between the foreground and background colors.
+    Gradient,
+    Rainbow,
+    Fire,
+}
+
+impl FromStr for Palette {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "gradient" => Ok(Palette::Gradient),
+            "rainbow" => Ok(Palette::Rainbow),
+            "fire" => Ok(Palette::Fire),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
+
+fn lerp(a: Rgb, b: Rgb, t: f64) -> Rgb {
+    let mut c = [0; 3];
+    for i in 0..3 {
+        c[i] = (a[i] as f64 + t * (b[i] as f64 - a[i] as f64)).round() as u8;
+    }
+    c
+}
+
+impl Palette {
+    /// Returns the color at position `t`, which should lie in [0, 1].
+    pub fn sample(&self, t: f64, style: &Style) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            // Stop short of the background so that points remain visible.
+            Palette::Gradient => lerp(style.foreground, style.background, 0.8 * t),
+            Palette::Rainbow => {
+                let h = 6.0 * 0.8 * t;
+                let x = 1.0 - (h % 2.0 - 1.0).abs();
+                let (r, g, b) = match h as u32 {
+                    0 => (1.0, x, 0.0),
+                    1 => (x, 1.0, 0.0),
+                    2 => (0.0, 1.0, x),
+                    3 => (0.0, x, 1.0),
+                    _ => (x, 0.0, 1.0),
+                };
+                [(255.0 * r) as u8, (255.0 * g) as u8, (255.0 * b) as u8]
+            }
+            Palette::Fire => {
+                let r = (3.0 * t).min(1.0);
+                let g = (3.0 * t - 1.0).clamp(0.0, 1.0);
+                let b = (3.0 * t - 2.0).clamp(0.0, 1.0);
+                [(255.0 * r) as u8, (255.0 * g) as u8, (255.0 * b) as u8]
+            }
+        }
+    }
+}
+
+/// Determines the color of each point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorBy {
+    /// Every point is drawn in the foreground color.
+    Uniform,
+    /// The color depends on the generator whose circle was mapped.
+    Generator,
+    /// The color depends on the length of the word.
+    Depth,
+    /// The color depends on the logarithm of the circle's radius.
+    Radius,
+}
+
+impl FromStr for ColorBy {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "uniform" => Ok(ColorBy::Uniform),
+            "generator" => Ok(ColorBy::Generator),
+            "depth" => Ok(ColorBy::Depth),
+            "radius" => Ok(ColorBy::Radius),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
+
+/// How points are turned into pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Each pixel containing a point is drawn in that point's color.
+    Points,
+    /// The number of points landing in each pixel is counted, and the
+    /// counts are tone-mapped, so that dense parts of the limit set are
+    /// drawn more strongly.
+    Density,
+}
+
+impl FromStr for Mode {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "points" => Ok(Mode::Points),
+            "density" => Ok(Mode::Density),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Style {
+    pub foreground: Rgb,
+    pub background: Rgb,
+    pub palette: Palette,
+    pub color_by: ColorBy,
+    /// The side length, in pixels, of the square drawn for each point.
+    pub point_size: usize,
+    pub mode: Mode,
+    /// The exponent applied to the normalized logarithmic density in
+    /// density mode.  Values less than 1 bring out sparse regions.
+    pub gamma: f64,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            foreground: [0, 0, 0],
+            background: [255, 255, 255],
+            palette: Palette::Gradient,
+            color_by: ColorBy::Uniform,
+            point_size: 1,
+            mode: Mode::Points,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// Returns a function giving the color of each of the points.
+    pub(crate) fn colorer<'a>(&'a self, pts: &[LimitPoint]) -> impl Fn(&LimitPoint) -> Rgb + 'a {
+        let max_depth = pts.iter().map(|p| p.depth).max().unwrap_or(1).max(1);
+        let (min_lr, max_lr) = pts
+            .iter()
+            .map(|p| p.radius.ln())
+            .filter(|r| r.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), r| {
+                (lo.min(r), hi.max(r))
+            });
+        let lr_range = (max_lr - min_lr).max(f64::MIN_POSITIVE);
+        move |p| match self.color_by {
+            ColorBy::Uniform => self.foreground,
+            ColorBy::Generator => self.palette.sample(p.generator as f64 / 3.0, self),
+            ColorBy::Depth => self.palette.sample(p.depth as f64 / max_depth as f64, self),
+            ColorBy::Radius => self
+                .palette
+                .sample((max_lr - p.radius.ln()) / lr_range, self),
+        }
+    }
+}
+
+/// Returns the rows and columns of the pixels covered by a point, with the
+/// imaginary axis pointing up, or `None` if the point is outside of the
+/// image.
+fn covered(
+    pt: &LimitPoint,
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    size: usize,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let (x, y) = trans.apply_within(&pt.z, width, height)?;
+    let row = height - 1 - y;
+    let x0 = x.saturating_sub((size - 1) / 2);
+    let y0 = row.saturating_sub((size - 1) / 2);
+    Some((
+        y0..usize::min(y0 + size, height),
+        x0..usize::min(x0 + size, width),
+    ))
+}
+
+/// Calls `f` with the index of every pixel covered by a point, with the
+/// imaginary axis pointing up.  Points outside of the image are skipped.
+#[cfg(not(feature = "rayon"))]
+fn for_each_pixel<F: FnMut(&LimitPoint, usize)>(
+    pts: &[LimitPoint],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    point_size: usize,
+    mut f: F,
+) {
+    let size = point_size.max(1);
+    for pt in pts {
+        if let Some((rows, cols)) = covered(pt, trans, width, height, size) {
+            for r in rows {
+                for col in cols.clone() {
+                    f(pt, r * width + col);
+                }
+            }
+        }
+    }
+}
+
+/// Like `for_each_pixel`, but the image is split into tiles of
+/// `TILE_ROWS` rows, which are drawn in parallel.  The points are first
+/// binned by the tiles that they cover, keeping their order, so the result
+/// is the same as drawing them one at a time.  `buf` holds the same number
+/// of entries for each pixel.  For each point, `f` returns a function that
+/// is called with the entries of each pixel that the point covers.
+#[cfg(feature = "rayon")]
+fn for_each_pixel<T, F, G>(
+    pts: &[LimitPoint],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    point_size: usize,
+    buf: &mut [T],
+    f: F,
+) where
+    T: Send,
+    F: Fn(&LimitPoint) -> G + Sync,
+    G: FnMut(&mut [T]),
+{
+    use rayon::prelude::*;
+    if buf.is_empty() {
+        return;
+    }
+    let size = point_size.max(1);
+    let stride = buf.len() / (width * height).max(1);
+    let num_tiles = height.div_ceil(TILE_ROWS);
+    // Each entry of a bin is the index of a point and the pixels that it
+    // covers.
+    type Bin = Vec<(usize, Range<usize>, Range<usize>)>;
+    let bin = |(k, chunk): (usize, &[LimitPoint])| {
+        let start = k * PAR_CHUNK;
+        let mut bins: Vec<Bin> = vec![Vec::new(); num_tiles];
+        for (i, pt) in chunk.iter().enumerate() {
+            if let Some((rows, cols)) = covered(pt, trans, width, height, size) {
+                for bin in &mut bins[rows.start / TILE_ROWS..=(rows.end - 1) / TILE_ROWS] {
+                    bin.push((start + i, rows.clone(), cols.clone()));
+                }
+            }
+        }
+        bins
+    };
+    let chunk_bins: Vec<Vec<Bin>> = pts.par_chunks(PAR_CHUNK).enumerate().map(bin).collect();
+    buf.par_chunks_mut(TILE_ROWS * width * stride)
+        .enumerate()
+        .for_each(|(t, tile)| {
+            let first = t * TILE_ROWS;
+            for (i, rows, cols) in chunk_bins.iter().flat_map(|bins| &bins[t]) {
+                let mut paint = f(&pts[*i]);
+                for r in rows.start.max(first)..rows.end.min(first + TILE_ROWS) {
+                    for col in cols.clone() {
+                        let idx = ((r - first) * width + col) * stride;
+                        paint(&mut tile[idx..idx + stride]);
+                    }
+                }
+            }
+        });
+}
+
+/// Counts the number of points covering each pixel of a `width` by
+/// `height` image, in row-major order.  With the `rayon` feature, the image
+/// is split into tiles, which are counted in parallel.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(points = pts.len(), width = width, height = height))
+)]
+pub fn density(
+    pts: &[LimitPoint],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    point_size: usize,
+) -> Vec<f32> {
+    let mut counts = vec![0.0; width * height];
+    #[cfg(feature = "rayon")]
+    for_each_pixel(pts, trans, width, height, point_size, &mut counts, |_| {
+        |c: &mut [f32]| c[0] += 1.0
+    });
+    #[cfg(not(feature = "rayon"))]
+    for_each_pixel(pts, trans, width, height, point_size, |_, idx| {
+        counts[idx] += 1.0
+    });
+    counts
+}
+
+/// Converts a density buffer, as returned by `density`, into an RGBA
+/// image.  The densities are scaled logarithmically so that the densest
+/// pixel gets full intensity.
+pub fn tone_map(counts: &[f32], style: &Style) -> Vec<u8> {
+    let max = counts.iter().copied().fold(0.0, f32::max) as f64;
+    let norm = (1.0 + max).ln().max(f64::MIN_POSITIVE);
+    let mut pixel_data = Vec::with_capacity(4 * counts.len());
+    for &c in counts {
+        let color = if c > 0.0 {
+            let t = ((1.0 + c as f64).ln() / norm).powf(style.gamma);
+            match style.color_by {
+                ColorBy::Uniform => lerp(style.background, style.foreground, t),
+                _ => lerp(style.background, style.palette.sample(1.0 - t, style), t),
+            }
+        } else {
+            style.background
+        };
+        pixel_data.extend_from_slice(&color);
+        pixel_data.push(255);
+    }
+    pixel_data
+}
+
+/// Draws the points into a `width` by `height` RGBA image, with the
+/// imaginary axis pointing up.  Points outside of the image are skipped.
+/// With the `rayon` feature, the image is split into tiles, which are drawn
+/// in parallel.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", name = "render", skip_all, fields(points = pts.len(), width = width, height = height))
+)]
+pub fn render_rgba(
+    pts: &[LimitPoint],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    style: &Style,
+) -> Vec<u8> {
+    if style.mode == Mode::Density {
+        let counts = density(pts, trans, width, height, style.point_size);
+        return tone_map(&counts, style);
+    }
+    let mut pixel_data = Vec::with_capacity(4 * width * height);
+    for _ in 0..width * height {
+        pixel_data.extend_from_slice(&style.background);
+        pixel_data.push(255);
+    }
+    let color = style.colorer(pts);
+    #[cfg(feature = "rayon")]
+    for_each_pixel(
+        pts,
+        trans,
+        width,
+        height,
+        style.point_size,
+        &mut pixel_data,
+        |pt| {
+            let c = color(pt);
+            move |px: &mut [u8]| px[..3].copy_from_slice(&c)
+        },
+    );
+    #[cfg(not(feature = "rayon"))]
+    for_each_pixel(pts, trans, width, height, style.point_size, |pt, idx| {
+        pixel_data[4 * idx..4 * idx + 3].copy_from_slice(&color(pt))
+    });
+    pixel_data
+}
+
+/// Draws the points on the Riemann sphere, in an equirectangular projection
+/// whose longitude runs across the image and whose latitude runs up it, so
+/// that the image can be used as an environment map or in 360° viewers.
+/// The sphere is placed by inverse stereographic projection, so 0 is at the
+/// bottom edge, infinity is at the top edge, and the unit circle is the
+/// horizontal center line, with 1 in the middle of the image.  Points at
+/// infinity are drawn along the top edge.  Images are usually twice as
+/// wide as they are high.
+pub fn render_equirect_rgba(
+    pts: &[LimitPoint],
+    width: usize,
+    height: usize,
+    style: &Style,
+) -> Vec<u8> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    let projected: Vec<LimitPoint> = pts
+        .iter()
+        .filter_map(|p| {
+            let [x, y, z] = Lift::Sphere.apply(p.z)?;
+            let lon = y.atan2(x);
+            let lon = if lon >= PI { lon - 2.0 * PI } else { lon };
+            // Keep the poles inside of the image.
+            let lat = z
+                .atan2(x.hypot(y))
+                .clamp(-FRAC_PI_2, FRAC_PI_2 * (1.0 - f64::EPSILON));
+            Some(LimitPoint {
+                z: Cpx::new(lon / (2.0 * PI) * width as f64, lat / PI * height as f64),
+                ..*p
+            })
+        })
+        .collect();
+    let trans = CoordTransform::centered(Cpx::new(0.0, 0.0), 1.0, width, height);
+    render_rgba(&projected, &trans, width, height, style)
+}
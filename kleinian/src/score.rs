@@ -0,0 +1,174 @@
+//! Heuristics for deciding which parameters give interesting pictures.
+
+use crate::dimension::hausdorff_dimension;
+use crate::render::UnknownName;
+use crate::{elliptic_order, CircleQueue, Generator};
+use std::str::FromStr;
+
+/// The word length used to estimate the dimension for `score`.  The
+/// estimate is rough, but the matrix has only 108 rows, so it is cheap
+/// enough to compute for every pixel of a map.
+const DIMENSION_WORD_LENGTH: usize = 4;
+
+/// Consecutive circles along the limit set are counted as touching for
+/// `Score::connectivity` if their centers are at most this many times the
+/// sum of their radii apart.
+const TOUCHING_SLACK: f64 = 2.0;
+
+/// Returns a number between 0 and 1 measuring how quickly the circles
+/// shrink as the group is explored, which is a rough indicator of whether
+/// the group is discrete.
+///
+/// If n circles of radius r cover a set of dimension D, then r is roughly
+/// proportional to n^{-1/D}.  The score compares the largest radius after
+/// `num_circles / 4` and after `num_circles` circles, so it approximates 1/D
+/// when the group is discrete.  When the group is not discrete, the circles
+/// do not shrink and the score is close to 0.  Degenerate groups get a score
+/// of 0.
+pub fn discreteness(gens: [Generator; 4], num_circles: usize) -> f64 {
+    let Ok(mut queue) = CircleQueue::new(gens) else {
+        return 0.0;
+    };
+    let mut explore = |n| {
+        while queue.len() < n {
+            queue.advance().ok()?;
+        }
+        Some((queue.len(), queue.max_radius()))
+    };
+    let (Some((n1, r1)), Some((n2, r2))) = (explore(num_circles / 4), explore(num_circles)) else {
+        return 0.0;
+    };
+    shrink_rate(n1, r1, n2, r2)
+}
+
+/// Returns the score of `discreteness` from the number of circles and the
+/// largest radius at two stages of the exploration.
+fn shrink_rate(n1: usize, r1: f64, n2: usize, r2: f64) -> f64 {
+    let score = (r1 / r2).ln() / (n2 as f64 / n1 as f64).ln();
+    if score.is_finite() {
+        score.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// The ingredients of a composite measure of how interesting the picture
+/// of a group is, returned by `score`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Score {
+    /// As returned by `discreteness`.
+    pub discreteness: f64,
+    /// A rough estimate of the Hausdorff dimension of the limit set, from
+    /// `dimension::hausdorff_dimension`, or `None` if it could not be
+    /// computed, as for groups with elliptic generators.
+    pub dimension: Option<f64>,
+    /// The fraction of consecutive circles along the limit set that nearly
+    /// touch, which is close to 1 when the limit set is connected, as for
+    /// the curves and gaskets on the boundary of the deformation space, and
+    /// close to 0 when it is dust.
+    pub connectivity: f64,
+    /// The fraction of matrices and circles that were not finite or not
+    /// valid, as counted by `diagnostics::Diagnostics`, or 1 if the
+    /// exploration failed.
+    pub degenerate: f64,
+}
+
+impl Score {
+    /// Combines the ingredients into a number between 0 and 1.  Groups that
+    /// are not discrete or that break down numerically score close to 0,
+    /// whatever else is true of them.  Otherwise, connected limit sets and
+    /// limit sets of dimension well above 1, which are not just circles,
+    /// score higher.  Without a dimension, that part counts as half.
+    pub fn total(&self) -> f64 {
+        let complexity = self.dimension.map_or(0.5, |d| (d - 1.0).clamp(0.0, 1.0));
+        let shape = 0.5 * (self.connectivity + complexity);
+        (self.discreteness * (1.0 - self.degenerate) * shape).clamp(0.0, 1.0)
+    }
+}
+
+/// Computes the ingredients of the composite score, exploring the group
+/// once with `num_circles` circles.
+pub fn score(gens: [Generator; 4], num_circles: usize) -> Score {
+    let free = gens[..2]
+        .iter()
+        .all(|g| elliptic_order(&g.matrix).is_none());
+    let dimension = if free {
+        hausdorff_dimension(&gens, DIMENSION_WORD_LENGTH).filter(|d| d.is_finite())
+    } else {
+        None
+    };
+    let failed = Score {
+        dimension,
+        degenerate: 1.0,
+        ..Score::default()
+    };
+    let Ok(mut queue) = CircleQueue::new(gens) else {
+        return failed;
+    };
+    // This also records the words, which `ordered_circles` needs.
+    queue.enable_diagnostics();
+    let mut explore = |n| {
+        while queue.len() < n {
+            queue.advance().ok()?;
+        }
+        Some((queue.len(), queue.max_radius()))
+    };
+    let (Some((n1, r1)), Some((n2, r2))) = (explore(num_circles / 4), explore(num_circles)) else {
+        return failed;
+    };
+    let degenerate = queue.diagnostics().map_or(0.0, |d| {
+        let bad = d.bad_matrices + d.bad_circles + d.invalid_circles;
+        bad as f64 / (d.matrices_checked + d.circles_checked).max(1) as f64
+    });
+    let circles: Vec<_> = queue.ordered_circles().collect();
+    let touching = circles
+        .iter()
+        .zip(circles.iter().cycle().skip(1))
+        .filter(|(c, d)| {
+            let gap = (c.center() - d.center()).norm();
+            gap <= TOUCHING_SLACK * (1.0 / c.radius_inv() + 1.0 / d.radius_inv())
+        })
+        .count();
+    Score {
+        discreteness: shrink_rate(n1, r1, n2, r2),
+        dimension,
+        connectivity: touching as f64 / circles.len().max(1) as f64,
+        degenerate,
+    }
+}
+
+/// A measure of interest that can be shown on a map of parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// `discreteness`, which is cheap.
+    Discreteness,
+    /// The total of `score`.
+    Score,
+}
+
+impl Metric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Metric::Discreteness => "discreteness",
+            Metric::Score => "score",
+        }
+    }
+    /// Returns the value of the metric, between 0 and 1, for the group.
+    pub fn evaluate(&self, gens: [Generator; 4], num_circles: usize) -> f64 {
+        match self {
+            Metric::Discreteness => discreteness(gens, num_circles),
+            Metric::Score => score(gens, num_circles).total(),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = UnknownName;
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "discreteness" => Ok(Metric::Discreteness),
+            "score" => Ok(Metric::Score),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Cross-sections of limit sets by lines and circles.  The points of a limit
+//! set near a curve show how the curve cuts through it, such as the gaps
+//! in a slice of a limit set that looks like a Cantor set, and their
+//! positions along the curve form a one dimensional spectrum of the
+//! fractal.
+
+use crate::depth_first::DepthFirst;
+use crate::{Circle, Cpx, Error, Generator};
+
+const MARGIN: f64 = 8.0;
+
+/// Returns the distance from `z` to the circle or line.
+fn distance(c: &Circle, z: Cpx) -> f64 {
+    if c.is_line() {
+        // The matrix is (0 β; β̄ γ), and (z̄ 1) C (z 1)^T = 2 Re(β̄ z) + γ
+        // is 2|β| times the signed distance.
+        let m = &c.0;
+        let f = m[(0, 1)] * z.conj() + m[(1, 0)] * z + m[(1, 1)];
+        f.re.abs() / (2.0 * m[(1, 0)].norm())
+    } else {
+        ((z - c.center()).norm() - c.radius()).abs()
+    }
+}
+
+/// Returns the limit points within `tol` of the circle or line `c`, in the
+/// order in which they occur along the limit set.  The group is explored
+/// depth first, as in `stream::generate_points_to`, down to circles of
+/// radius `tol` / 2, but only below circles that come near `c`, so the
+/// work depends on the size of the cross-section rather than on that of
+/// the whole limit set.  The points are the centers of the smallest
+/// circles, so they are within `tol` / 2 of the limit set.
+pub fn intersect_with(gens: &[Generator; 4], c: &Circle, tol: f64) -> Result<Vec<Cpx>, Error> {
+    let explorer = DepthFirst::new(gens, 0.5 * tol)?;
+    let mut stack = explorer.roots();
+    let mut pts = Vec::new();
+    while let Some(branch) = stack.pop() {
+        let circle = explorer.circle(&branch);
+        // Lines and circles through infinity are never skipped, since the
+        // distance to their centers means nothing.
+        if !circle.passes_near_infinity()
+            && distance(c, circle.center()) > MARGIN * circle.radius() + tol
+        {
+            continue;
+        }
+        if let Some(z) = explorer.visit(&branch, &mut stack)? {
+            if distance(c, z) <= tol {
+                pts.push(z);
+            }
+        }
+    }
+    Ok(pts)
+}
+
+/// Returns the positions of the points along the circle or line `c`, in
+/// increasing order: the angles around the center, from -π to π, for a
+/// circle, and the signed distances from the point of the line nearest to
+/// the origin for a line.  The gaps between consecutive positions are the
+/// gaps in the cross-section.
+pub fn positions(c: &Circle, pts: &[Cpx]) -> Vec<f64> {
+    let mut pos: Vec<f64> = if c.is_line() {
+        // The line is 2 Re(β̄ z) + γ = 0, whose direction is iβ.
+        let m = &c.0;
+        let dir = Cpx::i() * m[(1, 0)].conj() / m[(1, 0)].norm();
+        pts.iter().map(|z| (z * dir.conj()).re).collect()
+    } else {
+        let center = c.center();
+        pts.iter().map(|z| (z - center).arg()).collect()
+    };
+    pos.sort_by(f64::total_cmp);
+    pos
+}
@@ -0,0 +1,61 @@
+//! Choices of the circle of each generator, which is where exploration
+//! starts.  The circle of a generator g should be close to the boundary of
+//! the region that g maps the outside of the circle of g^{-1} into.  No
+//! single choice works well for every family of groups.
+
+use crate::ford::isometric_hemisphere;
+use crate::render::UnknownName;
+use crate::{Circle, Generator};
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub enum SeedCircles {
+    /// The circles computed by the recipe from the fixed points of the
+    /// commutators with `circle_for_transforms`.  This works well when the
+    /// commutators are parabolic, as they are for the recipes in this
+    /// library.
+    Commutator,
+    /// The isometric circles: the circle of g is the isometric circle of
+    /// g^{-1}, which g maps the isometric circle of g onto.  This works
+    /// well for Schottky groups whose isometric circles are disjoint.  A
+    /// generator that fixes infinity has no isometric circle, and keeps
+    /// the circle from the recipe.
+    Isometric,
+    /// Circles chosen by the user, in the order a, b, a^{-1}, b^{-1}.
+    Custom(Box<[Circle; 4]>),
+}
+
+impl SeedCircles {
+    /// Returns the generators with their circles replaced.
+    pub fn apply(&self, mut gens: [Generator; 4]) -> [Generator; 4] {
+        match self {
+            SeedCircles::Commutator => {}
+            SeedCircles::Isometric => {
+                let inverses: Vec<_> = (0..4).map(|i| gens[(i + 2) % 4].matrix).collect();
+                for (g, m) in gens.iter_mut().zip(inverses) {
+                    if let Some(h) = isometric_hemisphere(&m) {
+                        g.circle = Circle::new(h.center, h.radius);
+                    }
+                }
+            }
+            SeedCircles::Custom(circles) => {
+                for (g, c) in gens.iter_mut().zip(circles.iter()) {
+                    g.circle = *c;
+                }
+            }
+        }
+        gens
+    }
+}
+
+impl FromStr for SeedCircles {
+    type Err = UnknownName;
+    /// Parses the name of a heuristic.  Custom circles have no name.
+    fn from_str(s: &str) -> Result<Self, UnknownName> {
+        match s {
+            "commutator" => Ok(SeedCircles::Commutator),
+            "isometric" => Ok(SeedCircles::Isometric),
+            _ => Err(UnknownName(s.to_string())),
+        }
+    }
+}
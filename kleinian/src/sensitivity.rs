@@ -0,0 +1,124 @@
+//! Derivatives of limit points with respect to the traces of the
+//! generators.  The limit set moves holomorphically as the traces change,
+//! as long as the group stays discrete and free, so each limit point has a
+//! complex derivative with respect to each trace.  Together they form the
+//! deformation field of the limit set, which shows which parts of it move
+//! the most, and in which directions, when a parameter is changed.
+
+use crate::{explore, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+
+/// A limit point, along with its derivatives with respect to tr a and tr b.
+#[derive(Clone, Copy, Debug)]
+pub struct Sensitivity {
+    pub z: Cpx,
+    pub d_ta: Cpx,
+    pub d_tb: Cpx,
+}
+
+/// A matrix along with its derivatives with respect to the two traces,
+/// which multiply as dual numbers.
+#[derive(Clone, Copy)]
+struct Dual {
+    m: Matrix2<Cpx>,
+    d_ta: Matrix2<Cpx>,
+    d_tb: Matrix2<Cpx>,
+}
+
+impl Dual {
+    fn identity() -> Self {
+        Dual {
+            m: Matrix2::identity(),
+            d_ta: Matrix2::zeros(),
+            d_tb: Matrix2::zeros(),
+        }
+    }
+    fn mul(&self, other: &Dual) -> Dual {
+        Dual {
+            m: self.m * other.m,
+            d_ta: self.d_ta * other.m + self.m * other.d_ta,
+            d_tb: self.d_tb * other.m + self.m * other.d_tb,
+        }
+    }
+}
+
+/// Returns the image of `p` under the transformation with matrix `m`, and
+/// the derivative of the image when the matrix changes by `dm`.  `p` may be
+/// infinity, represented as in `algebra::fixed_points`.
+fn apply_with_derivative(m: &Matrix2<Cpx>, dm: &Matrix2<Cpx>, p: Cpx) -> (Cpx, Cpx) {
+    if !p.is_finite() {
+        let (a, c) = (m[(0, 0)], m[(1, 0)]);
+        let (da, dc) = (dm[(0, 0)], dm[(1, 0)]);
+        return (a / c, (da * c - a * dc) / (c * c));
+    }
+    let num = m[(0, 0)] * p + m[(0, 1)];
+    let den = m[(1, 0)] * p + m[(1, 1)];
+    let dnum = dm[(0, 0)] * p + dm[(0, 1)];
+    let dden = dm[(1, 0)] * p + dm[(1, 1)];
+    (num / den, (dnum * den - num * dden) / (den * den))
+}
+
+/// Returns the derivatives of the matrices of `recipe` at `(ta, tb)`, by
+/// central differences.  The matrices are polynomials in the traces and a
+/// few square roots, so this is accurate to about 1e-10.
+fn generator_derivatives<F: Fn(Cpx, Cpx) -> [Generator; 4]>(
+    recipe: &F,
+    ta: Cpx,
+    tb: Cpx,
+) -> [[Matrix2<Cpx>; 4]; 2] {
+    let diff = |f: &dyn Fn(Cpx) -> [Generator; 4], t: Cpx| {
+        let h = 1e-6 * (1.0 + t.norm());
+        let (plus, minus) = (f(t + h), f(t - h));
+        std::array::from_fn(|i| (plus[i].matrix - minus[i].matrix) / Cpx::from(2.0 * h))
+    };
+    [diff(&|t| recipe(t, tb), ta), diff(&|t| recipe(ta, t), tb)]
+}
+
+/// Explores the group given by `recipe(ta, tb)` until there are at least
+/// `num_points` circles, and returns a limit point in each circle along
+/// with its derivatives, in the order in which they occur along the limit
+/// set.  The recipe may be `generators`, or any other function of the
+/// traces that is holomorphic near `(ta, tb)`, such as one that also
+/// applies Nielsen moves or a conjugation.  For the circle of a word w,
+/// the point is the image under w of the center of the circle of the last
+/// letter of w.  This lies in the circle of w, so it is close to its
+/// center, and unlike the center it moves holomorphically.  `generators`
+/// jumps where the discriminant in its formula for tr ab is a negative real
+/// number, as when tr a and tr b are complex conjugates, and the
+/// derivatives are meaningless there.
+pub fn sensitivities<F: Fn(Cpx, Cpx) -> [Generator; 4]>(
+    recipe: F,
+    ta: Cpx,
+    tb: Cpx,
+    num_points: usize,
+) -> Result<Vec<Sensitivity>, Error> {
+    let derivs = generator_derivatives(&recipe, ta, tb);
+    let gens = recipe(ta, tb);
+    let letters: [Dual; 4] = std::array::from_fn(|i| Dual {
+        m: gens[i].matrix,
+        d_ta: derivs[0][i],
+        d_tb: derivs[1][i],
+    });
+    let bases: [Cpx; 4] = std::array::from_fn(|i| gens[i].circle.center_point().affine());
+    let words = explore(gens, num_points, true)?.words();
+    // prefixes[k] is the product of the first k letters of the previous
+    // word, as in `circles_for_words`.
+    let mut prefixes = vec![Dual::identity()];
+    let mut prev: &[u8] = &[];
+    let mut result = Vec::with_capacity(words.len());
+    for word in &words {
+        let common = prev.iter().zip(word).take_while(|(a, b)| a == b).count();
+        prefixes.truncate(common + 1);
+        for &l in &word[common..] {
+            let m = prefixes[prefixes.len() - 1].mul(&letters[l as usize]);
+            prefixes.push(m);
+        }
+        let w = &prefixes[word.len()];
+        let p = bases[*word.last().unwrap() as usize];
+        let (z, d_ta) = apply_with_derivative(&w.m, &w.d_ta, p);
+        let (_, d_tb) = apply_with_derivative(&w.m, &w.d_tb, p);
+        result.push(Sensitivity { z, d_ta, d_tb });
+        prev = word;
+    }
+    Ok(result)
+}
@@ -0,0 +1,130 @@
+//! Generation of points straight to a file, for runs with more points than
+//! fit in memory.  Instead of keeping every circle in a queue, the group is
+//! explored depth first, and each circle is written out as soon as it is
+//! small enough, so memory use only grows with the length of the words.
+//! The points can then be drawn in a second pass with `PointReader` and
+//! `rasterize_points`, which also do not keep them in memory.
+//!
+//! The file starts with an 8 byte header, followed by the real and
+//! imaginary parts of each point as little endian 32-bit floats, which is
+//! accurate enough to draw images of up to a few million pixels across.
+
+use crate::depth_first::{Branch, DepthFirst};
+use crate::window::CoordTransform;
+use crate::{Cpx, Generator};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+/// Identifies point files, and changes whenever their format does.
+const MAGIC: &[u8; 8] = b"KLPTS001";
+
+/// Writes the points found by the explorer below the branches on the
+/// stack, which is emptied, in the order in which they are popped.  Returns
+/// the number of points written.
+pub(crate) fn write_points<W: Write>(
+    explorer: &DepthFirst,
+    mut stack: Vec<Branch>,
+    out: &mut W,
+) -> io::Result<usize> {
+    let mut count = 0;
+    while let Some(branch) = stack.pop() {
+        if let Some(z) = explorer
+            .visit(&branch, &mut stack)
+            .map_err(io::Error::other)?
+        {
+            write_point(out, z)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Writes the header of a file of points.
+pub(crate) fn write_header<W: Write>(out: &mut W) -> io::Result<()> {
+    out.write_all(MAGIC)
+}
+
+/// Writes a point to a file of points.
+pub(crate) fn write_point<W: Write>(out: &mut W, z: Cpx) -> io::Result<()> {
+    out.write_all(&(z.re as f32).to_le_bytes())?;
+    out.write_all(&(z.im as f32).to_le_bytes())
+}
+
+/// Writes the centers of the circles with radius less than `min_radius`
+/// whose parents' circles are not, which are the points that
+/// `generate_points` returns once its largest circle is smaller than
+/// `min_radius`.  The points are written in the order in which they occur
+/// along the limit set.  Returns the number of points written.  Errors in
+/// exploring the group are returned as `io::Error`s wrapping an `Error`.
+pub fn generate_points_to<W: Write>(
+    gens: &[Generator; 4],
+    min_radius: f64,
+    out: W,
+) -> io::Result<usize> {
+    let explorer = DepthFirst::new(gens, min_radius).map_err(io::Error::other)?;
+    let mut out = BufWriter::new(out);
+    write_header(&mut out)?;
+    let count = write_points(&explorer, explorer.roots(), &mut out)?;
+    out.flush()?;
+    Ok(count)
+}
+
+/// Reads the points written by `generate_points_to`, one at a time.
+pub struct PointReader<R: Read> {
+    input: BufReader<R>,
+}
+
+impl<R: Read> PointReader<R> {
+    /// Checks the header, and returns a reader for the points that follow.
+    pub fn new(input: R) -> io::Result<Self> {
+        let mut input = BufReader::new(input);
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a file of points",
+            ));
+        }
+        Ok(PointReader { input })
+    }
+}
+
+impl<R: Read> Iterator for PointReader<R> {
+    type Item = io::Result<Cpx>;
+    fn next(&mut self) -> Option<io::Result<Cpx>> {
+        let mut rec = [0; 8];
+        let mut filled = 0;
+        while filled < rec.len() {
+            match self.input.read(&mut rec[filled..]) {
+                Ok(0) if filled == 0 => return None,
+                Ok(0) => return Some(Err(io::ErrorKind::UnexpectedEof.into())),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let re = f32::from_le_bytes(rec[..4].try_into().unwrap());
+        let im = f32::from_le_bytes(rec[4..].try_into().unwrap());
+        Some(Ok(Cpx::new(re as f64, im as f64)))
+    }
+}
+
+/// Marks the pixels of a `width` by `height` image that the points land in,
+/// in the same layout as `raster::rasterize`, so that the result can be
+/// converted with `raster::mask_to_rgba`.  Points outside of the image are
+/// skipped.  The window can be fitted to a smaller sample of the points,
+/// such as one returned by `generate_points`.
+pub fn rasterize_points<I: IntoIterator<Item = Cpx>>(
+    pts: I,
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+) -> Vec<bool> {
+    let mut mask = vec![false; width * height];
+    for z in pts {
+        if let Some((x, y)) = trans.apply_within(&z, width, height) {
+            mask[(height - 1 - y) * width + x] = true;
+        }
+    }
+    mask
+}
@@ -0,0 +1,286 @@
+//! Subgroups of finite index.  A subgroup H of finite index in the group G
+//! generated by a and b has the same limit set, but a different fundamental
+//! domain, made of one copy of the domain of G for each coset of H, and
+//! different words, so the pictures colored by word differ.  Subgroups are
+//! described by the action of a and b on the cosets, which can be found
+//! from generators of H given as words, or given directly as permutations,
+//! which is the same as giving a homomorphism to a finite group.
+//!
+//! G must be free on a and b, as it is for the groups of `generators`
+//! except at elliptic parameters.
+//!
+//! Words are sequences of generator indices in the order a, b, a^{-1},
+//! b^{-1}, as in `CircleQueue::words`, and act on cosets from the right:
+//! the coset of a word w is Hw, and H is the set of words with coset H,
+//! which is numbered 0.
+
+use crate::algebra::inv;
+use crate::{elliptic_order, word_matrix, CircleQueue, Error, Generator};
+use std::collections::{HashMap, VecDeque};
+
+/// The action of the generators on the cosets of a subgroup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CosetTable {
+    /// `action[l][c]` is the coset c multiplied by the letter l.
+    action: [Vec<usize>; 4],
+}
+
+/// Returns the inverse of the letter.
+fn inverse(l: u8) -> u8 {
+    (l + 2) % 4
+}
+
+/// Returns the word in the letters a, b, A, and B, as accepted by
+/// `parse_letters` with rank 2.
+pub fn format_word(word: &[u8]) -> String {
+    word.iter()
+        .map(|&l| ['a', 'b', 'A', 'B'][l as usize])
+        .collect()
+}
+
+/// A union-find structure for merging vertices while folding.
+struct Classes(Vec<usize>);
+
+impl Classes {
+    fn find(&mut self, mut v: usize) -> usize {
+        while self.0[v] != v {
+            self.0[v] = self.0[self.0[v]];
+            v = self.0[v];
+        }
+        v
+    }
+    /// Merges the classes, and returns whether they were different.
+    fn union(&mut self, u: usize, v: usize) -> bool {
+        let (u, v) = (self.find(u), self.find(v));
+        // Keep the smaller vertex, so that the base vertex 0 stays 0.
+        match u.cmp(&v) {
+            std::cmp::Ordering::Less => self.0[v] = u,
+            std::cmp::Ordering::Greater => self.0[u] = v,
+            std::cmp::Ordering::Equal => return false,
+        }
+        true
+    }
+}
+
+impl CosetTable {
+    /// Returns the table for the subgroup generated by the words.  The
+    /// loops spelled by the words are folded together, as in Stallings'
+    /// algorithm, and the subgroup has finite index exactly when every
+    /// vertex of the folded graph then has an edge of every kind.  Returns
+    /// `Error::InvalidSubgroup` if it does not.
+    pub fn from_words(words: &[Vec<u8>]) -> Result<Self, Error> {
+        // Each edge goes from the first vertex to the last one, and is
+        // labelled by a or b.
+        let mut edges: Vec<(usize, u8, usize)> = Vec::new();
+        let mut vertices = 1;
+        for word in words {
+            let mut v = 0;
+            for (i, &l) in word.iter().enumerate() {
+                let w = if i + 1 == word.len() {
+                    0
+                } else {
+                    vertices += 1;
+                    vertices - 1
+                };
+                if l < 2 {
+                    edges.push((v, l, w));
+                } else {
+                    edges.push((w, inverse(l), v));
+                }
+                v = w;
+            }
+        }
+        let mut classes = Classes((0..vertices).collect());
+        loop {
+            let mut changed = false;
+            let mut forward = HashMap::new();
+            let mut backward = HashMap::new();
+            for &(u, l, v) in &edges {
+                let (u, v) = (classes.find(u), classes.find(v));
+                if let Some(&w) = forward.get(&(u, l)) {
+                    changed |= classes.union(v, w);
+                } else {
+                    forward.insert((u, l), v);
+                }
+                if let Some(&w) = backward.get(&(v, l)) {
+                    changed |= classes.union(u, w);
+                } else {
+                    backward.insert((v, l), u);
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let mut number = vec![usize::MAX; vertices];
+        let mut index = 0;
+        for v in 0..vertices {
+            let c = classes.find(v);
+            if number[c] == usize::MAX {
+                number[c] = index;
+                index += 1;
+            }
+        }
+        let mut action: [Vec<Option<usize>>; 4] = std::array::from_fn(|_| vec![None; index]);
+        for &(u, l, v) in &edges {
+            let (u, v) = (number[classes.find(u)], number[classes.find(v)]);
+            action[l as usize][u] = Some(v);
+            action[inverse(l) as usize][v] = Some(u);
+        }
+        let mut table = [vec![], vec![], vec![], vec![]];
+        for (t, a) in table.iter_mut().zip(action) {
+            *t = a
+                .into_iter()
+                .collect::<Option<Vec<usize>>>()
+                .ok_or(Error::InvalidSubgroup)?;
+        }
+        Ok(CosetTable { action: table })
+    }
+    /// Returns the table for the subgroup of words that fix 0, when a and b
+    /// act on 0, ..., n - 1 by the permutations `a` and `b`, which map i to
+    /// `a[i]` and `b[i]`.  This is the preimage of a point stabilizer under
+    /// the homomorphism to the group generated by the permutations.
+    /// Returns `Error::InvalidSubgroup` if they are not permutations of the
+    /// same size, or do not move 0 to every point.
+    pub fn from_permutations(a: &[usize], b: &[usize]) -> Result<Self, Error> {
+        let n = a.len();
+        if n == 0 || b.len() != n {
+            return Err(Error::InvalidSubgroup);
+        }
+        let invert = |p: &[usize]| {
+            let mut q = vec![usize::MAX; n];
+            for (i, &j) in p.iter().enumerate() {
+                if j >= n || q[j] != usize::MAX {
+                    return Err(Error::InvalidSubgroup);
+                }
+                q[j] = i;
+            }
+            Ok(q)
+        };
+        let table = CosetTable {
+            action: [a.to_vec(), b.to_vec(), invert(a)?, invert(b)?],
+        };
+        if table.transversal().len() != n {
+            return Err(Error::InvalidSubgroup);
+        }
+        Ok(table)
+    }
+    /// Returns the number of cosets, which is the index of the subgroup.
+    pub fn index(&self) -> usize {
+        self.action[0].len()
+    }
+    /// Returns the coset c multiplied by the letter l.
+    pub fn act(&self, c: usize, l: u8) -> usize {
+        self.action[l as usize][c]
+    }
+    /// Returns the coset of the word.
+    pub fn coset(&self, word: &[u8]) -> usize {
+        word.iter().fold(0, |c, &l| self.act(c, l))
+    }
+    /// Returns whether the word is in the subgroup.
+    pub fn contains(&self, word: &[u8]) -> bool {
+        self.coset(word) == 0
+    }
+    /// Returns the shortest word in each coset, choosing the first in the
+    /// order a, b, a^{-1}, b^{-1} among words of the same length.  Every
+    /// prefix of one of the words is another of them.  The images of the
+    /// fundamental domain of G under the words make up a fundamental domain
+    /// of the subgroup.
+    pub fn transversal(&self) -> Vec<Vec<u8>> {
+        let n = self.index();
+        let mut reps: Vec<Option<Vec<u8>>> = vec![None; n];
+        reps[0] = Some(Vec::new());
+        let mut queue = VecDeque::from([0]);
+        while let Some(c) = queue.pop_front() {
+            for l in 0..4 {
+                let d = self.act(c, l);
+                if reps[d].is_none() {
+                    let mut w = reps[c].clone().unwrap();
+                    w.push(l);
+                    reps[d] = Some(w);
+                    queue.push_back(d);
+                }
+            }
+        }
+        reps.into_iter().flatten().collect()
+    }
+    /// Returns free generators of the subgroup, which has rank one more
+    /// than its index.  There is one for each coset c and letter l among a
+    /// and b such that the words t_c l and t_{cl} of the transversal differ
+    /// by more than the letter: the word t_c l t_{cl}^{-1}.
+    pub fn schreier_generators(&self) -> Vec<Vec<u8>> {
+        self.schreier_edges()
+            .into_iter()
+            .map(|(t, l, u)| {
+                let mut w = t;
+                w.push(l);
+                w.extend(u.iter().rev().map(|&l| inverse(l)));
+                w
+            })
+            .collect()
+    }
+    /// Returns the transversal words t, letters l, and transversal words u
+    /// of the Schreier generators t l u^{-1}.
+    fn schreier_edges(&self) -> Vec<(Vec<u8>, u8, Vec<u8>)> {
+        let reps = self.transversal();
+        let mut edges = Vec::new();
+        for c in 0..self.index() {
+            for l in 0..2 {
+                let d = self.act(c, l);
+                let (t, u) = (&reps[c], &reps[d]);
+                let tree = (u.len() == t.len() + 1 && u[..t.len()] == t[..] && u[t.len()] == l)
+                    || (t.len() == u.len() + 1
+                        && t[..u.len()] == u[..]
+                        && t[u.len()] == inverse(l));
+                if !tree {
+                    edges.push((t.clone(), l, u.clone()));
+                }
+            }
+        }
+        edges
+    }
+}
+
+/// Returns the Schreier generators of the subgroup, followed by their
+/// inverses, as `CircleQueue::with_generators` expects.  The circle of the
+/// generator t l u^{-1} is the circle of the word t l, and the circle of
+/// its inverse is the circle of u l^{-1}, which bound the fundamental
+/// domain described in `CosetTable::transversal`.  Returns
+/// `Error::InvalidSubgroup` if a or b is elliptic, since G is then not free.
+pub fn subgroup_generators(
+    gens: &[Generator; 4],
+    table: &CosetTable,
+) -> Result<Vec<Generator>, Error> {
+    if gens[..2]
+        .iter()
+        .any(|g| elliptic_order(&g.matrix).is_some())
+    {
+        return Err(Error::InvalidSubgroup);
+    }
+    let edges = table.schreier_edges();
+    let mut forward = Vec::with_capacity(edges.len());
+    let mut backward = Vec::with_capacity(edges.len());
+    for (t, l, u) in edges {
+        // The letters of the transversal are all below 4.
+        let (mt, mu) = (
+            word_matrix(&t, gens).unwrap(),
+            word_matrix(&u, gens).unwrap(),
+        );
+        let matrix = mt * gens[l as usize].matrix * inv(&mu);
+        forward.push(Generator {
+            matrix,
+            circle: mt * gens[l as usize].circle,
+        });
+        backward.push(Generator {
+            matrix: inv(&matrix),
+            circle: mu * gens[inverse(l) as usize].circle,
+        });
+    }
+    forward.append(&mut backward);
+    Ok(forward)
+}
+
+/// Returns a queue for exploring the subgroup with its Schreier generators.
+pub fn subgroup_queue(gens: &[Generator; 4], table: &CosetTable) -> Result<CircleQueue, Error> {
+    CircleQueue::with_generators(subgroup_generators(gens, table)?)
+}
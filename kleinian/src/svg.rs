@@ -0,0 +1,104 @@
+//! Vector output of limit points and paths as SVG.
+
+use crate::render::{Rgb, Style};
+use crate::window::CoordTransform;
+use crate::{Cpx, LimitPoint};
+use std::fmt::Write;
+
+fn hex(c: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+/// Writes the opening tag and the background of an image.
+fn header(s: &mut String, width: usize, height: usize, style: &Style) {
+    let _ = writeln!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        s,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        hex(style.background)
+    );
+}
+
+/// Draws the points as an SVG image of size `width` by `height`, with the
+/// imaginary axis pointing up.  Each point is a dot whose diameter is the
+/// point size of the style, colored as in `render::render_rgba`.  Points
+/// outside of the image are skipped.  Density mode is not supported, so
+/// the points are always drawn individually.
+pub fn to_svg(
+    pts: &[LimitPoint],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    style: &Style,
+) -> String {
+    let mut s = String::new();
+    header(&mut s, width, height, style);
+    let r = 0.5 * style.point_size.max(1) as f64;
+    let color = style.colorer(pts);
+    for pt in pts {
+        let (x, y) = trans.map(&pt.z);
+        if x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64 {
+            let _ = writeln!(
+                s,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="{}"/>"#,
+                x,
+                height as f64 - y,
+                r,
+                hex(color(pt))
+            );
+        }
+    }
+    s.push_str("</svg>\n");
+    s
+}
+
+/// Draws the polylines, such as those returned by `warp::warp_orbit`, as an
+/// SVG image of size `width` by `height`, with the imaginary axis pointing
+/// up.  The lines are drawn in the foreground color of the style, with the
+/// point size as their width.  Polylines whose bounding boxes lie outside
+/// of the image are skipped.
+pub fn paths_to_svg(
+    lines: &[Vec<Cpx>],
+    trans: &CoordTransform,
+    width: usize,
+    height: usize,
+    style: &Style,
+) -> String {
+    let mut s = String::new();
+    header(&mut s, width, height, style);
+    let _ = writeln!(
+        s,
+        r#"<g fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round">"#,
+        hex(style.foreground),
+        style.point_size.max(1)
+    );
+    for line in lines {
+        let pts: Vec<(f64, f64)> = line
+            .iter()
+            .map(|z| {
+                let (x, y) = trans.map(z);
+                (x, height as f64 - y)
+            })
+            .collect();
+        let (mut x0, mut y0, mut x1, mut y1) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for &(x, y) in &pts {
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+        }
+        if x1 < 0.0 || y1 < 0.0 || x0 > width as f64 || y0 > height as f64 {
+            continue;
+        }
+        s.push_str(r#"<polyline points=""#);
+        for (i, (x, y)) in pts.iter().enumerate() {
+            let sep = if i == 0 { "" } else { " " };
+            let _ = write!(s, "{}{:.2},{:.2}", sep, x, y);
+        }
+        s.push_str("\"/>\n");
+    }
+    s.push_str("</g>\n</svg>\n");
+    s
+}
@@ -0,0 +1,113 @@
+//! The systole of a group, which is its loxodromic element with the
+//! smallest translation length.  It corresponds to the shortest closed
+//! geodesic of the quotient 3-manifold, and the length tends to 0 as the
+//! group approaches a cusp on the boundary of its deformation space.
+
+use crate::algebra::fixed_points;
+use crate::elements::elements;
+use crate::{Cpx, Generator};
+use nalgebra::Matrix2;
+
+/// Elements whose translation length is below this are treated as
+/// elliptic, and are not candidates for the systole.
+const MIN_LENGTH: f64 = 1e-9;
+
+/// Elements with |tr^2 - 4| below this are treated as parabolic.  The
+/// translation length of an element with trace 2 + ε is about 2 sqrt(ε),
+/// so rounding errors in the traces of parabolic elements would otherwise
+/// give them small positive lengths.
+const PARABOLIC_TOLERANCE: f64 = 1e-9;
+
+/// A loxodromic element, found by `systole`.
+#[derive(Clone, Debug)]
+pub struct Systole {
+    /// The word, as a sequence of generator indices as in
+    /// `CircleQueue::with_generators`.
+    pub word: Vec<u8>,
+    pub matrix: Matrix2<Cpx>,
+    /// The complex translation length λ, with tr = ±2 cosh(λ/2).  The real
+    /// part is the distance by which the element moves points of its axis,
+    /// and the imaginary part is the angle by which it turns around it.
+    pub complex_length: Cpx,
+    /// The repelling and attracting fixed points, which are the ends of the
+    /// axis in hyperbolic space.  Infinity is represented as in
+    /// `algebra::fixed_points`.
+    pub axis: [Cpx; 2],
+}
+
+impl Systole {
+    /// Returns the translation length, which is the length of the closed
+    /// geodesic.
+    pub fn length(&self) -> f64 {
+        self.complex_length.re
+    }
+}
+
+/// Returns the complex translation length of the transformation with matrix
+/// `m`, of determinant 1, whose real part is nonnegative.
+pub fn complex_length(m: &Matrix2<Cpx>) -> Cpx {
+    2.0 * (0.5 * m.trace()).acosh()
+}
+
+/// Returns the repelling and attracting fixed points of the loxodromic
+/// transformation with matrix `m`.
+fn axis(m: &Matrix2<Cpx>) -> [Cpx; 2] {
+    let [p, q] = fixed_points(m);
+    let (a, c, d) = (m[(0, 0)], m[(1, 0)], m[(1, 1)]);
+    // The derivative at a finite fixed point z is 1 / (cz + d)^2, and
+    // infinity is attracting when |a| > |d|.
+    let attracting = |z: Cpx| {
+        if z.is_finite() {
+            (c * z + d).norm() > 1.0
+        } else {
+            a.norm() > d.norm()
+        }
+    };
+    if attracting(p) {
+        [q, p]
+    } else {
+        [p, q]
+    }
+}
+
+/// Searches the reduced words of length up to `max_length` in the
+/// generators, listed as in `CircleQueue::with_generators`, for the
+/// loxodromic element with the smallest translation length.  Only words
+/// that are cyclically reduced are tried, since the others are conjugate
+/// to shorter words with the same length.  Of words with the same length,
+/// up to rounding, the first in shortlex order is returned.  Returns `None`
+/// if there are no loxodromic elements among the words.
+pub fn systole(gens: &[Generator], max_length: usize) -> Option<Systole> {
+    let n = gens.len() as u8;
+    let rank = n / 2;
+    let mut best: Option<(Vec<u8>, Matrix2<Cpx>, Cpx)> = None;
+    for (word, m) in elements(gens)
+        .skip(1)
+        .take_while(|(w, _)| w.len() <= max_length)
+    {
+        let (first, last) = (word[0], word[word.len() - 1]);
+        if word.len() > 1 && first == (last + rank) % n {
+            continue;
+        }
+        let t = m.trace();
+        if (t * t - 4.0).norm() < PARABOLIC_TOLERANCE * (1.0 + t.norm_sqr()) {
+            continue;
+        }
+        let lambda = complex_length(&m);
+        if lambda.re <= MIN_LENGTH || lambda.re.is_nan() {
+            continue;
+        }
+        let shorter = best
+            .as_ref()
+            .is_none_or(|(_, _, l)| lambda.re < l.re * (1.0 - 1e-12));
+        if shorter {
+            best = Some((word, m, lambda));
+        }
+    }
+    best.map(|(word, matrix, complex_length)| Systole {
+        axis: axis(&matrix),
+        word,
+        matrix,
+        complex_length,
+    })
+}
@@ -0,0 +1,94 @@
+//! Export of labelled limit points as Apache Arrow tables, which can be
+//! written as Arrow IPC or (with the `parquet` feature) Parquet files and
+//! analyzed with tools such as DuckDB and Polars.
+//!
+//! Each row is one point, in the order in which the points occur along
+//! the limit set, with the columns
+//!
+//! - `re`, `im`: the point.
+//! - `radius`: the radius of the circle that the point is the center of.
+//! - `generator`: the index of the last letter of the word, in the order
+//!   a, b, a^{-1}, b^{-1}.
+//! - `depth`: the length of the word.
+//! - `word_hash`: a hash of the word, which is stable across runs and
+//!   platforms, so that rows from different runs can be joined.
+//! - `weight`: the share of the limit set that the point stands for, if
+//!   each circle's share is split evenly among its three children.  The
+//!   weights sum to 1.
+
+use crate::CircleQueue;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, UInt32Array, UInt64Array, UInt8Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Returns the 64 bit FNV-1a hash of the word.
+pub fn word_hash(word: &[u8]) -> u64 {
+    word.iter().fold(0xcbf29ce484222325, |h, &c| {
+        (h ^ c as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("re", DataType::Float64, false),
+        Field::new("im", DataType::Float64, false),
+        Field::new("radius", DataType::Float64, false),
+        Field::new("generator", DataType::UInt8, false),
+        Field::new("depth", DataType::UInt32, false),
+        Field::new("word_hash", DataType::UInt64, false),
+        Field::new("weight", DataType::Float64, false),
+    ])
+}
+
+/// Returns a table of the points currently in the queue, whose words must
+/// have been recorded, as in `CircleQueue::enable_words`.
+pub fn record_batch(queue: &CircleQueue) -> Result<RecordBatch, ArrowError> {
+    let rows = queue.ordered_labelled_points();
+    let f64s = |f: fn(&crate::LimitPoint) -> f64| -> ArrayRef {
+        Arc::new(rows.iter().map(|(p, _)| f(p)).collect::<Float64Array>())
+    };
+    let columns: Vec<ArrayRef> = vec![
+        f64s(|p| p.z.re),
+        f64s(|p| p.z.im),
+        f64s(|p| p.radius),
+        Arc::new(
+            rows.iter()
+                .map(|(p, _)| p.generator)
+                .collect::<UInt8Array>(),
+        ),
+        Arc::new(
+            rows.iter()
+                .map(|(p, _)| p.depth as u32)
+                .collect::<UInt32Array>(),
+        ),
+        Arc::new(
+            rows.iter()
+                .map(|(_, w)| word_hash(w))
+                .collect::<UInt64Array>(),
+        ),
+        f64s(|p| 0.25 * 3f64.powi(1 - p.depth as i32)),
+    ];
+    RecordBatch::try_new(Arc::new(schema()), columns)
+}
+
+/// Writes the points currently in the queue as an Arrow IPC file.
+pub fn write_arrow<W: Write>(out: W, queue: &CircleQueue) -> Result<(), ArrowError> {
+    let batch = record_batch(queue)?;
+    let mut writer = arrow_ipc::writer::FileWriter::try_new(out, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()
+}
+
+/// Writes the points currently in the queue as a Parquet file.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: Write + Send>(
+    out: W,
+    queue: &CircleQueue,
+) -> Result<(), parquet::errors::ParquetError> {
+    let batch = record_batch(queue)?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(out, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
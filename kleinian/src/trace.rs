@@ -0,0 +1,379 @@
+//! Traces of words as polynomials in tr a, tr b, and tr ab.  By a theorem
+//! of Fricke, the trace of any word in a, b, and their inverses is a
+//! polynomial with integer coefficients in x = tr a, y = tr b, and
+//! z = tr ab, the same for every pair of matrices of determinant 1, so the
+//! traces of the words of a recipe are determined by the traces that it
+//! was given.  The polynomial shows where a word becomes parabolic, as the
+//! Farey polynomials of `maskit` do for the Farey words.
+//!
+//! Words are sequences of generator indices in the order a, b, a^{-1},
+//! b^{-1}, as in `parse_letters`.  The polynomial is found with the
+//! identities tr MN = tr M tr N - tr MN^{-1} and a^{-1} = tr a - a, which
+//! shorten the word or remove an inverse at each step.  To find the traces
+//! of many words at given traces of the generators, `word_traces` is much
+//! faster than evaluating their polynomials.
+
+use crate::elements::inverse_word;
+use crate::Cpx;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A polynomial in x = tr a, y = tr b, and z = tr ab, with the coefficient
+/// of x^i y^j z^k at `[i, j, k]`.  Terms with coefficient 0 are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TracePolynomial {
+    pub terms: BTreeMap<[u32; 3], i64>,
+}
+
+impl TracePolynomial {
+    fn constant(c: i64) -> Self {
+        Self::monomial(c, [0, 0, 0])
+    }
+    fn monomial(c: i64, powers: [u32; 3]) -> Self {
+        let mut terms = BTreeMap::new();
+        if c != 0 {
+            terms.insert(powers, c);
+        }
+        TracePolynomial { terms }
+    }
+    /// Returns `self + sign * other`.
+    fn add(&self, other: &Self, sign: i64) -> Self {
+        let mut terms = self.terms.clone();
+        for (powers, c) in &other.terms {
+            let sum = terms.get(powers).copied().unwrap_or(0) + sign * c;
+            if sum == 0 {
+                terms.remove(powers);
+            } else {
+                terms.insert(*powers, sum);
+            }
+        }
+        TracePolynomial { terms }
+    }
+    fn mul(&self, other: &Self) -> Self {
+        let mut product = TracePolynomial::default();
+        for (p, c) in &self.terms {
+            for (q, d) in &other.terms {
+                let powers = [p[0] + q[0], p[1] + q[1], p[2] + q[2]];
+                product = product.add(&TracePolynomial::monomial(c * d, powers), 1);
+            }
+        }
+        product
+    }
+    /// Returns the value of the polynomial at `[tr a, tr b, tr ab]`.
+    pub fn eval(&self, [ta, tb, tab]: [Cpx; 3]) -> Cpx {
+        self.terms
+            .iter()
+            .map(|(p, &c)| c as f64 * ta.powu(p[0]) * tb.powu(p[1]) * tab.powu(p[2]))
+            .sum()
+    }
+    /// Returns the total degree, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<u32> {
+        self.terms.keys().map(|p| p[0] + p[1] + p[2]).max()
+    }
+}
+
+/// Writes the polynomial in terms of ta, tb, and tab, such as
+/// `-ta tb tab + ta^2 + tb^2 + tab^2 - 2` for the commutator.
+impl fmt::Display for TracePolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        // The terms of highest degree come first, and then those with the
+        // highest powers of ta and tb.
+        let mut terms: Vec<(&[u32; 3], &i64)> = self.terms.iter().collect();
+        terms.sort_by_key(|(p, _)| std::cmp::Reverse((p[0] + p[1] + p[2], **p)));
+        for (n, (p, &c)) in terms.into_iter().enumerate() {
+            let sign = if c < 0 { "-" } else { "+" };
+            if n == 0 {
+                if c < 0 {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            let vars: Vec<String> = ["ta", "tb", "tab"]
+                .iter()
+                .zip(p)
+                .filter(|(_, &k)| k > 0)
+                .map(|(v, &k)| match k {
+                    1 => v.to_string(),
+                    _ => format!("{}^{}", v, k),
+                })
+                .collect();
+            if c.abs() != 1 || vars.is_empty() {
+                write!(f, "{}", c.abs())?;
+                if !vars.is_empty() {
+                    write!(f, " ")?;
+                }
+            }
+            write!(f, "{}", vars.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the inverse of the letter.
+fn inverse(l: u8) -> u8 {
+    (l + 2) % 4
+}
+
+/// Cancels letters next to their inverses, including around the end of the
+/// word, which does not change the trace.
+fn reduce(word: &[u8]) -> Vec<u8> {
+    let mut reduced: Vec<u8> = Vec::with_capacity(word.len());
+    for &l in word {
+        if reduced.last() == Some(&inverse(l)) {
+            reduced.pop();
+        } else {
+            reduced.push(l);
+        }
+    }
+    let mut start = 0;
+    while reduced.len() >= start + 2 && reduced[start] == inverse(reduced[reduced.len() - 1]) {
+        start += 1;
+        reduced.pop();
+    }
+    reduced.drain(..start);
+    reduced
+}
+
+/// Returns the smallest of the rotations of the word and of its inverse,
+/// which all have the same trace.
+fn canonical(word: &[u8]) -> Vec<u8> {
+    let inverse = inverse_word(word, 2);
+    (0..word.len().max(1))
+        .flat_map(|k| {
+            [word, &inverse[..]].map(|w| {
+                let mut r = w.to_vec();
+                r.rotate_left(k.min(w.len()));
+                r
+            })
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Computes trace polynomials, remembering those of the shorter words that
+/// they are computed from, which recur often.
+#[derive(Default)]
+pub struct TraceCache {
+    known: HashMap<Vec<u8>, TracePolynomial>,
+}
+
+impl TraceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the trace of the word as a polynomial in tr a, tr b, and
+    /// tr ab.
+    pub fn polynomial(&mut self, word: &[u8]) -> TracePolynomial {
+        let word = canonical(&reduce(word));
+        if let Some(p) = self.known.get(&word) {
+            return p.clone();
+        }
+        let p = self.compute(&word);
+        self.known.insert(word, p.clone());
+        p
+    }
+    fn compute(&mut self, word: &[u8]) -> TracePolynomial {
+        let n = word.len();
+        let var = |l: u8| {
+            let mut powers = [0, 0, 0];
+            powers[(l % 2) as usize] = 1;
+            TracePolynomial::monomial(1, powers)
+        };
+        match n {
+            0 => return TracePolynomial::constant(2),
+            1 => return var(word[0]),
+            2 if word[0] % 2 != word[1] % 2 => {
+                // The word is a or b followed by the other, or by their
+                // inverses.
+                let z = TracePolynomial::monomial(1, [0, 0, 1]);
+                return if (word[0] < 2) == (word[1] < 2) {
+                    z
+                } else {
+                    // tr ab^{-1} = tr a tr b - tr ab.
+                    TracePolynomial::monomial(1, [1, 1, 0]).add(&z, -1)
+                };
+            }
+            _ => {}
+        }
+        // Some generator occurs twice, since the word is not one of the
+        // above.  A repeated letter is used if there is one.
+        let pairs = (0..n).flat_map(|j| (0..j).map(move |i| (i, j)));
+        let (i, j) = pairs
+            .clone()
+            .find(|&(i, j)| word[i] == word[j])
+            .or_else(|| pairs.clone().find(|&(i, j)| word[i] % 2 == word[j] % 2))
+            .unwrap();
+        // Rotate the word so that it is P g Q h, with h the letter at j.
+        let mut rotated = word.to_vec();
+        rotated.rotate_left(j + 1);
+        let at = i + n - j - 1;
+        let (p, q) = (&rotated[..at], &rotated[at + 1..n - 1]);
+        let (g, h) = (rotated[at], rotated[n - 1]);
+        let cat = |parts: &[&[u8]]| parts.concat();
+        if g == h {
+            // tr PgQg = tr Pg tr Qg - tr PQ^{-1}, from the identity with
+            // M = Pg and N = Qg.
+            let pg = self.polynomial(&cat(&[p, &[g]]));
+            let qg = self.polynomial(&cat(&[q, &[g]]));
+            let pq = self.polynomial(&cat(&[p, &inverse_word(q, 2)]));
+            pg.mul(&qg).add(&pq, -1)
+        } else {
+            // g = h^{-1} = tr h - h, so tr PgQh = tr h tr PQh - tr PhQh.
+            let pqh = self.polynomial(&cat(&[p, q, &[h]]));
+            let phqh = self.polynomial(&cat(&[p, &[h], q, &[h]]));
+            var(h).mul(&pqh).add(&phqh, -1)
+        }
+    }
+}
+
+/// Returns the trace of the word as a polynomial in tr a, tr b, and tr ab.
+/// Use a `TraceCache` to find the traces of many words.
+pub fn trace_polynomial(word: &[u8]) -> TracePolynomial {
+    TraceCache::new().polynomial(word)
+}
+
+/// Returns the trace of the word for a group with traces
+/// `[tr a, tr b, tr ab]`.
+pub fn word_trace(word: &[u8], traces: [Cpx; 3]) -> Cpx {
+    trace_polynomial(word).eval(traces)
+}
+
+/// The element of a word, as the coefficients of 1, a, b, and ab.  Every
+/// 2×2 matrix is a combination of these when a and b have no common fixed
+/// point, and by the Cayley–Hamilton theorem, products of them can be
+/// written as combinations again with coefficients that are polynomials in
+/// tr a, tr b, and tr ab.
+type Coefficients = [Cpx; 4];
+
+/// Iterates over the reduced words of length from 1 to a maximum, with
+/// their traces, as returned by `word_traces`.
+pub struct WordTraces {
+    traces: [Cpx; 3],
+    max_len: usize,
+    stack: Vec<(Vec<u8>, Coefficients)>,
+}
+
+impl WordTraces {
+    /// Returns the coefficients of the element times the letter.
+    fn times(&self, c: &Coefficients, letter: u8) -> Coefficients {
+        let [x, y, z] = self.traces;
+        // a^2 = x a - 1, ba = y a + x b + (z - xy) - ab, and
+        // aba = -y + z a + b.
+        let times_a = [
+            -c[1] + (z - x * y) * c[2] - y * c[3],
+            c[0] + x * c[1] + y * c[2] + z * c[3],
+            x * c[2] + c[3],
+            -c[2],
+        ];
+        // b^2 = y b - 1 and ab^2 = y ab - a.
+        let times_b = [-c[2], -c[3], c[0] + y * c[2], c[1] + y * c[3]];
+        // a^{-1} = x - a and b^{-1} = y - b.
+        match letter {
+            0 => times_a,
+            1 => times_b,
+            2 => std::array::from_fn(|k| x * c[k] - times_a[k]),
+            _ => std::array::from_fn(|k| y * c[k] - times_b[k]),
+        }
+    }
+}
+
+impl Iterator for WordTraces {
+    type Item = (Vec<u8>, Cpx);
+    fn next(&mut self) -> Option<(Vec<u8>, Cpx)> {
+        let (word, c) = self.stack.pop()?;
+        if word.len() < self.max_len {
+            let last = word[word.len() - 1];
+            // The children are pushed in reverse, so that they come out
+            // in order.
+            for l in (0..4).rev().filter(|&l| l != inverse(last)) {
+                let mut child = word.clone();
+                child.push(l);
+                let product = self.times(&c, l);
+                self.stack.push((child, product));
+            }
+        }
+        let [x, y, z] = self.traces;
+        let trace = 2.0 * c[0] + x * c[1] + y * c[2] + z * c[3];
+        Some((word, trace))
+    }
+}
+
+/// Returns the reduced words of length from 1 to `max_len`, in
+/// lexicographic order of their letters, with their traces for a group
+/// with traces `[tr a, tr b, tr ab]`.  Each trace is found from the parent
+/// word's with a few multiplications, by writing the elements in terms of
+/// 1, a, b, and ab as the traces allow, so no matrices are needed and the
+/// whole tree costs about as much as its number of words, 4·3^{n-1} words
+/// of length n.  This is the way to look for accidental parabolics, the
+/// words other than conjugates of powers of the commutator with trace ±2.
+pub fn word_traces(traces: [Cpx; 3], max_len: usize) -> WordTraces {
+    let [x, y, _] = traces;
+    let (zero, one) = (Cpx::from(0.0), Cpx::from(1.0));
+    let letters = [
+        [zero, one, zero, zero],
+        [zero, zero, one, zero],
+        [x, -one, zero, zero],
+        [y, zero, -one, zero],
+    ];
+    let stack = if max_len == 0 {
+        Vec::new()
+    } else {
+        (0..4)
+            .rev()
+            .map(|l| (vec![l], letters[l as usize]))
+            .collect()
+    };
+    WordTraces {
+        traces,
+        max_len,
+        stack,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Matrix2;
+
+    /// Returns a, b, a^{-1}, and b^{-1} for a pair of matrices of
+    /// determinant 1 with no common fixed point, along with
+    /// `[tr a, tr b, tr ab]`.  The entries are Gaussian integers, so the
+    /// traces of short words are computed exactly either way.
+    fn letters() -> ([Matrix2<Cpx>; 4], [Cpx; 3]) {
+        let (one, i) = (Cpx::from(1.0), Cpx::i());
+        let a = Matrix2::new(one, one + i, i, i);
+        let b = Matrix2::new(2.0 * one, i, i, 0.0 * one);
+        let inv = crate::algebra::inv;
+        (
+            [a, b, inv(&a), inv(&b)],
+            [a.trace(), b.trace(), (a * b).trace()],
+        )
+    }
+
+    fn matrix_trace(word: &[u8], letters: &[Matrix2<Cpx>; 4]) -> Cpx {
+        word.iter()
+            .fold(Matrix2::identity(), |m, &l| m * letters[l as usize])
+            .trace()
+    }
+
+    #[test]
+    fn traces_match_matrices() {
+        let (letters, traces) = letters();
+        let found: Vec<(Vec<u8>, Cpx)> = word_traces(traces, 7).collect();
+        let count: usize = (1..=7).map(|n| 4 * 3usize.pow(n - 1)).sum();
+        assert_eq!(found.len(), count);
+        for (word, t) in &found {
+            let expected = matrix_trace(word, &letters);
+            assert!((t - expected).norm() < 1e-14, "{word:?}: {t} != {expected}");
+            let polynomial = word_trace(word, traces);
+            assert!(
+                (polynomial - expected).norm() < 1e-14,
+                "{word:?}: {polynomial} != {expected}"
+            );
+        }
+    }
+}
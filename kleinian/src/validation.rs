@@ -0,0 +1,151 @@
+//! Checks that a set of generators is what the rest of the library expects,
+//! before exploring it.  Each check is reported as a residual, which is 0
+//! for exact generators, so that frontends can show how far off a group is
+//! rather than just whether it passed.
+
+use crate::algebra::inv;
+use crate::elementary::{elementary, Elementary};
+use crate::{Circle, Cpx, Generator};
+use nalgebra::Matrix2;
+use std::fmt;
+
+/// Residuals larger than this are reported as failures.
+pub const TOLERANCE: f64 = 1e-6;
+
+/// The results of `validate`.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    /// The largest value of |det g - 1| over the generators g.
+    pub determinant: f64,
+    /// The largest entry of g h - 1, where h is the generator listed as the
+    /// inverse of g.
+    pub inverse: f64,
+    /// The trace of the commutator aba^{-1}b^{-1} and the value that it was
+    /// expected to have, if one was given to `validate`.
+    pub commutator_trace: Option<(Cpx, Cpx)>,
+    /// The largest difference, relative to the size of the entries, between
+    /// the circle of g and the image under g of the circle of g^{-1}.  The
+    /// circles of a fundamental domain are paired up this way.
+    pub pairing: f64,
+    /// The pairs of generators whose circles cross each other, along with
+    /// the absolute value of the inversive distance between the circles,
+    /// which is less than 1.
+    /// The group is only sure to be discrete if there are none, but the
+    /// circles chosen with `circle_for_transforms` usually overlap a little,
+    /// since there is usually no fundamental domain bounded by circles, so
+    /// this is a warning rather than a failure.
+    pub overlapping: Vec<(usize, usize, f64)>,
+    /// How the group is elementary, for two generators, in which case its
+    /// limit set has at most two points.  This is also a warning, since it
+    /// is a property of the group rather than an error in the generators.
+    pub elementary: Option<Elementary>,
+}
+
+impl ValidationReport {
+    /// Returns the distance of the commutator trace from the expected value,
+    /// or 0 if none was given.
+    pub fn commutator_residual(&self) -> f64 {
+        self.commutator_trace.map_or(0.0, |(t, e)| (t - e).norm())
+    }
+    /// Returns whether every residual is within `TOLERANCE`.
+    pub fn is_ok(&self) -> bool {
+        // Written so that NaN residuals fail.
+        [
+            self.determinant,
+            self.inverse,
+            self.commutator_residual(),
+            self.pairing,
+        ]
+        .iter()
+        .all(|&r| r <= TOLERANCE)
+    }
+}
+
+/// Returns the largest absolute value of the entries.
+fn size(m: &Matrix2<Cpx>) -> f64 {
+    m.iter().map(|z| z.norm()).fold(0.0, f64::max)
+}
+
+/// Returns the absolute value of the inversive distance between the
+/// circles, which is |d^2 - r^2 - s^2| / 2rs for circles of radii r and s
+/// whose centers are d apart, and is defined for lines as well.  It is 1
+/// for tangent circles and less than 1 for circles that cross.  The sign
+/// says whether the disks or a disk and the outside of the other are
+/// apart, but which side of each circle is meant is not recorded.
+fn inversive_distance(c: &Circle, d: &Circle) -> f64 {
+    let (c, d) = (&c.0, &d.0);
+    (0.5 * (c[(0, 0)].re * d[(1, 1)].re + c[(1, 1)].re * d[(0, 0)].re)
+        - (c[(0, 1)] * d[(0, 1)].conj()).re)
+        .abs()
+}
+
+/// Checks the generators, which are listed as in
+/// `CircleQueue::with_generators`.  For two generators, the trace of the
+/// commutator is compared with `commutator_trace`, which is -2 for
+/// `generators` and `generators_from_matrices`.
+pub fn validate(gens: &[Generator], commutator_trace: Option<Cpx>) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let n = gens.len();
+    let rank = n / 2;
+    for (i, g) in gens.iter().enumerate() {
+        report.determinant = report
+            .determinant
+            .max((g.matrix.determinant() - 1.0).norm());
+        let h = &gens[(i + rank) % n];
+        report.inverse = report
+            .inverse
+            .max(size(&(g.matrix * h.matrix - Matrix2::identity())));
+        let image = g.matrix * h.circle;
+        let scale = size(&g.circle.0).max(1.0);
+        let diff = size(&(image.0 - g.circle.0)).min(size(&(image.0 + g.circle.0)));
+        report.pairing = report.pairing.max(diff / scale);
+    }
+    if let Ok(gens) = <&[Generator; 4]>::try_from(gens) {
+        report.elementary = elementary(gens);
+    }
+    if let (2, Some(expected)) = (rank, commutator_trace) {
+        let (a, b) = (gens[0].matrix, gens[1].matrix);
+        let t = (a * b * inv(&a) * inv(&b)).trace();
+        report.commutator_trace = Some((t, expected));
+    }
+    for i in 0..n {
+        for j in i + 1..n {
+            let dist = inversive_distance(&gens[i].circle, &gens[j].circle);
+            if dist < 1.0 - TOLERANCE || dist.is_nan() {
+                report.overlapping.push((i, j, dist));
+            }
+        }
+    }
+    report
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "determinant residual {:e}", self.determinant)?;
+        writeln!(f, "inverse residual {:e}", self.inverse)?;
+        if let Some((t, e)) = self.commutator_trace {
+            writeln!(
+                f,
+                "commutator trace {} (expected {}, residual {:e})",
+                t,
+                e,
+                self.commutator_residual()
+            )?;
+        }
+        writeln!(f, "circle pairing residual {:e}", self.pairing)?;
+        if self.overlapping.is_empty() {
+            writeln!(f, "no overlapping circles")?;
+        }
+        for (i, j, dist) in &self.overlapping {
+            writeln!(
+                f,
+                "warning: circles {} and {} overlap (inversive distance {:.6})",
+                i, j, dist
+            )?;
+        }
+        if let Some(e) = self.elementary {
+            writeln!(f, "warning: the group is elementary ({})", e)?;
+        }
+        writeln!(f, "{}", if self.is_ok() { "ok" } else { "failed" })
+    }
+}
@@ -0,0 +1,123 @@
+//! Logging of limit sets to the Rerun viewer, with the `rerun` feature.
+//! The viewer shows the logged points and circles interactively, and
+//! animations as frames on a timeline that can be scrubbed, so that a
+//! family of groups can be explored without writing any UI.
+//!
+//! The complex plane is logged with the imaginary axis pointing up, as in
+//! `render`, so the imaginary parts are negated, since 2D views in Rerun
+//! have the y axis pointing down.  Points and circles that are not finite,
+//! such as the circles of lines, are left out.
+
+use crate::morph::GroupPath;
+use crate::{generate_circles, Circle, Cpx, Generator};
+use rerun::{Ellipses2D, Points2D, RecordingStream, RecordingStreamResult, Scalars};
+
+/// The timeline on which `log_frames` logs the frames of an animation.
+pub const FRAME_TIMELINE: &str = "frame";
+
+fn position(z: Cpx) -> (f32, f32) {
+    (z.re as f32, -z.im as f32)
+}
+
+/// Logs the points at `entity`.
+pub fn log_points(
+    rec: &RecordingStream,
+    entity: &str,
+    points: &[Cpx],
+) -> RecordingStreamResult<()> {
+    let positions = points
+        .iter()
+        .filter(|z| z.is_finite())
+        .map(|&z| position(z));
+    rec.log(entity, &Points2D::new(positions))
+}
+
+/// Logs the centers of the circles at `entity/points`, and the circles
+/// themselves at `entity/circles`.
+pub fn log_circles(
+    rec: &RecordingStream,
+    entity: &str,
+    circles: &[Circle],
+) -> RecordingStreamResult<()> {
+    let finite: Vec<(Cpx, f32)> = circles
+        .iter()
+        .map(|c| (c.center(), (1.0 / c.radius_inv().abs()) as f32))
+        .filter(|(z, r)| z.is_finite() && r.is_finite())
+        .collect();
+    let centers: Vec<(f32, f32)> = finite.iter().map(|&(z, _)| position(z)).collect();
+    rec.log(
+        format!("{entity}/points"),
+        &Points2D::new(centers.iter().copied()),
+    )?;
+    rec.log(
+        format!("{entity}/circles"),
+        &Ellipses2D::from_centers_and_half_sizes(centers, finite.iter().map(|&(_, r)| (r, r))),
+    )
+}
+
+/// Logs the circles of the generators at `entity`.
+pub fn log_generators(
+    rec: &RecordingStream,
+    entity: &str,
+    gens: &[Generator; 4],
+) -> RecordingStreamResult<()> {
+    let circles: Vec<Circle> = gens.iter().map(|g| g.circle).collect();
+    log_circles(rec, entity, &circles)
+}
+
+/// Logs `frames` frames of an animation, for t from 0 to 1, where `path`
+/// gives the generators at time t.  Frame i is logged at step i of
+/// `FRAME_TIMELINE`, with t at `entity/t`, the real and imaginary parts of
+/// tr a, tr b, and tr ab at `entity/traces`, the generator circles at
+/// `entity/generators`, and about `num_points` circles of the limit set at
+/// `entity/limit`, as in `log_circles`.  The limit set is cleared at the
+/// frames at which the exploration fails, such as groups that are not
+/// discrete.
+pub fn log_frames<F: FnMut(f64) -> [Generator; 4]>(
+    rec: &RecordingStream,
+    entity: &str,
+    frames: usize,
+    num_points: usize,
+    mut path: F,
+) -> RecordingStreamResult<()> {
+    for i in 0..frames {
+        let t = if frames > 1 {
+            i as f64 / (frames - 1) as f64
+        } else {
+            0.0
+        };
+        rec.set_time_sequence(FRAME_TIMELINE, i as i64);
+        rec.log(format!("{entity}/t"), &Scalars::single(t))?;
+        let gens = path(t);
+        let (a, b) = (gens[0].matrix, gens[1].matrix);
+        let traces = [("a", a.trace()), ("b", b.trace()), ("ab", (a * b).trace())];
+        for (name, tr) in traces {
+            rec.log(
+                format!("{entity}/traces/{name}/re"),
+                &Scalars::single(tr.re),
+            )?;
+            rec.log(
+                format!("{entity}/traces/{name}/im"),
+                &Scalars::single(tr.im),
+            )?;
+        }
+        log_generators(rec, &format!("{entity}/generators"), &gens)?;
+        match generate_circles(gens, num_points) {
+            Ok(circles) => log_circles(rec, &format!("{entity}/limit"), &circles)?,
+            Err(_) => rec.log(format!("{entity}/limit"), &rerun::Clear::recursive())?,
+        }
+    }
+    Ok(())
+}
+
+/// Logs the frames of the animation along the path, as in `log_frames`,
+/// with the traces on the consistent branch that the path follows.
+pub fn log_group_path(
+    rec: &RecordingStream,
+    entity: &str,
+    path: &GroupPath,
+    frames: usize,
+    num_points: usize,
+) -> RecordingStreamResult<()> {
+    log_frames(rec, entity, frames, num_points, |t| path.generators_at(t))
+}
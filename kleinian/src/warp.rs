@@ -0,0 +1,157 @@
+//! Images of vector paths under Möbius transformations.  The image of a
+//! line segment or a Bézier curve is usually not a curve of the same kind,
+//! so the images are approximated by polylines, which are subdivided until
+//! they are within a tolerance of the true image.  Mapping a motif through
+//! the elements of the group in this way gives a drawing that can be
+//! exported with `svg::paths_to_svg`.
+
+use crate::cyclic::{apply_all, mobius};
+use crate::{CircleQueue, Cpx, Error, Generator};
+use nalgebra::Matrix2;
+
+/// A piece of a path, which starts where the previous piece ends.
+#[derive(Clone, Copy, Debug)]
+pub enum Segment {
+    /// A line segment to the given point.
+    Line(Cpx),
+    /// A cubic Bézier curve with the given two control points, ending at
+    /// the last point.
+    Cubic(Cpx, Cpx, Cpx),
+}
+
+impl Segment {
+    /// Returns the point where the segment ends.
+    fn end(&self) -> Cpx {
+        match *self {
+            Segment::Line(end) | Segment::Cubic(_, _, end) => end,
+        }
+    }
+}
+
+/// A path made of line segments and cubic Bézier curves.
+#[derive(Clone, Debug)]
+pub struct Path {
+    pub start: Cpx,
+    pub segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Returns the polyline through the given points.
+    pub fn polyline(pts: &[Cpx]) -> Self {
+        Path {
+            start: pts.first().copied().unwrap_or_default(),
+            segments: pts.iter().skip(1).map(|&z| Segment::Line(z)).collect(),
+        }
+    }
+}
+
+/// Every segment is split at least this many times, so that small bumps
+/// in its image are not missed.
+const MIN_DEPTH: usize = 3;
+
+/// A piece of a segment is split at most this many times.  If its image is
+/// still not accurate, the piece passes close to the point that is mapped
+/// to infinity, and the image is broken there.
+const MAX_DEPTH: usize = 20;
+
+/// Evaluates the segment starting at `start` at time `t`.
+fn point(start: Cpx, seg: &Segment, t: f64) -> Cpx {
+    match *seg {
+        Segment::Line(end) => start + t * (end - start),
+        Segment::Cubic(c1, c2, end) => {
+            let s = 1.0 - t;
+            s * s * s * start + 3.0 * s * s * t * c1 + 3.0 * s * t * t * c2 + t * t * t * end
+        }
+    }
+}
+
+/// Builds the image of a path as a list of polylines.
+struct Warper<'a> {
+    m: &'a Matrix2<Cpx>,
+    tolerance: f64,
+    lines: Vec<Vec<Cpx>>,
+}
+
+impl Warper<'_> {
+    /// Adds a point of the image, starting a new polyline if the previous
+    /// point is not connected to it.
+    fn push(&mut self, w: Cpx, connected: bool) {
+        match self.lines.last_mut() {
+            Some(line) if connected && !line.is_empty() => line.push(w),
+            _ => self.lines.push(vec![w]),
+        }
+    }
+    /// Adds the image of the part of the segment between times `t0` and
+    /// `t1`, whose images `w0` and `w1` are known.  The image of `t0` has
+    /// already been added.
+    fn subdivide(
+        &mut self,
+        start: Cpx,
+        seg: &Segment,
+        (t0, w0): (f64, Cpx),
+        (t1, w1): (f64, Cpx),
+        depth: usize,
+    ) {
+        let t = 0.5 * (t0 + t1);
+        let w = mobius(self.m, point(start, seg, t));
+        let finite = w0.is_finite() && w1.is_finite() && w.is_finite();
+        let accurate = finite && (w - 0.5 * (w0 + w1)).norm() <= self.tolerance;
+        if depth >= MAX_DEPTH || (depth >= MIN_DEPTH && accurate) {
+            let connected = accurate || (finite && (w1 - w0).norm() <= self.tolerance);
+            if w1.is_finite() {
+                self.push(w1, connected);
+            }
+            return;
+        }
+        self.subdivide(start, seg, (t0, w0), (t, w), depth + 1);
+        self.subdivide(start, seg, (t, w), (t1, w1), depth + 1);
+    }
+}
+
+/// Returns the image of the path under the Möbius transformation with
+/// matrix `m`, as a list of polylines.  Each point of a polyline is within
+/// about `tolerance` of the true image.  The image is broken into several
+/// polylines where the path passes through the point that is mapped to
+/// infinity.
+pub fn warp(path: &Path, m: &Matrix2<Cpx>, tolerance: f64) -> Vec<Vec<Cpx>> {
+    let mut warper = Warper {
+        m,
+        tolerance,
+        lines: Vec::new(),
+    };
+    // The images of the ends of the segments are all found at once, and
+    // only the points between them are mapped one at a time.
+    let ends: Vec<Cpx> = std::iter::once(path.start)
+        .chain(path.segments.iter().map(Segment::end))
+        .collect();
+    let mut images = ends.clone();
+    apply_all(m, &mut images);
+    if images[0].is_finite() {
+        warper.push(images[0], false);
+    }
+    for (k, seg) in path.segments.iter().enumerate() {
+        let (start, w0, w1) = (ends[k], images[k], images[k + 1]);
+        warper.subdivide(start, seg, (0.0, w0), (1.0, w1), 0);
+    }
+    warper.lines.retain(|line| line.len() >= 2);
+    warper.lines
+}
+
+/// Returns the images of the path under the identity and under every word
+/// whose circle, as in `CircleQueue::words`, has radius at least
+/// `min_radius`.  The group is explored in the same order as
+/// `generate_circles`, and the tolerance is as in `warp`.
+pub fn warp_orbit(
+    gens: [Generator; 4],
+    path: &Path,
+    min_radius: f64,
+    tolerance: f64,
+) -> Result<Vec<Vec<Cpx>>, Error> {
+    let mut queue = CircleQueue::new(gens)?;
+    let mut lines = warp(path, &Matrix2::identity(), tolerance);
+    while !queue.is_empty() && queue.max_radius() >= min_radius {
+        let element = queue.expand(|_| ())?;
+        lines.extend(warp(path, &element.matrix, tolerance));
+    }
+    Ok(lines)
+}
@@ -1,4 +1,4 @@
-use crate::Cpx;
+use crate::{Cpx, Error};
 use ordered_float::NotNan;
 
 #[derive(Clone, Copy)]
@@ -9,16 +9,75 @@ pub struct CoordTransform {
 }
 
 impl CoordTransform {
+    /// Returns a transform taking `center` to the middle of a `width` by
+    /// `height` window, with `scale` pixels per unit length.
+    pub fn centered(center: Cpx, scale: f64, width: usize, height: usize) -> Self {
+        let xoff = center.re - 0.5 * width as f64 / scale;
+        let yoff = center.im - 0.5 * height as f64 / scale;
+        CoordTransform { scale, xoff, yoff }
+    }
+    /// Returns the point that is mapped to the middle of a `width` by
+    /// `height` window.
+    pub fn center(&self, width: usize, height: usize) -> Cpx {
+        Cpx::new(
+            self.xoff + 0.5 * width as f64 / self.scale,
+            self.yoff + 0.5 * height as f64 / self.scale,
+        )
+    }
     pub fn apply(&self, pt: &Cpx) -> (usize, usize) {
         let x = (self.scale * (pt.re - self.xoff)) as usize;
         let y = (self.scale * (pt.im - self.yoff)) as usize;
         (x, y)
     }
+    /// Like `apply`, but returns `None` if the point lies outside of a
+    /// `width` by `height` window.
+    pub fn apply_within(&self, pt: &Cpx, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (x, y) = self.map(pt);
+        if x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64 {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+    /// Like `apply`, but without rounding to integer coordinates.
+    #[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+    pub fn map(&self, pt: &Cpx) -> (f64, f64) {
+        let x = self.scale * (pt.re - self.xoff);
+        let y = self.scale * (pt.im - self.yoff);
+        (x, y)
+    }
+    /// Like `apply`, but without rounding to integer coordinates.  Both
+    /// coordinates are transformed at once using SIMD instructions.
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    pub fn map(&self, pt: &Cpx) -> (f64, f64) {
+        use core::arch::wasm32::*;
+        let v = f64x2_mul(
+            f64x2_sub(f64x2(pt.re, pt.im), f64x2(self.xoff, self.yoff)),
+            f64x2_splat(self.scale),
+        );
+        (f64x2_extract_lane::<0>(v), f64x2_extract_lane::<1>(v))
+    }
+    /// Returns the point that `map` takes to `(x, y)`.
+    pub fn inverse(&self, x: f64, y: f64) -> Cpx {
+        Cpx::new(self.xoff + x / self.scale, self.yoff + y / self.scale)
+    }
+    /// The number of output units per unit distance in the complex plane.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
 }
 
-pub fn window_transform(pts: &[Cpx], width: usize, height: usize) -> CoordTransform {
-    let w = width as f64;
-    let h = height as f64;
+pub fn window_transform(pts: &[Cpx], width: usize, height: usize) -> Result<CoordTransform, Error> {
+    fit_transform(pts, width as f64, height as f64)
+}
+
+/// Returns a transform that fits the points into a `w` by `h` rectangle.
+/// Points that are not finite are ignored.
+pub fn fit_transform(pts: &[Cpx], w: f64, h: f64) -> Result<CoordTransform, Error> {
+    let pts: Vec<Cpx> = pts.iter().copied().filter(|z| z.is_finite()).collect();
+    if pts.is_empty() {
+        return Err(Error::NoFinitePoints);
+    }
     let p_xmin = *pts
         .iter()
         .map(|z| NotNan::new(z.re).unwrap())
@@ -42,5 +101,5 @@ pub fn window_transform(pts: &[Cpx], width: usize, height: usize) -> CoordTransf
     let scale = f64::min(w / (p_xmax - p_xmin), h / (p_ymax - p_ymin)) * 0.999;
     let xoff = 0.5 * (p_xmin + p_xmax - w / scale);
     let yoff = 0.5 * (p_ymin + p_ymax - h / scale);
-    CoordTransform { scale, xoff, yoff }
+    Ok(CoordTransform { scale, xoff, yoff })
 }